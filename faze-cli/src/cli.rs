@@ -1,5 +1,9 @@
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
+use faze_collector::convert::tail::LineFormat;
+use faze_collector::scrape::{ScrapeTarget, parse_interval};
+use faze_collector::tail::parse_pattern;
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Parser)]
 #[command(name = "faze")]
@@ -25,6 +29,32 @@ pub enum Commands {
         /// Custom database file path (auto-detected by default)
         #[arg(long)]
         db_path: Option<PathBuf>,
+
+        /// Export batches that can wait to be written before receivers ask clients to retry
+        #[arg(long, default_value_t = faze_collector::DEFAULT_QUEUE_DEPTH)]
+        ingest_queue_depth: usize,
+
+        #[command(flatten)]
+        receivers: ReceiverArgs,
+    },
+
+    /// Run a command with OTel pointed at faze, capturing its output as logs
+    Run {
+        /// Service name for the command's telemetry (defaults to the program name)
+        #[arg(long)]
+        service: Option<String>,
+
+        /// OTLP/HTTP endpoint the command's SDK exports to
+        #[arg(long, default_value = "http://localhost:4318")]
+        endpoint: String,
+
+        /// Custom database file path (auto-detected by default)
+        #[arg(long)]
+        db_path: Option<PathBuf>,
+
+        /// Command to run and its arguments, after `--`
+        #[arg(last = true, required = true, value_name = "COMMAND")]
+        command: Vec<String>,
     },
 
     /// Query traces
@@ -64,3 +94,59 @@ pub enum Commands {
     /// Open TUI
     Tui,
 }
+
+/// Optional receivers for non-OTLP protocols, all disabled by default
+#[derive(Args, Debug, Clone, Default)]
+pub struct ReceiverArgs {
+    /// Accept Zipkin v2 JSON spans on `/api/v2/spans` [default port: 9411]
+    #[arg(long, value_name = "PORT", num_args = 0..=1, default_missing_value = "9411")]
+    pub zipkin: Option<u16>,
+
+    /// Accept Jaeger spans over gRPC (`api_v2.CollectorService`) [default port: 14250]
+    #[arg(long, value_name = "PORT", num_args = 0..=1, default_missing_value = "14250")]
+    pub jaeger_grpc: Option<u16>,
+
+    /// Accept Jaeger Thrift spans over HTTP on `/api/traces` [default port: 14268]
+    #[arg(long, value_name = "PORT", num_args = 0..=1, default_missing_value = "14268")]
+    pub jaeger_thrift_http: Option<u16>,
+
+    /// Accept Prometheus remote-write requests on `/api/v1/write` [default port: 9201]
+    #[arg(long, value_name = "PORT", num_args = 0..=1, default_missing_value = "9201")]
+    pub prometheus_remote_write: Option<u16>,
+
+    /// Accept Loki pushes on `/loki/api/v1/push` [default port: 3100]
+    #[arg(long, value_name = "PORT", num_args = 0..=1, default_missing_value = "3100")]
+    pub loki: Option<u16>,
+
+    /// Accept Fluent Forward logs over TCP, e.g. from Docker's fluentd driver [default port: 24224]
+    #[arg(long, value_name = "PORT", num_args = 0..=1, default_missing_value = "24224")]
+    pub fluent: Option<u16>,
+
+    /// Accept RFC 5424 and RFC 3164 syslog messages over UDP and TCP [default port: 5514]
+    #[arg(long, value_name = "PORT", num_args = 0..=1, default_missing_value = "5514")]
+    pub syslog: Option<u16>,
+
+    /// Accept StatsD and DogStatsD metrics over UDP [default port: 8125]
+    #[arg(long, value_name = "PORT", num_args = 0..=1, default_missing_value = "8125")]
+    pub statsd: Option<u16>,
+
+    /// How often aggregated StatsD metrics are written
+    #[arg(long, value_name = "INTERVAL", default_value = "10s", value_parser = parse_interval)]
+    pub statsd_flush_interval: Duration,
+
+    /// Scrape a Prometheus `/metrics` endpoint, optionally with its own interval (repeatable)
+    #[arg(long = "scrape", value_name = "URL[,INTERVAL]")]
+    pub scrape_targets: Vec<ScrapeTarget>,
+
+    /// Interval for scrape targets that don't set their own
+    #[arg(long, value_name = "INTERVAL", default_value = "15s", value_parser = parse_interval)]
+    pub scrape_interval: Duration,
+
+    /// Follow log files matching a glob pattern (repeatable)
+    #[arg(long = "tail", value_name = "GLOB", value_parser = parse_pattern)]
+    pub tail_patterns: Vec<String>,
+
+    /// How tailed lines are parsed: auto, json, logfmt or regex:PATTERN with named captures
+    #[arg(long, value_name = "FORMAT", default_value = "auto")]
+    pub tail_format: LineFormat,
+}
@@ -1,6 +1,7 @@
 pub mod clean;
 pub mod info;
 pub mod logs;
+pub mod run;
 pub mod serve;
 pub mod traces;
 pub mod tui;
@@ -13,7 +14,15 @@ pub async fn dispatch(cli: Cli) -> Result<(), Box<dyn std::error::Error>> {
             port,
             grpc_port,
             db_path,
-        } => serve::run(port, grpc_port, db_path).await,
+            ingest_queue_depth,
+            receivers,
+        } => serve::run(port, grpc_port, db_path, ingest_queue_depth, receivers).await,
+        Commands::Run {
+            service,
+            endpoint,
+            db_path,
+            command,
+        } => run::run(command, service, endpoint, db_path).await,
         Commands::Traces { slow, db_path } => traces::run(slow, db_path).await,
         Commands::Logs { service, db_path } => logs::run(service, db_path).await,
         Commands::Clean { db_path, all } => clean::run(db_path, all).await,
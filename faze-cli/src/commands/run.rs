@@ -0,0 +1,242 @@
+use colored::*;
+use faze::Storage;
+use faze::models::{Attributes, Log, Resource, Span, SpanKind, Status, StatusCode};
+use faze_collector::convert::tail::{LineFormat, parse_line};
+use faze_collector::ingest::log_failures;
+use faze_collector::{DEFAULT_QUEUE_DEPTH, IngestQueue, now_unix_nano};
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::mpsc;
+
+/// Most captured lines written in one batch
+const MAX_BATCH_SIZE: usize = 512;
+
+/// A captured line, the stream it was written to and when it was read
+type Line = (&'static str, String, i64);
+
+/// Random hex ID of `len` bytes, for the run's trace and span
+fn random_id(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    getrandom::fill(&mut bytes).expect("system random source unavailable");
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Exit code to leave with, following the shell convention of 128 + signal for killed children
+fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+        return 128 + signal;
+    }
+    status.code().unwrap_or(1)
+}
+
+/// Copy a child's output through to ours, sending each line on to be stored
+async fn capture<R, W>(
+    reader: R,
+    mut passthrough: W,
+    stream: &'static str,
+    lines: mpsc::UnboundedSender<Line>,
+) -> std::io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf).await? == 0 {
+            return Ok(());
+        }
+        passthrough.write_all(&buf).await?;
+        passthrough.flush().await?;
+
+        let line = String::from_utf8_lossy(&buf);
+        let line = line.trim_end_matches(['\r', '\n']);
+        if !line.trim().is_empty() {
+            // The receiving end only goes away once the child is done
+            let _ = lines.send((stream, line.to_string(), now_unix_nano()));
+        }
+    }
+}
+
+/// Store captured lines in batches as they arrive, until every sender is gone, returning how
+/// many were stored
+async fn store_lines(
+    mut lines: mpsc::UnboundedReceiver<Line>,
+    queue: IngestQueue,
+    service_name: String,
+    trace_id: String,
+    span_id: String,
+) -> usize {
+    let resource = Resource::for_service(&service_name);
+    let mut stored = 0;
+
+    while let Some(line) = lines.recv().await {
+        let mut batch = vec![line];
+        while batch.len() < MAX_BATCH_SIZE
+            && let Ok(line) = lines.try_recv()
+        {
+            batch.push(line);
+        }
+
+        let logs: Vec<Log> = batch
+            .into_iter()
+            .map(|(stream, line, received_unix_nano)| {
+                let mut log = parse_line(
+                    &LineFormat::Auto,
+                    &line,
+                    Some(&service_name),
+                    received_unix_nano,
+                );
+                log.attributes.insert("log.iostream", stream);
+                Log {
+                    service_name: Some(service_name.clone()),
+                    trace_id: log.trace_id.or_else(|| Some(trace_id.clone())),
+                    span_id: log.span_id.or_else(|| Some(span_id.clone())),
+                    ..log
+                }
+                .with_resource(resource.clone())
+            })
+            .collect();
+
+        match queue.submit_logs(logs).await {
+            Ok((logs, outcome)) => {
+                log_failures("log", &logs, &outcome, |log| &log.body);
+                stored += outcome.stored;
+            }
+            Err(e) => tracing::error!("Failed to write captured output: {}", e),
+        }
+    }
+    stored
+}
+
+pub async fn run(
+    command: Vec<String>,
+    service: Option<String>,
+    endpoint: String,
+    db_path: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let storage = if let Some(path) = db_path {
+        Storage::new_with_path(&path)?
+    } else {
+        Storage::new()?
+    };
+    let queue = IngestQueue::spawn(storage, DEFAULT_QUEUE_DEPTH);
+
+    let (program, args) = command.split_first().ok_or("no command given")?;
+    let program_name = Path::new(program).file_name().map_or_else(
+        || program.clone(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    let service_name = service
+        .or_else(|| std::env::var("OTEL_SERVICE_NAME").ok())
+        .unwrap_or_else(|| program_name.clone());
+    let trace_id = random_id(16);
+    let span_id = random_id(8);
+
+    let mut child_command = Command::new(program);
+    child_command
+        .args(args)
+        .env("OTEL_SERVICE_NAME", &service_name)
+        // Lets SDKs that read context from the environment parent their spans under the run
+        .env("TRACEPARENT", format!("00-{trace_id}-{span_id}-01"))
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    // Settings the caller already made are left alone
+    for (name, value) in [
+        ("OTEL_EXPORTER_OTLP_ENDPOINT", endpoint.as_str()),
+        ("OTEL_EXPORTER_OTLP_PROTOCOL", "http/protobuf"),
+        ("OTEL_TRACES_EXPORTER", "otlp"),
+        ("OTEL_METRICS_EXPORTER", "otlp"),
+        ("OTEL_LOGS_EXPORTER", "otlp"),
+    ] {
+        if std::env::var_os(name).is_none() {
+            child_command.env(name, value);
+        }
+    }
+
+    let start_time_unix_nano = now_unix_nano();
+    let mut child = child_command
+        .spawn()
+        .map_err(|e| format!("failed to start {}: {}", program, e))?;
+    let pid = child.id();
+
+    // Ctrl-C reaches the child from the terminal too; outlive it so its exit is still recorded
+    tokio::spawn(async { while tokio::signal::ctrl_c().await.is_ok() {} });
+
+    let (lines_tx, lines_rx) = mpsc::unbounded_channel();
+    let store_task = tokio::spawn(store_lines(
+        lines_rx,
+        queue.clone(),
+        service_name.clone(),
+        trace_id.clone(),
+        span_id.clone(),
+    ));
+    let stdout = child.stdout.take().expect("stdout is piped");
+    let stderr = child.stderr.take().expect("stderr is piped");
+    let stdout_task = tokio::spawn(capture(
+        stdout,
+        tokio::io::stdout(),
+        "stdout",
+        lines_tx.clone(),
+    ));
+    let stderr_task = tokio::spawn(capture(stderr, tokio::io::stderr(), "stderr", lines_tx));
+
+    let status = child.wait().await?;
+    let end_time_unix_nano = now_unix_nano();
+    for task in [stdout_task, stderr_task] {
+        if let Err(e) = task.await? {
+            tracing::warn!("Failed to capture output of {}: {}", program, e);
+        }
+    }
+    let stored = store_task.await?;
+
+    let code = exit_code(status);
+    let mut attributes = Attributes::new();
+    attributes.insert("process.command_line", command.join(" "));
+    attributes.insert("process.executable.name", program_name.clone());
+    attributes.insert("process.exit.code", i64::from(code));
+    if let Some(pid) = pid {
+        attributes.insert("process.pid", i64::from(pid));
+    }
+    let span_status = if status.success() {
+        Status::ok()
+    } else {
+        Status {
+            code: StatusCode::Error,
+            message: Some(format!("{} exited with {}", program_name, status)),
+        }
+    };
+    let span = Span::new(
+        span_id,
+        trace_id.clone(),
+        None,
+        program_name,
+        SpanKind::Internal,
+        start_time_unix_nano,
+        end_time_unix_nano,
+        attributes,
+        span_status,
+        Some(service_name.clone()),
+    )
+    .with_resource(Resource::for_service(&service_name));
+    let duration_ms = span.duration_ms();
+    queue.submit_spans(vec![span]).await?;
+
+    eprintln!(
+        "\n{} {} {}",
+        "faze".bright_cyan().bold(),
+        format!(
+            "{} exited with code {} after {:.0}ms, {} line(s) captured",
+            service_name, code, duration_ms, stored
+        )
+        .dimmed(),
+        format!("trace {}", trace_id).dimmed()
+    );
+
+    std::process::exit(code);
+}
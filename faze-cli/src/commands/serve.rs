@@ -1,24 +1,26 @@
+use crate::cli::ReceiverArgs;
 use colored::*;
 use faze::{Storage, detect_project_root, get_project_db_path};
-use faze_collector::grpc::{logs, metrics, traces};
+use faze_collector::IngestQueue;
+use faze_collector::grpc::{jaeger, logs, metrics, traces};
 use std::path::PathBuf;
 
 pub async fn run(
     port: u16,
     grpc_port: u16,
     db_path: Option<PathBuf>,
+    ingest_queue_depth: usize,
+    receivers: ReceiverArgs,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use std::sync::Arc;
-
-    let (storage, project_root, db_path_display) = if let Some(path) = db_path {
+    let (storage, project_root, db_path) = if let Some(path) = db_path {
         let storage = Storage::new_with_path(&path)?;
         let project_root = detect_project_root();
-        (storage, project_root, path.display().to_string())
+        (storage, project_root, path)
     } else {
         let detected_path = get_project_db_path()?;
         let project_root = detect_project_root();
         let storage = Storage::new()?;
-        (storage, project_root, detected_path.display().to_string())
+        (storage, project_root, detected_path)
     };
 
     println!("\n{}", "Starting Faze".bright_cyan().bold());
@@ -26,18 +28,19 @@ pub async fn run(
         "  Project:  {}",
         project_root.display().to_string().bright_white()
     );
-    println!("  Database: {}", db_path_display.dimmed());
+    println!("  Database: {}", db_path.display().to_string().dimmed());
     println!("  Storage:  {}", "ready".green());
 
-    let storage_arc = Arc::new(storage.clone());
+    // Every receiver writes through one queue, drained by a single writer thread
+    let ingest_queue = IngestQueue::spawn(storage.clone(), ingest_queue_depth);
 
-    let spans_collector = traces::OtlpSpansCollector::new(storage.clone());
+    let spans_collector = traces::OtlpSpansCollector::with_queue(ingest_queue.clone());
     let spans_grpc_service = spans_collector.into_service();
 
-    let logs_collector = logs::OtlpLogsCollector::new(storage.clone());
+    let logs_collector = logs::OtlpLogsCollector::with_queue(ingest_queue.clone());
     let logs_grpc_service = logs_collector.into_service();
 
-    let metrics_collector = metrics::OtlpMetricsCollector::new(storage.clone());
+    let metrics_collector = metrics::OtlpMetricsCollector::with_queue(ingest_queue.clone());
     let metrics_grpc_service = metrics_collector.into_service();
 
     let grpc_addr = format!("0.0.0.0:{}", grpc_port).parse()?;
@@ -47,7 +50,7 @@ pub async fn run(
         .add_service(metrics_grpc_service)
         .serve(grpc_addr);
 
-    let http_collector_router = faze_collector::create_router(storage_arc);
+    let http_collector_router = faze_collector::create_router_with_queue(ingest_queue.clone());
     let http_collector_addr = "0.0.0.0:4318";
 
     let http_collector_listener = tokio::net::TcpListener::bind(http_collector_addr).await?;
@@ -69,6 +72,121 @@ pub async fn run(
     println!("  OTLP HTTP  {}", "0.0.0.0:4318".cyan());
     println!("  API Server {}", format!("0.0.0.0:{}", port).cyan());
 
+    if let Some(zipkin_port) = receivers.zipkin {
+        let router = faze_collector::zipkin::create_router(ingest_queue.clone());
+        spawn_receiver("Zipkin", zipkin_port, router).await?;
+        println!("  Zipkin     {}", format!("0.0.0.0:{}", zipkin_port).cyan());
+    }
+
+    if let Some(jaeger_grpc_port) = receivers.jaeger_grpc {
+        let service = jaeger::JaegerCollector::with_queue(ingest_queue.clone()).into_service();
+        let incoming = tonic::transport::server::TcpIncoming::bind(
+            format!("0.0.0.0:{}", jaeger_grpc_port).parse()?,
+        )?;
+        tokio::spawn(async move {
+            let server = tonic::transport::Server::builder()
+                .add_service(service)
+                .serve_with_incoming(incoming);
+            if let Err(e) = server.await {
+                tracing::error!("Jaeger gRPC receiver error: {}", e);
+            }
+        });
+        println!(
+            "  Jaeger     {} {}",
+            format!("0.0.0.0:{}", jaeger_grpc_port).cyan(),
+            "gRPC".dimmed()
+        );
+    }
+
+    if let Some(jaeger_http_port) = receivers.jaeger_thrift_http {
+        let router = faze_collector::jaeger::create_router(ingest_queue.clone());
+        spawn_receiver("Jaeger", jaeger_http_port, router).await?;
+        println!(
+            "  Jaeger     {} {}",
+            format!("0.0.0.0:{}", jaeger_http_port).cyan(),
+            "Thrift HTTP".dimmed()
+        );
+    }
+
+    if let Some(remote_write_port) = receivers.prometheus_remote_write {
+        let router = faze_collector::prometheus::create_router(ingest_queue.clone());
+        spawn_receiver("Prometheus", remote_write_port, router).await?;
+        println!(
+            "  Prometheus {} {}",
+            format!("0.0.0.0:{}", remote_write_port).cyan(),
+            "remote-write".dimmed()
+        );
+    }
+
+    if let Some(loki_port) = receivers.loki {
+        let router = faze_collector::loki::create_router(ingest_queue.clone());
+        spawn_receiver("Loki", loki_port, router).await?;
+        println!("  Loki       {}", format!("0.0.0.0:{}", loki_port).cyan());
+    }
+
+    if let Some(fluent_port) = receivers.fluent {
+        let listener = tokio::net::TcpListener::bind(("0.0.0.0", fluent_port)).await?;
+        faze_collector::fluent::spawn_fluent(listener, ingest_queue.clone());
+        println!(
+            "  Fluent     {} {}",
+            format!("0.0.0.0:{}", fluent_port).cyan(),
+            "Forward".dimmed()
+        );
+    }
+
+    if let Some(syslog_port) = receivers.syslog {
+        let socket = tokio::net::UdpSocket::bind(("0.0.0.0", syslog_port)).await?;
+        let listener = tokio::net::TcpListener::bind(("0.0.0.0", syslog_port)).await?;
+        faze_collector::syslog::spawn_syslog_udp(socket, ingest_queue.clone());
+        faze_collector::syslog::spawn_syslog_tcp(listener, ingest_queue.clone());
+        println!(
+            "  Syslog     {} {}",
+            format!("0.0.0.0:{}", syslog_port).cyan(),
+            "UDP/TCP".dimmed()
+        );
+    }
+
+    if let Some(statsd_port) = receivers.statsd {
+        let socket = tokio::net::UdpSocket::bind(("0.0.0.0", statsd_port)).await?;
+        faze_collector::statsd::spawn_statsd(
+            socket,
+            receivers.statsd_flush_interval,
+            ingest_queue.clone(),
+        );
+        println!(
+            "  StatsD     {} {}",
+            format!("0.0.0.0:{}", statsd_port).cyan(),
+            "UDP".dimmed()
+        );
+    }
+
+    for target in receivers.scrape_targets {
+        let interval = target.interval.unwrap_or(receivers.scrape_interval);
+        println!(
+            "  Scrape     {} {}",
+            target.to_string().cyan(),
+            format!("every {:?}", interval).dimmed()
+        );
+        faze_collector::scrape::spawn_scraper(target, interval, ingest_queue.clone());
+    }
+
+    if !receivers.tail_patterns.is_empty() {
+        for pattern in &receivers.tail_patterns {
+            println!(
+                "  Tail       {} {}",
+                pattern.cyan(),
+                receivers.tail_format.to_string().dimmed()
+            );
+        }
+        let source = faze_collector::tail::TailSource {
+            patterns: receivers.tail_patterns,
+            format: receivers.tail_format,
+            // Kept beside the database, so each project resumes its own files
+            checkpoint_path: Some(db_path.with_extension("tail.json")),
+        };
+        faze_collector::tail::spawn_tail(source, ingest_queue.clone());
+    }
+
     println!("\n{}", "Ready".green().bold());
     println!(
         "  Web UI    {}",
@@ -106,3 +224,19 @@ pub async fn run(
 
     Ok(())
 }
+
+/// Serve an optional receiver in the background
+async fn spawn_receiver(
+    name: &'static str,
+    port: u16,
+    router: axum::Router,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let listener = tokio::net::TcpListener::bind(("0.0.0.0", port)).await?;
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router).await {
+            tracing::error!("{} receiver error: {}", name, e);
+        }
+    });
+
+    Ok(())
+}
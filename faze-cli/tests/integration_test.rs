@@ -272,3 +272,52 @@ async fn test_storage_logs() {
     let count = storage.count_logs().unwrap();
     assert_eq!(count, 2);
 }
+
+/// Test that `faze run` injects the OTel environment and records output and exit as telemetry
+#[cfg(unix)]
+#[test]
+fn test_run_captures_output_and_exit() {
+    let dir = tempfile::tempdir().unwrap();
+    let db_path = dir.path().join("run.db");
+
+    let output = std::process::Command::new(env!("CARGO_BIN_EXE_faze"))
+        .args(["run", "--service", "demo", "--db-path"])
+        .arg(&db_path)
+        .args([
+            "--",
+            "sh",
+            "-c",
+            "echo \"level=warn msg=hello\"; echo oops >&2; echo oops >&2; echo $OTEL_SERVICE_NAME; exit 3",
+        ])
+        .env_remove("OTEL_EXPORTER_OTLP_ENDPOINT")
+        .output()
+        .unwrap();
+
+    assert_eq!(output.status.code(), Some(3));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("level=warn msg=hello"));
+    assert!(stdout.contains("demo"));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("4 line(s) captured"));
+
+    let storage = Storage::new_with_path(&db_path).unwrap();
+    let logs = storage.list_logs(Some("demo"), Some(10)).unwrap();
+    // Repeated lines are each kept
+    assert_eq!(logs.len(), 4);
+    let warn = logs.iter().find(|log| log.body == "hello").unwrap();
+    assert_eq!(warn.severity_level, faze::models::SeverityLevel::Warn);
+    let oops = logs.iter().find(|log| log.body == "oops").unwrap();
+    assert_eq!(oops.attributes.get_string("log.iostream"), Some("stderr"));
+
+    let trace = storage
+        .get_trace_by_id(oops.trace_id.as_deref().unwrap())
+        .unwrap();
+    assert_eq!(trace.spans.len(), 1);
+    let span = &trace.spans[0];
+    assert_eq!(span.name, "sh");
+    assert_eq!(span.service_name.as_deref(), Some("demo"));
+    assert_eq!(span.status.code, faze::models::StatusCode::Error);
+    assert_eq!(
+        span.attributes.get("process.exit.code"),
+        Some(&faze::models::AttributeValue::Int(3))
+    );
+}
@@ -1,14 +1,149 @@
+/// Trace and span IDs, which the OTLP JSON mapping encodes as hex strings
+const HEX_ID_FIELDS: &[&str] = &[
+    ".opentelemetry.proto.trace.v1.Span.trace_id",
+    ".opentelemetry.proto.trace.v1.Span.span_id",
+    ".opentelemetry.proto.trace.v1.Span.parent_span_id",
+    ".opentelemetry.proto.trace.v1.Link.trace_id",
+    ".opentelemetry.proto.trace.v1.Link.span_id",
+    ".opentelemetry.proto.logs.v1.LogRecord.trace_id",
+    ".opentelemetry.proto.logs.v1.LogRecord.span_id",
+    ".opentelemetry.proto.metrics.v1.Exemplar.trace_id",
+    ".opentelemetry.proto.metrics.v1.Exemplar.span_id",
+];
+
+/// 64-bit unsigned integers, which the proto3 JSON mapping encodes as strings
+const U64_FIELDS: &[&str] = &[
+    ".opentelemetry.proto.trace.v1.Span.start_time_unix_nano",
+    ".opentelemetry.proto.trace.v1.Span.end_time_unix_nano",
+    ".opentelemetry.proto.trace.v1.Event.time_unix_nano",
+    ".opentelemetry.proto.logs.v1.LogRecord.time_unix_nano",
+    ".opentelemetry.proto.logs.v1.LogRecord.observed_time_unix_nano",
+    ".opentelemetry.proto.metrics.v1.NumberDataPoint.start_time_unix_nano",
+    ".opentelemetry.proto.metrics.v1.NumberDataPoint.time_unix_nano",
+    ".opentelemetry.proto.metrics.v1.HistogramDataPoint.start_time_unix_nano",
+    ".opentelemetry.proto.metrics.v1.HistogramDataPoint.time_unix_nano",
+    ".opentelemetry.proto.metrics.v1.HistogramDataPoint.count",
+    ".opentelemetry.proto.metrics.v1.ExponentialHistogramDataPoint.start_time_unix_nano",
+    ".opentelemetry.proto.metrics.v1.ExponentialHistogramDataPoint.time_unix_nano",
+    ".opentelemetry.proto.metrics.v1.ExponentialHistogramDataPoint.count",
+    ".opentelemetry.proto.metrics.v1.ExponentialHistogramDataPoint.zero_count",
+    ".opentelemetry.proto.metrics.v1.SummaryDataPoint.start_time_unix_nano",
+    ".opentelemetry.proto.metrics.v1.SummaryDataPoint.time_unix_nano",
+    ".opentelemetry.proto.metrics.v1.SummaryDataPoint.count",
+    ".opentelemetry.proto.metrics.v1.Exemplar.time_unix_nano",
+];
+
+/// Repeated 64-bit unsigned integers
+const U64_LIST_FIELDS: &[&str] = &[
+    ".opentelemetry.proto.metrics.v1.HistogramDataPoint.bucket_counts",
+    ".opentelemetry.proto.metrics.v1.ExponentialHistogramDataPoint.Buckets.bucket_counts",
+];
+
+/// 64-bit signed integers, which the proto3 JSON mapping encodes as strings
+const I64_FIELDS: &[&str] = &[
+    ".opentelemetry.proto.common.v1.AnyValue.value.int_value",
+    ".opentelemetry.proto.metrics.v1.NumberDataPoint.value.as_int",
+    ".opentelemetry.proto.metrics.v1.Exemplar.value.as_int",
+    ".opentelemetry.proto.collector.trace.v1.ExportTracePartialSuccess.rejected_spans",
+    ".opentelemetry.proto.collector.logs.v1.ExportLogsPartialSuccess.rejected_log_records",
+    ".opentelemetry.proto.collector.metrics.v1.ExportMetricsPartialSuccess.rejected_data_points",
+];
+
+/// Enum fields paired with the deserializer that accepts their integer or name form
+const ENUM_FIELDS: &[(&str, &str)] = &[
+    (".opentelemetry.proto.trace.v1.Span.kind", "span_kind"),
+    (".opentelemetry.proto.trace.v1.Status.code", "status_code"),
+    (
+        ".opentelemetry.proto.logs.v1.LogRecord.severity_number",
+        "severity_number",
+    ),
+    (
+        ".opentelemetry.proto.metrics.v1.Sum.aggregation_temporality",
+        "aggregation_temporality",
+    ),
+    (
+        ".opentelemetry.proto.metrics.v1.Histogram.aggregation_temporality",
+        "aggregation_temporality",
+    ),
+    (
+        ".opentelemetry.proto.metrics.v1.ExponentialHistogram.aggregation_temporality",
+        "aggregation_temporality",
+    ),
+];
+
+/// Oneofs, which the JSON mapping inlines into their parent message
+const ONEOF_FIELDS: &[&str] = &[
+    ".opentelemetry.proto.common.v1.AnyValue.value",
+    ".opentelemetry.proto.metrics.v1.Metric.data",
+    ".opentelemetry.proto.metrics.v1.NumberDataPoint.value",
+    ".opentelemetry.proto.metrics.v1.Exemplar.value",
+];
+
+/// Optional response fields that are omitted rather than serialized as null
+const OPTIONAL_FIELDS: &[&str] = &[
+    ".opentelemetry.proto.collector.trace.v1.ExportTraceServiceResponse.partial_success",
+    ".opentelemetry.proto.collector.logs.v1.ExportLogsServiceResponse.partial_success",
+    ".opentelemetry.proto.collector.metrics.v1.ExportMetricsServiceResponse.partial_success",
+];
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tonic_prost_build::configure()
+    let mut builder = tonic_prost_build::configure()
         .build_server(true)
         .build_client(true)
-        .compile_protos(
-            &[
-                "proto/opentelemetry/proto/collector/trace/v1/trace_service.proto",
-                "proto/opentelemetry/proto/collector/logs/v1/logs_service.proto",
-                "proto/opentelemetry/proto/collector/metrics/v1/metrics_service.proto",
-            ],
-            &["proto"],
-        )?;
+        .message_attribute(
+            ".opentelemetry.proto",
+            "#[derive(serde::Serialize, serde::Deserialize)]",
+        )
+        .message_attribute(
+            ".opentelemetry.proto",
+            "#[serde(rename_all = \"camelCase\", default)]",
+        )
+        .field_attribute(
+            ".opentelemetry.proto.common.v1.AnyValue.value.bytes_value",
+            "#[serde(with = \"crate::json::base64_bytes\")]",
+        );
+
+    for path in ONEOF_FIELDS {
+        // A fully-qualified field path also matches the oneof's variants as a prefix, so the
+        // flatten attribute is registered as a suffix path to keep it on the field alone.
+        builder = builder
+            .enum_attribute(path, "#[derive(serde::Serialize, serde::Deserialize)]")
+            .enum_attribute(path, "#[serde(rename_all = \"camelCase\")]")
+            .field_attribute(path.trim_start_matches('.'), "#[serde(flatten)]");
+    }
+    for path in HEX_ID_FIELDS {
+        builder = builder.field_attribute(path, "#[serde(with = \"crate::json::hex_bytes\")]");
+    }
+    for path in U64_FIELDS {
+        builder = builder.field_attribute(path, "#[serde(with = \"crate::json::u64_string\")]");
+    }
+    for path in U64_LIST_FIELDS {
+        builder = builder.field_attribute(path, "#[serde(with = \"crate::json::u64_list\")]");
+    }
+    for path in I64_FIELDS {
+        builder = builder.field_attribute(path, "#[serde(with = \"crate::json::i64_string\")]");
+    }
+    for (path, deserializer) in ENUM_FIELDS {
+        builder = builder.field_attribute(
+            path,
+            format!("#[serde(deserialize_with = \"crate::json::{deserializer}\")]"),
+        );
+    }
+    for path in OPTIONAL_FIELDS {
+        builder =
+            builder.field_attribute(path, "#[serde(skip_serializing_if = \"Option::is_none\")]");
+    }
+
+    builder.compile_protos(
+        &[
+            "proto/opentelemetry/proto/collector/trace/v1/trace_service.proto",
+            "proto/opentelemetry/proto/collector/logs/v1/logs_service.proto",
+            "proto/opentelemetry/proto/collector/metrics/v1/metrics_service.proto",
+            "proto/jaeger/api_v2/collector.proto",
+            "proto/loki/push.proto",
+            "proto/prometheus/remote.proto",
+        ],
+        &["proto"],
+    )?;
     Ok(())
 }
@@ -0,0 +1,563 @@
+use faze::models::{
+    AggregationTemporality, Attributes, HistogramData, Metric, MetricDataPoint, MetricType,
+    QuantileValue, SummaryData,
+};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+
+/// Malformed line in a Prometheus text exposition payload
+#[derive(Debug, Error, PartialEq)]
+#[error("line {line}: {message}")]
+pub struct ExpositionError {
+    pub line: usize,
+    pub message: String,
+}
+
+/// Metric type declared by a `# TYPE` line
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FamilyType {
+    Counter,
+    Gauge,
+    Histogram,
+    Summary,
+    Untyped,
+}
+
+impl FamilyType {
+    fn parse(value: &str) -> Self {
+        match value {
+            "counter" => Self::Counter,
+            "gauge" => Self::Gauge,
+            "histogram" => Self::Histogram,
+            "summary" => Self::Summary,
+            _ => Self::Untyped,
+        }
+    }
+}
+
+/// Label pairs of a series, in a stable order so series can be grouped by them
+type Labels = Vec<(String, String)>;
+
+struct Sample {
+    name: String,
+    labels: Labels,
+    value: f64,
+    time_unix_nano: Option<i64>,
+}
+
+struct Family {
+    name: String,
+    help: Option<String>,
+    family_type: FamilyType,
+    samples: Vec<Sample>,
+}
+
+impl Family {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            help: None,
+            family_type: FamilyType::Untyped,
+            samples: Vec::new(),
+        }
+    }
+}
+
+/// Families in the order they first appear, looked up by name
+#[derive(Default)]
+struct Families {
+    families: Vec<Family>,
+    index: HashMap<String, usize>,
+}
+
+impl Families {
+    fn get_or_insert(&mut self, name: &str) -> &mut Family {
+        let index = *self.index.entry(name.to_string()).or_insert_with(|| {
+            self.families.push(Family::new(name));
+            self.families.len() - 1
+        });
+        &mut self.families[index]
+    }
+
+    /// Find the family a sample belongs to; histogram and summary samples carry a suffix
+    fn family_for(&mut self, sample_name: &str) -> &mut Family {
+        let aggregate = ["_bucket", "_sum", "_count"]
+            .iter()
+            .filter_map(|suffix| sample_name.strip_suffix(suffix))
+            .find(|family| {
+                self.index.get(*family).is_some_and(|&i| {
+                    matches!(
+                        self.families[i].family_type,
+                        FamilyType::Histogram | FamilyType::Summary
+                    )
+                })
+            })
+            .map(str::to_string);
+
+        self.get_or_insert(aggregate.as_deref().unwrap_or(sample_name))
+    }
+}
+
+/// Read a metric or label name from the start of `input`
+fn split_name(input: &str) -> (&str, &str) {
+    let end = input
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == ':'))
+        .unwrap_or(input.len());
+    input.split_at(end)
+}
+
+/// Read a double-quoted, escaped label value from the start of `input`
+fn split_label_value(input: &str) -> Result<(String, &str), String> {
+    let input = input
+        .strip_prefix('"')
+        .ok_or("expected '\"' before label value")?;
+    let mut value = String::new();
+    let mut chars = input.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &input[i + 1..])),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, escaped)) => value.push(escaped),
+                None => break,
+            },
+            _ => value.push(c),
+        }
+    }
+    Err("unterminated label value".to_string())
+}
+
+/// Parse a `{name="value", ...}` label set up to and including its closing brace, which Loki
+/// also uses for stream labels
+pub(crate) fn parse_labels(mut input: &str) -> Result<(Labels, &str), String> {
+    let mut labels = Labels::new();
+    loop {
+        input = input.trim_start();
+        if let Some(rest) = input.strip_prefix('}') {
+            return Ok((labels, rest));
+        }
+
+        let (name, rest) = split_name(input);
+        if name.is_empty() {
+            return Err("expected label name".to_string());
+        }
+        let rest = rest
+            .trim_start()
+            .strip_prefix('=')
+            .ok_or("expected '=' after label name")?;
+        let (value, rest) = split_label_value(rest.trim_start())?;
+        labels.push((name.to_string(), value));
+
+        input = rest.trim_start();
+        input = input.strip_prefix(',').unwrap_or(input);
+    }
+}
+
+/// Parse a float, including the `+Inf`, `-Inf` and `NaN` spellings the format uses
+fn parse_value(value: &str) -> Result<f64, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid sample value {value:?}"))
+}
+
+fn parse_sample(line: &str) -> Result<Sample, String> {
+    let (name, rest) = split_name(line);
+    if name.is_empty() {
+        return Err("expected metric name".to_string());
+    }
+
+    let (mut labels, rest) = match rest.trim_start().strip_prefix('{') {
+        Some(rest) => parse_labels(rest)?,
+        None => (Labels::new(), rest),
+    };
+    labels.sort();
+
+    let mut fields = rest.split_whitespace();
+    let value = parse_value(fields.next().ok_or("missing sample value")?)?;
+    // Timestamps are in milliseconds, and rejected when they don't fit in nanoseconds
+    let time_unix_nano = fields
+        .next()
+        .map(|ts| {
+            ts.parse::<i64>()
+                .ok()
+                .and_then(|ms| ms.checked_mul(1_000_000))
+                .ok_or_else(|| format!("invalid timestamp {ts:?}"))
+        })
+        .transpose()?;
+
+    Ok(Sample {
+        name: name.to_string(),
+        labels,
+        value,
+        time_unix_nano,
+    })
+}
+
+fn parse_families(text: &str) -> Result<Families, ExpositionError> {
+    let mut families = Families::default();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let error = |message: String| ExpositionError {
+            line: number + 1,
+            message,
+        };
+
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut parts = comment.trim_start().splitn(3, char::is_whitespace);
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some("HELP"), Some(name), help) => {
+                    let help = help.unwrap_or_default().trim();
+                    families.get_or_insert(name).help =
+                        Some(help.replace("\\n", "\n").replace("\\\\", "\\"));
+                }
+                (Some("TYPE"), Some(name), Some(family_type)) => {
+                    families.get_or_insert(name).family_type =
+                        FamilyType::parse(family_type.trim());
+                }
+                // Any other comment is free text
+                _ => {}
+            }
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+
+        let sample = parse_sample(line).map_err(error)?;
+        families.family_for(&sample.name).samples.push(sample);
+    }
+
+    Ok(families)
+}
+
+fn to_attributes(labels: &[(String, String)]) -> Attributes {
+    labels
+        .iter()
+        .map(|(name, value)| (name.clone(), value.clone().into()))
+        .collect()
+}
+
+/// Split off the label that distinguishes the series of one histogram bucket or summary
+/// quantile from another
+fn split_label(labels: &[(String, String)], name: &str) -> (Labels, Option<String>) {
+    let mut rest = Labels::new();
+    let mut found = None;
+    for (label, value) in labels {
+        if label == name {
+            found = Some(value.clone());
+        } else {
+            rest.push((label.clone(), value.clone()));
+        }
+    }
+    (rest, found)
+}
+
+/// Samples of one histogram or summary series, gathered from its suffixed series
+#[derive(Default)]
+struct Aggregate {
+    /// (bound or quantile, value)
+    entries: Vec<(f64, f64)>,
+    sum: Option<f64>,
+    count: Option<f64>,
+    time_unix_nano: Option<i64>,
+}
+
+fn group_aggregates(family: &Family, split_on: &str) -> BTreeMap<Labels, Aggregate> {
+    let mut groups: BTreeMap<Labels, Aggregate> = BTreeMap::new();
+    for sample in &family.samples {
+        let (labels, key) = split_label(&sample.labels, split_on);
+        let group = groups.entry(labels).or_default();
+        group.time_unix_nano = group.time_unix_nano.or(sample.time_unix_nano);
+
+        match sample.name.strip_prefix(&family.name) {
+            Some("_sum") => group.sum = Some(sample.value),
+            Some("_count") => group.count = Some(sample.value),
+            _ => {
+                if let Some(key) = key.as_deref().and_then(|k| parse_value(k).ok()) {
+                    group.entries.push((key, sample.value));
+                }
+            }
+        }
+    }
+    groups
+}
+
+fn histogram_data_points(family: &Family, default_time: i64) -> Vec<MetricDataPoint> {
+    group_aggregates(family, "le")
+        .into_iter()
+        .map(|(labels, mut group)| {
+            // Only +Inf can close the buckets, and a repeated bound keeps its first series, so
+            // there is always one more bucket than bounds
+            group
+                .entries
+                .retain(|&(bound, _)| bound.is_finite() || bound == f64::INFINITY);
+            group.entries.sort_by(|a, b| a.0.total_cmp(&b.0));
+            group.entries.dedup_by(|a, b| a.0 == b.0);
+
+            // Buckets are cumulative, and the +Inf bucket holds the total count
+            let count = group
+                .count
+                .or_else(|| group.entries.last().map(|&(_, c)| c))
+                .unwrap_or_default() as u64;
+            let mut explicit_bounds = Vec::new();
+            let mut bucket_counts = Vec::new();
+            let mut seen = 0u64;
+            for &(bound, cumulative) in &group.entries {
+                let cumulative = cumulative as u64;
+                bucket_counts.push(cumulative.saturating_sub(seen));
+                seen = seen.max(cumulative);
+                if bound.is_finite() {
+                    explicit_bounds.push(bound);
+                }
+            }
+            if bucket_counts.len() == explicit_bounds.len() {
+                bucket_counts.push(count.saturating_sub(seen));
+            }
+
+            MetricDataPoint::new(
+                group.time_unix_nano.unwrap_or(default_time),
+                None,
+                group.sum.unwrap_or(count as f64),
+                to_attributes(&labels),
+            )
+            .with_histogram(HistogramData {
+                count,
+                sum: group.sum,
+                min: None,
+                max: None,
+                explicit_bounds,
+                bucket_counts,
+            })
+        })
+        .collect()
+}
+
+fn summary_data_points(family: &Family, default_time: i64) -> Vec<MetricDataPoint> {
+    group_aggregates(family, "quantile")
+        .into_iter()
+        .map(|(labels, mut group)| {
+            group.entries.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let sum = group.sum.unwrap_or_default();
+            let summary = SummaryData {
+                count: group.count.unwrap_or_default() as u64,
+                sum,
+                // Quantiles of a summary that hasn't observed anything yet are NaN
+                quantile_values: group
+                    .entries
+                    .iter()
+                    .filter(|(_, value)| !value.is_nan())
+                    .map(|&(quantile, value)| QuantileValue { quantile, value })
+                    .collect(),
+            };
+
+            let time = group.time_unix_nano.unwrap_or(default_time);
+            MetricDataPoint::new(time, None, sum, to_attributes(&labels)).with_summary(summary)
+        })
+        .collect()
+}
+
+fn number_data_points(family: &Family, default_time: i64) -> Vec<MetricDataPoint> {
+    family
+        .samples
+        .iter()
+        .map(|sample| {
+            let time = sample.time_unix_nano.unwrap_or(default_time);
+            MetricDataPoint::new(time, None, sample.value, to_attributes(&sample.labels))
+        })
+        .collect()
+}
+
+/// Parse a Prometheus text exposition payload into internal Metrics, one per metric family
+///
+/// Samples without their own timestamp are stamped with `time_unix_nano`, the time of the
+/// scrape.
+pub fn parse_exposition(text: &str, time_unix_nano: i64) -> Result<Vec<Metric>, ExpositionError> {
+    let families = parse_families(text)?;
+
+    let metrics = families
+        .families
+        .into_iter()
+        .filter_map(|family| {
+            let (metric_type, temporality, data_points) = match family.family_type {
+                FamilyType::Counter => (
+                    MetricType::Sum,
+                    AggregationTemporality::Cumulative,
+                    number_data_points(&family, time_unix_nano),
+                ),
+                FamilyType::Gauge | FamilyType::Untyped => (
+                    MetricType::Gauge,
+                    AggregationTemporality::Unspecified,
+                    number_data_points(&family, time_unix_nano),
+                ),
+                FamilyType::Histogram => (
+                    MetricType::Histogram,
+                    AggregationTemporality::Cumulative,
+                    histogram_data_points(&family, time_unix_nano),
+                ),
+                FamilyType::Summary => (
+                    MetricType::Summary,
+                    AggregationTemporality::Unspecified,
+                    summary_data_points(&family, time_unix_nano),
+                ),
+            };
+
+            // Families with only HELP or TYPE lines have nothing to store
+            (!data_points.is_empty()).then(|| {
+                Metric::new(
+                    family.name,
+                    family.help.filter(|help| !help.is_empty()),
+                    None,
+                    metric_type,
+                    temporality,
+                    data_points,
+                    None,
+                )
+            })
+        })
+        .collect();
+
+    Ok(metrics)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRAPE_TIME: i64 = 1_700_000_000_000_000_000;
+
+    #[test]
+    fn test_parse_counter_and_gauge() {
+        let text = r#"
+# HELP http_requests_total The total number of HTTP requests.
+# TYPE http_requests_total counter
+http_requests_total{method="post",code="200"} 1027 1395066363000
+http_requests_total{method="post",code="400"}    3 1395066363000
+
+# A free-form comment
+# TYPE temperature gauge
+temperature{room="kitchen \"north\""} 21.5
+temperature{room="attic"} NaN
+process_start_time_seconds 1.7e+09
+"#;
+        let metrics = parse_exposition(text, SCRAPE_TIME).unwrap();
+
+        assert_eq!(metrics.len(), 3);
+        let requests = &metrics[0];
+        assert_eq!(requests.name, "http_requests_total");
+        assert_eq!(requests.metric_type, MetricType::Sum);
+        assert_eq!(
+            requests.description.as_deref(),
+            Some("The total number of HTTP requests.")
+        );
+        assert_eq!(requests.data_points.len(), 2);
+        assert_eq!(requests.data_points[0].value, 1027.0);
+        assert_eq!(
+            requests.data_points[0].time_unix_nano,
+            1_395_066_363_000_000_000
+        );
+        assert_eq!(
+            requests.data_points[1].attributes.get_string("code"),
+            Some("400")
+        );
+
+        let temperature = &metrics[1];
+        assert_eq!(temperature.metric_type, MetricType::Gauge);
+        assert_eq!(temperature.data_points.len(), 2);
+        assert_eq!(temperature.data_points[0].time_unix_nano, SCRAPE_TIME);
+        // Storage leaves out the NaN point
+        assert!(temperature.data_points[1].value.is_nan());
+        assert_eq!(
+            temperature.data_points[0].attributes.get_string("room"),
+            Some("kitchen \"north\"")
+        );
+
+        // Series without a TYPE line are untyped, which is stored as a gauge
+        assert_eq!(metrics[2].name, "process_start_time_seconds");
+        assert_eq!(metrics[2].metric_type, MetricType::Gauge);
+        assert_eq!(metrics[2].data_points[0].value, 1.7e9);
+    }
+
+    #[test]
+    fn test_parse_histogram() {
+        let text = r#"
+# TYPE http_request_duration_seconds histogram
+http_request_duration_seconds_bucket{le="0.05",path="/"} 24054
+http_request_duration_seconds_bucket{le="0.1",path="/"} 33444
+http_request_duration_seconds_bucket{le="0.5",path="/"} 129389
+http_request_duration_seconds_bucket{le="+Inf",path="/"} 144320
+http_request_duration_seconds_sum{path="/"} 53423
+http_request_duration_seconds_count{path="/"} 144320
+"#;
+        let metrics = parse_exposition(text, SCRAPE_TIME).unwrap();
+
+        assert_eq!(metrics.len(), 1);
+        let metric = &metrics[0];
+        assert_eq!(metric.metric_type, MetricType::Histogram);
+        assert_eq!(metric.temporality, AggregationTemporality::Cumulative);
+        assert_eq!(metric.data_points.len(), 1);
+
+        let dp = &metric.data_points[0];
+        assert_eq!(dp.value, 53423.0);
+        assert_eq!(dp.attributes.get_string("path"), Some("/"));
+        assert!(dp.attributes.get("le").is_none());
+        let histogram = dp.histogram.as_ref().unwrap();
+        assert_eq!(histogram.count, 144_320);
+        assert_eq!(histogram.sum, Some(53423.0));
+        assert_eq!(histogram.explicit_bounds, vec![0.05, 0.1, 0.5]);
+        assert_eq!(histogram.bucket_counts, vec![24054, 9390, 95945, 14931]);
+    }
+
+    #[test]
+    fn test_parse_malformed_histogram() {
+        let text = r#"
+# TYPE latency histogram
+latency_bucket{le="-Inf"} 1
+latency_bucket{le="NaN"} 2
+latency_bucket{le="0.1"} 3
+latency_bucket{le="0.10"} 4
+latency_bucket{le="1"} 6
+latency_count 10
+"#;
+        let metrics = parse_exposition(text, SCRAPE_TIME).unwrap();
+
+        let histogram = metrics[0].data_points[0].histogram.as_ref().unwrap();
+        assert_eq!(histogram.count, 10);
+        assert_eq!(histogram.explicit_bounds, vec![0.1, 1.0]);
+        assert_eq!(histogram.bucket_counts, vec![3, 3, 4]);
+    }
+
+    #[test]
+    fn test_parse_summary() {
+        let text = r#"
+# TYPE rpc_duration_seconds summary
+rpc_duration_seconds{quantile="0.5"} 4773
+rpc_duration_seconds{quantile="0.99"} 76656
+rpc_duration_seconds{quantile="0.999"} NaN
+rpc_duration_seconds_sum 1.7560473e+07
+rpc_duration_seconds_count 2693
+"#;
+        let metrics = parse_exposition(text, SCRAPE_TIME).unwrap();
+
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].metric_type, MetricType::Summary);
+        let summary = metrics[0].data_points[0].summary.as_ref().unwrap();
+        assert_eq!(summary.count, 2693);
+        assert_eq!(summary.sum, 1.7560473e7);
+        assert_eq!(summary.quantile_values.len(), 2);
+        assert_eq!(summary.value_at(0.99), Some(76656.0));
+    }
+
+    #[test]
+    fn test_parse_malformed_line() {
+        let text = "# TYPE up gauge\nup{job=\"api} 1\n";
+        let error = parse_exposition(text, SCRAPE_TIME).unwrap_err();
+        assert_eq!(error.line, 2);
+
+        let error = parse_exposition("up one\n", SCRAPE_TIME).unwrap_err();
+        assert_eq!(error.message, "invalid sample value \"one\"");
+
+        let error = parse_exposition("up 1 9223372036854776\n", SCRAPE_TIME).unwrap_err();
+        assert_eq!(error.message, "invalid timestamp \"9223372036854776\"");
+    }
+}
@@ -0,0 +1,436 @@
+use crate::convert::logs::convert_severity_text;
+use faze::models::{AttributeValue, Attributes, Log, Resource, SeverityLevel};
+use rmpv::Value;
+use std::io::Read;
+use thiserror::Error;
+
+/// Attribute carrying the event's tag
+const TAG_ATTRIBUTE: &str = "fluent.tag";
+
+/// Record fields holding the log line, in order of preference
+const BODY_FIELDS: &[&str] = &["log", "message", "msg"];
+
+/// Record fields that name the service, in order of preference. Docker's fluentd driver sets
+/// `container_name`, prefixed with `/`
+const SERVICE_FIELDS: &[&str] = &["service.name", "service_name", "container_name"];
+
+/// Record fields carrying the log level, in order of preference
+const LEVEL_FIELDS: &[&str] = &["level", "severity"];
+
+/// Largest size compressed entries may expand to, so a small message can't decompress without
+/// bound
+const MAX_DECOMPRESSED_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Extension type of Fluentd's EventTime: big-endian seconds and nanoseconds, 4 bytes each
+const EVENT_TIME_EXT: i8 = 0;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum FluentError {
+    #[error("invalid message: {0}")]
+    InvalidMessage(&'static str),
+
+    #[error("invalid event time {0}")]
+    InvalidTime(String),
+
+    #[error("invalid packed entries: {0}")]
+    InvalidEntries(String),
+
+    #[error("unsupported compression {0:?}")]
+    UnsupportedCompression(String),
+}
+
+/// Events of one Forward protocol message
+#[derive(Debug)]
+pub struct ForwardMessage {
+    pub logs: Vec<Log>,
+    /// Chunk ID to acknowledge once the events are stored, if the client asked for an ack
+    pub chunk: Option<String>,
+}
+
+fn convert_time(time: &Value) -> Result<i64, FluentError> {
+    let invalid = || FluentError::InvalidTime(time.to_string());
+    match time {
+        Value::Integer(seconds) => seconds
+            .as_i64()
+            .and_then(|seconds| seconds.checked_mul(1_000_000_000))
+            .ok_or_else(invalid),
+        Value::F64(seconds) => Ok((seconds * 1e9) as i64),
+        Value::F32(seconds) => Ok((f64::from(*seconds) * 1e9) as i64),
+        Value::Ext(EVENT_TIME_EXT, data) if data.len() == 8 => {
+            let seconds = u32::from_be_bytes(data[..4].try_into().expect("4 bytes"));
+            let nanos = u32::from_be_bytes(data[4..].try_into().expect("4 bytes"));
+            Ok(i64::from(seconds) * 1_000_000_000 + i64::from(nanos))
+        }
+        _ => Err(invalid()),
+    }
+}
+
+fn convert_value(value: Value) -> AttributeValue {
+    match value {
+        Value::Nil => AttributeValue::String(String::new()),
+        Value::Boolean(b) => AttributeValue::Bool(b),
+        Value::Integer(i) => match i.as_i64() {
+            Some(i) => AttributeValue::Int(i),
+            None => AttributeValue::Double(i.as_f64().unwrap_or_default()),
+        },
+        Value::F32(f) => AttributeValue::Double(f64::from(f)),
+        Value::F64(f) => AttributeValue::Double(f),
+        Value::String(s) => match s.into_str() {
+            Some(s) => AttributeValue::String(s),
+            None => AttributeValue::String(String::new()),
+        },
+        Value::Binary(bytes) => match String::from_utf8(bytes) {
+            // Older clients send strings as raw bytes
+            Ok(s) => AttributeValue::String(s),
+            Err(e) => AttributeValue::Bytes(e.into_bytes()),
+        },
+        Value::Array(values) => {
+            AttributeValue::Array(values.into_iter().map(convert_value).collect())
+        }
+        Value::Map(entries) => AttributeValue::Map(convert_map(entries)),
+        Value::Ext(_, data) => AttributeValue::Bytes(data),
+    }
+}
+
+fn convert_map(entries: Vec<(Value, Value)>) -> Attributes {
+    let mut attributes = Attributes::new();
+    for (key, value) in entries {
+        let key = match key {
+            Value::String(s) => s.into_str().unwrap_or_default(),
+            Value::Binary(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            key => key.to_string(),
+        };
+        attributes.insert(key, convert_value(value));
+    }
+    attributes
+}
+
+/// Convert one event's record to an internal Log
+fn convert_record(tag: &str, time_unix_nano: i64, record: Value) -> Result<Log, FluentError> {
+    let Value::Map(entries) = record else {
+        return Err(FluentError::InvalidMessage("record must be a map"));
+    };
+    let mut attributes = convert_map(entries);
+    attributes.insert(TAG_ATTRIBUTE, tag);
+
+    let body = BODY_FIELDS
+        .iter()
+        .find_map(|field| attributes.get_string(field).map(str::to_string));
+    let service_name = SERVICE_FIELDS.iter().find_map(|field| {
+        attributes
+            .get_string(field)
+            .map(|name| name.trim_start_matches('/').to_string())
+    });
+    let level = LEVEL_FIELDS
+        .iter()
+        .find_map(|field| attributes.get_string(field).map(str::to_string));
+    let severity_level = level
+        .as_deref()
+        .map_or(SeverityLevel::Unspecified, convert_severity_text);
+    let trace_id = attributes.get_string("trace_id").map(str::to_string);
+    let span_id = attributes.get_string("span_id").map(str::to_string);
+
+    let log = Log::new(
+        time_unix_nano,
+        severity_level,
+        level,
+        body.unwrap_or_default(),
+        attributes,
+        trace_id,
+        span_id,
+        service_name.clone(),
+    );
+    Ok(match service_name {
+        Some(name) => log.with_resource(Resource::for_service(name)),
+        None => log,
+    })
+}
+
+/// Convert a `[time, record]` entry of Forward or PackedForward mode
+fn convert_entry(tag: &str, entry: Value) -> Result<Log, FluentError> {
+    let Value::Array(entry) = entry else {
+        return Err(FluentError::InvalidMessage("entry must be an array"));
+    };
+    let mut entry = entry.into_iter();
+    match (entry.next(), entry.next()) {
+        (Some(time), Some(record)) => convert_record(tag, convert_time(&time)?, record),
+        _ => Err(FluentError::InvalidMessage("entry must be [time, record]")),
+    }
+}
+
+/// Convert the concatenated entries of PackedForward mode, gunzipping them first for
+/// CompressedPackedForward
+fn convert_packed_entries(
+    tag: &str,
+    entries: &[u8],
+    compressed: Option<&str>,
+) -> Result<Vec<Log>, FluentError> {
+    let decompressed;
+    let mut entries = match compressed {
+        None | Some("text") => entries,
+        Some("gzip") => {
+            let mut buf = Vec::new();
+            // Compressed entries may be several concatenated gzip members
+            flate2::read::MultiGzDecoder::new(entries)
+                .take(MAX_DECOMPRESSED_SIZE + 1)
+                .read_to_end(&mut buf)
+                .map_err(|e| FluentError::InvalidEntries(e.to_string()))?;
+            if buf.len() as u64 > MAX_DECOMPRESSED_SIZE {
+                return Err(FluentError::InvalidEntries(format!(
+                    "entries decompress to more than {MAX_DECOMPRESSED_SIZE} bytes"
+                )));
+            }
+            decompressed = buf;
+            &decompressed
+        }
+        Some(other) => return Err(FluentError::UnsupportedCompression(other.to_string())),
+    };
+
+    let mut logs = Vec::new();
+    while !entries.is_empty() {
+        let entry = rmpv::decode::read_value(&mut entries)
+            .map_err(|e| FluentError::InvalidEntries(e.to_string()))?;
+        logs.push(convert_entry(tag, entry)?);
+    }
+    Ok(logs)
+}
+
+fn option_str<'a>(option: Option<&'a Value>, key: &str) -> Option<&'a str> {
+    option?
+        .as_map()?
+        .iter()
+        .find(|(k, _)| k.as_str() == Some(key))
+        .and_then(|(_, v)| v.as_str())
+}
+
+/// Convert a Forward protocol message in Message, Forward, PackedForward or
+/// CompressedPackedForward mode to internal Logs
+pub fn convert_message(message: Value) -> Result<ForwardMessage, FluentError> {
+    let Value::Array(items) = message else {
+        return Err(FluentError::InvalidMessage("message must be an array"));
+    };
+    let mut items = items.into_iter();
+    let tag = match items.next() {
+        Some(Value::String(tag)) => tag.into_str().unwrap_or_default(),
+        _ => return Err(FluentError::InvalidMessage("tag must be a string")),
+    };
+    let Some(events) = items.next() else {
+        return Err(FluentError::InvalidMessage("message is too short"));
+    };
+    let mut rest = items;
+
+    let (logs, option) = match events {
+        Value::Array(entries) => {
+            let logs = entries
+                .into_iter()
+                .map(|entry| convert_entry(&tag, entry))
+                .collect::<Result<_, _>>()?;
+            (logs, rest.next())
+        }
+        Value::Binary(entries) => {
+            let option = rest.next();
+            let compressed = option_str(option.as_ref(), "compressed");
+            (convert_packed_entries(&tag, &entries, compressed)?, option)
+        }
+        Value::String(entries) => {
+            let option = rest.next();
+            let compressed = option_str(option.as_ref(), "compressed");
+            (
+                convert_packed_entries(&tag, entries.as_bytes(), compressed)?,
+                option,
+            )
+        }
+        time => {
+            let time_unix_nano = convert_time(&time)?;
+            let record = rest
+                .next()
+                .ok_or(FluentError::InvalidMessage("message is missing its record"))?;
+            (
+                vec![convert_record(&tag, time_unix_nano, record)?],
+                rest.next(),
+            )
+        }
+    };
+
+    let chunk = option_str(option.as_ref(), "chunk").map(str::to_string);
+    Ok(ForwardMessage { logs, chunk })
+}
+
+/// Response acknowledging a chunk
+pub fn ack_response(chunk: &str) -> Vec<u8> {
+    let mut response = Vec::new();
+    let ack = Value::Map(vec![(Value::from("ack"), Value::from(chunk))]);
+    rmpv::encode::write_value(&mut response, &ack).expect("writing to a Vec never fails");
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn encode(value: &Value) -> Vec<u8> {
+        let mut buf = Vec::new();
+        rmpv::encode::write_value(&mut buf, value).unwrap();
+        buf
+    }
+
+    fn record(fields: &[(&str, &str)]) -> Value {
+        Value::Map(
+            fields
+                .iter()
+                .map(|(k, v)| (Value::from(*k), Value::from(*v)))
+                .collect(),
+        )
+    }
+
+    fn event_time(seconds: u32, nanos: u32) -> Value {
+        let mut data = seconds.to_be_bytes().to_vec();
+        data.extend_from_slice(&nanos.to_be_bytes());
+        Value::Ext(EVENT_TIME_EXT, data)
+    }
+
+    #[test]
+    fn test_convert_message_mode() {
+        let message = Value::Array(vec![
+            Value::from("docker.abc123"),
+            event_time(1_700_000_000, 5),
+            record(&[
+                ("log", "GET /health 200"),
+                ("container_name", "/api"),
+                ("source", "stdout"),
+                ("level", "info"),
+            ]),
+            Value::Map(vec![(Value::from("chunk"), Value::from("p8n9gmxTQVC8"))]),
+        ]);
+
+        let message = convert_message(message).unwrap();
+
+        assert_eq!(message.chunk.as_deref(), Some("p8n9gmxTQVC8"));
+        assert_eq!(message.logs.len(), 1);
+        let log = &message.logs[0];
+        assert_eq!(log.time_unix_nano, 1_700_000_000_000_000_005);
+        assert_eq!(log.body, "GET /health 200");
+        assert_eq!(log.severity_level, SeverityLevel::Info);
+        assert_eq!(log.service_name.as_deref(), Some("api"));
+        assert_eq!(
+            log.attributes.get_string("fluent.tag"),
+            Some("docker.abc123")
+        );
+        assert_eq!(log.attributes.get_string("source"), Some("stdout"));
+        assert_eq!(log.resource.as_ref().unwrap().service_name(), Some("api"));
+    }
+
+    #[test]
+    fn test_convert_forward_mode() {
+        let message = Value::Array(vec![
+            Value::from("app"),
+            Value::Array(vec![
+                Value::Array(vec![
+                    Value::from(1_700_000_000),
+                    record(&[("message", "one")]),
+                ]),
+                Value::Array(vec![
+                    Value::from(1_700_000_001),
+                    record(&[("message", "two")]),
+                ]),
+            ]),
+        ]);
+
+        let message = convert_message(message).unwrap();
+
+        assert_eq!(message.chunk, None);
+        assert_eq!(message.logs.len(), 2);
+        assert_eq!(message.logs[1].body, "two");
+        assert_eq!(message.logs[1].time_unix_nano, 1_700_000_001_000_000_000);
+        assert_eq!(message.logs[0].service_name, None);
+    }
+
+    #[test]
+    fn test_convert_packed_forward_mode() {
+        let mut entries = encode(&Value::Array(vec![
+            event_time(1_700_000_000, 0),
+            record(&[("log", "first")]),
+        ]));
+        entries.extend(encode(&Value::Array(vec![
+            event_time(1_700_000_000, 1),
+            Value::Map(vec![
+                (Value::from("log"), Value::from("second")),
+                (Value::from("status"), Value::from(503)),
+            ]),
+        ])));
+
+        let message = Value::Array(vec![
+            Value::from("app"),
+            Value::Binary(entries.clone()),
+            Value::Map(vec![(Value::from("size"), Value::from(2))]),
+        ]);
+        let logs = convert_message(message).unwrap().logs;
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[1].body, "second");
+        assert_eq!(
+            logs[1].attributes.get("status"),
+            Some(&AttributeValue::Int(503))
+        );
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&entries).unwrap();
+        let message = Value::Array(vec![
+            Value::from("app"),
+            Value::Binary(encoder.finish().unwrap()),
+            Value::Map(vec![
+                (Value::from("compressed"), Value::from("gzip")),
+                (Value::from("chunk"), Value::from("abc")),
+            ]),
+        ]);
+        let message = convert_message(message).unwrap();
+        assert_eq!(message.logs.len(), 2);
+        assert_eq!(message.chunk.as_deref(), Some("abc"));
+    }
+
+    #[test]
+    fn test_convert_compressed_entries_over_limit() {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        let zeros = vec![0u8; 1024 * 1024];
+        for _ in 0..=MAX_DECOMPRESSED_SIZE / zeros.len() as u64 {
+            encoder.write_all(&zeros).unwrap();
+        }
+        let message = Value::Array(vec![
+            Value::from("app"),
+            Value::Binary(encoder.finish().unwrap()),
+            Value::Map(vec![(Value::from("compressed"), Value::from("gzip"))]),
+        ]);
+
+        assert!(matches!(
+            convert_message(message),
+            Err(FluentError::InvalidEntries(_))
+        ));
+    }
+
+    #[test]
+    fn test_convert_invalid_message() {
+        assert_eq!(
+            convert_message(Value::from("hello")).unwrap_err(),
+            FluentError::InvalidMessage("message must be an array")
+        );
+        let message = Value::Array(vec![Value::from("app"), Value::from("yesterday")]);
+        assert_eq!(
+            convert_message(message).unwrap_err(),
+            FluentError::InvalidMessage("entry must be an array")
+        );
+        let message = Value::Array(vec![Value::from("app"), Value::Boolean(true), record(&[])]);
+        assert!(matches!(
+            convert_message(message),
+            Err(FluentError::InvalidTime(_))
+        ));
+    }
+
+    #[test]
+    fn test_ack_response() {
+        let ack = rmpv::decode::read_value(&mut ack_response("abc").as_slice()).unwrap();
+        assert_eq!(
+            ack,
+            Value::Map(vec![(Value::from("ack"), Value::from("abc"))])
+        );
+    }
+}
@@ -0,0 +1,603 @@
+use crate::{
+    convert::bytes_to_hex,
+    proto::jaeger::api_v2::{
+        Batch, KeyValue, Log, Process, Span as JaegerSpan, SpanRef, SpanRefType, ValueType,
+    },
+    thrift::{self, Reader, ThriftError},
+};
+use faze::models::{
+    AttributeValue, Attributes, Resource, Span, SpanEvent, SpanKind, SpanLink, Status,
+};
+use prost_types::{Duration, Timestamp};
+
+/// Tags that Jaeger clients use to carry span fields OTLP models directly
+const SPAN_KIND_TAG: &str = "span.kind";
+const STATUS_CODE_TAG: &str = "otel.status_code";
+const STATUS_DESCRIPTION_TAG: &str = "otel.status_description";
+const ERROR_TAG: &str = "error";
+
+/// Convert a Jaeger tag to internal AttributeValue
+fn convert_tag_value(tag: &KeyValue) -> AttributeValue {
+    match ValueType::try_from(tag.v_type).unwrap_or(ValueType::String) {
+        ValueType::String => AttributeValue::String(tag.v_str.clone()),
+        ValueType::Bool => AttributeValue::Bool(tag.v_bool),
+        ValueType::Int64 => AttributeValue::Int(tag.v_int64),
+        ValueType::Float64 => AttributeValue::Double(tag.v_float64),
+        ValueType::Binary => AttributeValue::Bytes(tag.v_binary.clone()),
+    }
+}
+
+/// Convert Jaeger tags to Attributes
+fn convert_tags<'a>(tags: impl IntoIterator<Item = &'a KeyValue>) -> Attributes {
+    tags.into_iter()
+        .map(|tag| (tag.key.clone(), convert_tag_value(tag)))
+        .collect()
+}
+
+/// Convert the `span.kind` tag to internal SpanKind
+fn convert_span_kind(kind: Option<&str>) -> SpanKind {
+    match kind {
+        Some("server") => SpanKind::Server,
+        Some("client") => SpanKind::Client,
+        Some("producer") => SpanKind::Producer,
+        Some("consumer") => SpanKind::Consumer,
+        // Jaeger leaves the kind out for spans that stay within one process
+        _ => SpanKind::Internal,
+    }
+}
+
+/// Derive the span status from the `otel.status_code` tag set by OpenTelemetry's Jaeger
+/// exporter, or the `error` tag set by Jaeger clients
+fn convert_status(tags: &Attributes) -> Status {
+    let message = tags
+        .get_string(STATUS_DESCRIPTION_TAG)
+        .unwrap_or_default()
+        .to_string();
+
+    match tags.get_string(STATUS_CODE_TAG) {
+        Some("ERROR") => return Status::error(message),
+        Some("OK") => return Status::ok(),
+        _ => {}
+    }
+
+    match tags.get(ERROR_TAG) {
+        Some(AttributeValue::Bool(true)) => Status::error(message),
+        Some(AttributeValue::String(s)) if s == "true" => Status::error(message),
+        _ => Status::unset(),
+    }
+}
+
+/// Nanoseconds in `seconds` and `nanos`, or None when they don't fit in an i64
+fn to_nanos(seconds: i64, nanos: i32) -> Option<i64> {
+    seconds
+        .checked_mul(1_000_000_000)?
+        .checked_add(i64::from(nanos))
+}
+
+fn timestamp_to_nanos(timestamp: Option<&Timestamp>) -> Option<i64> {
+    timestamp.map_or(Some(0), |t| to_nanos(t.seconds, t.nanos))
+}
+
+fn duration_to_nanos(duration: Option<&Duration>) -> Option<i64> {
+    duration.map_or(Some(0), |d| to_nanos(d.seconds, d.nanos))
+}
+
+/// Convert a Jaeger log to a span event, named after its `event` field when there is one
+fn convert_log(log: &Log) -> Option<SpanEvent> {
+    let time_unix_nano = timestamp_to_nanos(log.timestamp.as_ref())?;
+    let attributes = convert_tags(&log.fields);
+    let name = attributes.get_string("event").unwrap_or("log").to_string();
+    Some(SpanEvent::new(name, time_unix_nano, attributes))
+}
+
+/// Convert a Jaeger process to internal Resource
+fn convert_process(process: &Process) -> Resource {
+    let mut attributes = convert_tags(&process.tags);
+    if !process.service_name.is_empty() {
+        attributes.insert("service.name", process.service_name.clone());
+    }
+    Resource::new(attributes)
+}
+
+/// Convert a Jaeger span to internal Span, taking its resource from `process` unless the span
+/// carries its own
+///
+/// Returns None when the span's start or end time is out of range.
+pub fn convert_span(span: &JaegerSpan, process: Option<&Process>) -> Option<Span> {
+    let trace_id = bytes_to_hex(&span.trace_id);
+    let start_time_unix_nano = timestamp_to_nanos(span.start_time.as_ref())?;
+    let end_time_unix_nano =
+        start_time_unix_nano.checked_add(duration_to_nanos(span.duration.as_ref())?)?;
+
+    // The first CHILD_OF reference within the trace is the parent; the rest become links
+    let parent = span
+        .references
+        .iter()
+        .position(|r| r.ref_type == SpanRefType::ChildOf as i32 && r.trace_id == span.trace_id);
+    let links = span
+        .references
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| Some(*index) != parent)
+        .map(|(_, r)| {
+            SpanLink::new(
+                bytes_to_hex(&r.trace_id),
+                bytes_to_hex(&r.span_id),
+                None,
+                Attributes::new(),
+            )
+        })
+        .collect();
+
+    let tags = convert_tags(&span.tags);
+    let kind = convert_span_kind(tags.get_string(SPAN_KIND_TAG));
+    let status = convert_status(&tags);
+    let attributes = convert_tags(span.tags.iter().filter(|tag| {
+        ![SPAN_KIND_TAG, STATUS_CODE_TAG, STATUS_DESCRIPTION_TAG].contains(&tag.key.as_str())
+    }));
+
+    // Logs with out-of-range timestamps are left out rather than losing the whole span
+    let mut events: Vec<SpanEvent> = span.logs.iter().filter_map(convert_log).collect();
+    events.sort_by_key(|event| event.time_unix_nano);
+
+    let process = span.process.as_ref().or(process);
+    let service_name = process
+        .map(|p| p.service_name.clone())
+        .filter(|name| !name.is_empty());
+
+    let converted = Span::new(
+        bytes_to_hex(&span.span_id),
+        trace_id,
+        parent.map(|index| bytes_to_hex(&span.references[index].span_id)),
+        span.operation_name.clone(),
+        kind,
+        start_time_unix_nano,
+        end_time_unix_nano,
+        attributes,
+        status,
+        service_name,
+    )
+    .with_events(events)
+    .with_links(links);
+
+    Some(match process {
+        Some(process) => converted.with_resource(convert_process(process)),
+        None => converted,
+    })
+}
+
+/// Convert a Jaeger batch to list of internal Spans, dropping spans with out-of-range times
+pub fn convert_batch(batch: &Batch) -> Vec<Span> {
+    batch
+        .spans
+        .iter()
+        .filter_map(|span| convert_span(span, batch.process.as_ref()))
+        .collect()
+}
+
+// Decoding of the Thrift model from jaeger-idl's thrift/jaeger.thrift, which the HTTP
+// receiver accepts. Thrift spans are read straight into the api_v2 protobuf types so both
+// receivers share the conversion above.
+
+fn micros_to_timestamp(micros: i64) -> Timestamp {
+    Timestamp {
+        seconds: micros.div_euclid(1_000_000),
+        nanos: (micros.rem_euclid(1_000_000) * 1_000) as i32,
+    }
+}
+
+fn micros_to_duration(micros: i64) -> Duration {
+    Duration {
+        seconds: micros / 1_000_000,
+        nanos: (micros % 1_000_000 * 1_000) as i32,
+    }
+}
+
+fn trace_id_bytes(high: i64, low: i64) -> Vec<u8> {
+    [high.to_be_bytes(), low.to_be_bytes()].concat()
+}
+
+fn read_tag(r: &mut Reader) -> thrift::Result<KeyValue> {
+    let mut tag = KeyValue::default();
+    r.read_struct(|r, id, field_type| {
+        match (id, field_type) {
+            (1, thrift::STRING) => tag.key = r.read_string()?,
+            // Thrift's TagType orders its variants differently from the protobuf ValueType
+            (2, thrift::I32) => {
+                tag.v_type = match r.read_i32()? {
+                    1 => ValueType::Float64,
+                    2 => ValueType::Bool,
+                    3 => ValueType::Int64,
+                    4 => ValueType::Binary,
+                    _ => ValueType::String,
+                } as i32
+            }
+            (3, thrift::STRING) => tag.v_str = r.read_string()?,
+            (4, thrift::DOUBLE) => tag.v_float64 = r.read_double()?,
+            (5, thrift::BOOL) => tag.v_bool = r.read_bool()?,
+            (6, thrift::I64) => tag.v_int64 = r.read_i64()?,
+            (7, thrift::STRING) => tag.v_binary = r.read_binary()?.to_vec(),
+            _ => r.skip(field_type)?,
+        }
+        Ok(())
+    })?;
+    Ok(tag)
+}
+
+fn read_tags(r: &mut Reader) -> thrift::Result<Vec<KeyValue>> {
+    r.read_struct_list(read_tag)
+}
+
+fn read_log(r: &mut Reader) -> thrift::Result<Log> {
+    let mut log = Log::default();
+    r.read_struct(|r, id, field_type| {
+        match (id, field_type) {
+            (1, thrift::I64) => log.timestamp = Some(micros_to_timestamp(r.read_i64()?)),
+            (2, thrift::LIST) => log.fields = read_tags(r)?,
+            _ => r.skip(field_type)?,
+        }
+        Ok(())
+    })?;
+    Ok(log)
+}
+
+fn read_span_ref(r: &mut Reader) -> thrift::Result<SpanRef> {
+    let (mut ref_type, mut trace_id_low, mut trace_id_high, mut span_id) = (0, 0, 0, 0);
+    r.read_struct(|r, id, field_type| {
+        match (id, field_type) {
+            (1, thrift::I32) => ref_type = r.read_i32()?,
+            (2, thrift::I64) => trace_id_low = r.read_i64()?,
+            (3, thrift::I64) => trace_id_high = r.read_i64()?,
+            (4, thrift::I64) => span_id = r.read_i64()?,
+            _ => r.skip(field_type)?,
+        }
+        Ok(())
+    })?;
+
+    // Thrift's SpanRefType matches the protobuf one: CHILD_OF = 0, FOLLOWS_FROM = 1
+    Ok(SpanRef {
+        trace_id: trace_id_bytes(trace_id_high, trace_id_low),
+        span_id: span_id.to_be_bytes().to_vec(),
+        ref_type,
+    })
+}
+
+fn read_span(r: &mut Reader) -> thrift::Result<JaegerSpan> {
+    let mut span = JaegerSpan::default();
+    let (mut trace_id_low, mut trace_id_high, mut span_id, mut parent_span_id) = (0, 0, 0, 0);
+    r.read_struct(|r, id, field_type| {
+        match (id, field_type) {
+            (1, thrift::I64) => trace_id_low = r.read_i64()?,
+            (2, thrift::I64) => trace_id_high = r.read_i64()?,
+            (3, thrift::I64) => span_id = r.read_i64()?,
+            (4, thrift::I64) => parent_span_id = r.read_i64()?,
+            (5, thrift::STRING) => span.operation_name = r.read_string()?,
+            (6, thrift::LIST) => span.references = r.read_struct_list(read_span_ref)?,
+            (7, thrift::I32) => span.flags = r.read_i32()? as u32,
+            (8, thrift::I64) => span.start_time = Some(micros_to_timestamp(r.read_i64()?)),
+            (9, thrift::I64) => span.duration = Some(micros_to_duration(r.read_i64()?)),
+            (10, thrift::LIST) => span.tags = read_tags(r)?,
+            (11, thrift::LIST) => span.logs = r.read_struct_list(read_log)?,
+            _ => r.skip(field_type)?,
+        }
+        Ok(())
+    })?;
+
+    span.trace_id = trace_id_bytes(trace_id_high, trace_id_low);
+    span.span_id = span_id.to_be_bytes().to_vec();
+
+    // Thrift spans carry their parent in a field of its own, which older clients send without
+    // a matching CHILD_OF reference
+    if parent_span_id != 0 {
+        let parent = SpanRef {
+            trace_id: span.trace_id.clone(),
+            span_id: parent_span_id.to_be_bytes().to_vec(),
+            ref_type: SpanRefType::ChildOf as i32,
+        };
+        if !span.references.contains(&parent) {
+            span.references.insert(0, parent);
+        }
+    }
+
+    Ok(span)
+}
+
+fn read_process(r: &mut Reader) -> thrift::Result<Process> {
+    let mut process = Process::default();
+    r.read_struct(|r, id, field_type| {
+        match (id, field_type) {
+            (1, thrift::STRING) => process.service_name = r.read_string()?,
+            (2, thrift::LIST) => process.tags = read_tags(r)?,
+            _ => r.skip(field_type)?,
+        }
+        Ok(())
+    })?;
+    Ok(process)
+}
+
+/// Decode a binary-encoded Thrift `Batch`
+pub fn decode_thrift_batch(buf: &[u8]) -> Result<Batch, ThriftError> {
+    let mut batch = Batch::default();
+    Reader::new(buf).read_struct(|r, id, field_type| {
+        match (id, field_type) {
+            (1, thrift::STRUCT) => batch.process = Some(read_process(r)?),
+            (2, thrift::LIST) => batch.spans = r.read_struct_list(read_span)?,
+            _ => r.skip(field_type)?,
+        }
+        Ok(())
+    })?;
+    Ok(batch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use faze::models::StatusCode;
+
+    fn tag(key: &str, v_type: ValueType) -> KeyValue {
+        KeyValue {
+            key: key.to_string(),
+            v_type: v_type as i32,
+            ..Default::default()
+        }
+    }
+
+    fn string_tag(key: &str, value: &str) -> KeyValue {
+        KeyValue {
+            v_str: value.to_string(),
+            ..tag(key, ValueType::String)
+        }
+    }
+
+    fn process(service_name: &str) -> Process {
+        Process {
+            service_name: service_name.to_string(),
+            tags: vec![
+                string_tag("hostname", "web-1"),
+                KeyValue {
+                    v_float64: 0.5,
+                    ..tag("sampler.param", ValueType::Float64)
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_convert_span() {
+        let trace_id = vec![0; 15].into_iter().chain([1]).collect::<Vec<u8>>();
+        let span = JaegerSpan {
+            trace_id: trace_id.clone(),
+            span_id: vec![0, 0, 0, 0, 0, 0, 0, 2],
+            operation_name: "GET /api".to_string(),
+            references: vec![
+                SpanRef {
+                    trace_id: trace_id.clone(),
+                    span_id: vec![0, 0, 0, 0, 0, 0, 0, 1],
+                    ref_type: SpanRefType::ChildOf as i32,
+                },
+                SpanRef {
+                    trace_id: trace_id.clone(),
+                    span_id: vec![0, 0, 0, 0, 0, 0, 0, 3],
+                    ref_type: SpanRefType::FollowsFrom as i32,
+                },
+            ],
+            start_time: Some(Timestamp {
+                seconds: 1_556_604_172,
+                nanos: 355_737_000,
+            }),
+            duration: Some(Duration {
+                seconds: 0,
+                nanos: 1_431_000,
+            }),
+            tags: vec![
+                string_tag("span.kind", "server"),
+                string_tag("http.method", "GET"),
+                KeyValue {
+                    v_int64: 200,
+                    ..tag("http.status_code", ValueType::Int64)
+                },
+            ],
+            logs: vec![Log {
+                timestamp: Some(Timestamp {
+                    seconds: 1_556_604_172,
+                    nanos: 356_000_000,
+                }),
+                fields: vec![string_tag("event", "cache miss")],
+            }],
+            ..Default::default()
+        };
+
+        let span = convert_span(&span, Some(&process("frontend"))).unwrap();
+
+        assert_eq!(span.trace_id, "00000000000000000000000000000001");
+        assert_eq!(span.span_id, "0000000000000002");
+        assert_eq!(span.parent_span_id.as_deref(), Some("0000000000000001"));
+        assert_eq!(span.name, "GET /api");
+        assert_eq!(span.kind, SpanKind::Server);
+        assert_eq!(span.start_time_unix_nano, 1_556_604_172_355_737_000);
+        assert_eq!(span.end_time_unix_nano, 1_556_604_172_357_168_000);
+        assert_eq!(span.attributes.get_string("http.method"), Some("GET"));
+        assert_eq!(span.attributes.get_int("http.status_code"), Some(200));
+        assert!(span.attributes.get("span.kind").is_none());
+        assert_eq!(span.status.code, StatusCode::Unset);
+        assert_eq!(span.links.len(), 1);
+        assert_eq!(span.links[0].span_id, "0000000000000003");
+        assert_eq!(span.events[0].name, "cache miss");
+
+        assert_eq!(span.service_name.as_deref(), Some("frontend"));
+        let resource = span.resource.unwrap();
+        assert_eq!(resource.service_name(), Some("frontend"));
+        assert_eq!(resource.attributes.get_string("hostname"), Some("web-1"));
+        assert_eq!(resource.attributes.get_double("sampler.param"), Some(0.5));
+    }
+
+    #[test]
+    fn test_convert_span_prefers_own_process() {
+        let span = JaegerSpan {
+            process: Some(process("backend")),
+            ..Default::default()
+        };
+
+        let span = convert_span(&span, Some(&process("frontend"))).unwrap();
+
+        assert_eq!(span.service_name.as_deref(), Some("backend"));
+        assert_eq!(span.kind, SpanKind::Internal);
+    }
+
+    #[test]
+    fn test_convert_batch_drops_out_of_range_times() {
+        let timestamp = |seconds| Some(Timestamp { seconds, nanos: 0 });
+        let batch = Batch {
+            spans: vec![
+                JaegerSpan {
+                    start_time: timestamp(i64::MAX),
+                    ..Default::default()
+                },
+                JaegerSpan {
+                    start_time: timestamp(9_000_000_000),
+                    duration: Some(Duration {
+                        seconds: 1_000_000_000,
+                        nanos: 0,
+                    }),
+                    ..Default::default()
+                },
+                JaegerSpan {
+                    start_time: timestamp(1_556_604_172),
+                    logs: vec![Log {
+                        timestamp: timestamp(i64::MIN),
+                        fields: vec![string_tag("event", "cache miss")],
+                    }],
+                    ..Default::default()
+                },
+            ],
+            process: Some(process("frontend")),
+        };
+
+        let spans = convert_batch(&batch);
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].start_time_unix_nano, 1_556_604_172_000_000_000);
+        assert!(spans[0].events.is_empty());
+    }
+
+    #[test]
+    fn test_convert_status() {
+        let mut tags = Attributes::new();
+        assert_eq!(convert_status(&tags).code, StatusCode::Unset);
+
+        tags.insert("error", true);
+        assert_eq!(convert_status(&tags).code, StatusCode::Error);
+
+        tags.insert("otel.status_code", "OK");
+        assert_eq!(convert_status(&tags).code, StatusCode::Ok);
+
+        tags.insert("otel.status_code", "ERROR");
+        tags.insert("otel.status_description", "connection refused");
+        let status = convert_status(&tags);
+        assert_eq!(status.code, StatusCode::Error);
+        assert_eq!(status.message.as_deref(), Some("connection refused"));
+    }
+
+    /// Binary-encoded Thrift batch with one span, as sent by jaeger-client libraries
+    fn thrift_batch() -> Vec<u8> {
+        fn field(buf: &mut Vec<u8>, field_type: u8, id: i16) {
+            buf.push(field_type);
+            buf.extend(id.to_be_bytes());
+        }
+        fn string(buf: &mut Vec<u8>, value: &str) {
+            buf.extend((value.len() as i32).to_be_bytes());
+            buf.extend(value.as_bytes());
+        }
+        fn list(buf: &mut Vec<u8>, element_type: u8, size: i32) {
+            buf.push(element_type);
+            buf.extend(size.to_be_bytes());
+        }
+
+        let mut buf = Vec::new();
+        // 1: process
+        field(&mut buf, thrift::STRUCT, 1);
+        field(&mut buf, thrift::STRING, 1);
+        string(&mut buf, "frontend");
+        field(&mut buf, thrift::LIST, 2);
+        list(&mut buf, thrift::STRUCT, 1);
+        field(&mut buf, thrift::STRING, 1);
+        string(&mut buf, "jaeger.version");
+        field(&mut buf, thrift::I32, 2);
+        buf.extend(0i32.to_be_bytes());
+        field(&mut buf, thrift::STRING, 3);
+        string(&mut buf, "Go-2.30.0");
+        buf.push(thrift::STOP);
+        buf.push(thrift::STOP);
+
+        // 2: spans
+        field(&mut buf, thrift::LIST, 2);
+        list(&mut buf, thrift::STRUCT, 1);
+        for (id, value) in [
+            (1i16, 0x5af7183fb1d4cf5fi64),
+            (2, 0),
+            (3, 0x352b),
+            (4, 0x6b22),
+        ] {
+            field(&mut buf, thrift::I64, id);
+            buf.extend(value.to_be_bytes());
+        }
+        field(&mut buf, thrift::STRING, 5);
+        string(&mut buf, "query");
+        field(&mut buf, thrift::I32, 7);
+        buf.extend(1i32.to_be_bytes());
+        field(&mut buf, thrift::I64, 8);
+        buf.extend(1_556_604_172_355_900i64.to_be_bytes());
+        field(&mut buf, thrift::I64, 9);
+        buf.extend(800i64.to_be_bytes());
+        field(&mut buf, thrift::LIST, 10);
+        list(&mut buf, thrift::STRUCT, 2);
+        field(&mut buf, thrift::STRING, 1);
+        string(&mut buf, "span.kind");
+        field(&mut buf, thrift::STRING, 3);
+        string(&mut buf, "client");
+        buf.push(thrift::STOP);
+        field(&mut buf, thrift::STRING, 1);
+        string(&mut buf, "error");
+        field(&mut buf, thrift::I32, 2);
+        buf.extend(2i32.to_be_bytes());
+        field(&mut buf, thrift::BOOL, 5);
+        buf.push(1);
+        buf.push(thrift::STOP);
+        buf.push(thrift::STOP);
+        buf.push(thrift::STOP);
+
+        buf
+    }
+
+    #[test]
+    fn test_decode_thrift_batch() {
+        let batch = decode_thrift_batch(&thrift_batch()).unwrap();
+        let spans = convert_batch(&batch);
+
+        assert_eq!(spans.len(), 1);
+        let span = &spans[0];
+        assert_eq!(span.trace_id, "00000000000000005af7183fb1d4cf5f");
+        assert_eq!(span.span_id, "000000000000352b");
+        assert_eq!(span.parent_span_id.as_deref(), Some("0000000000006b22"));
+        assert_eq!(span.name, "query");
+        assert_eq!(span.kind, SpanKind::Client);
+        assert_eq!(span.start_time_unix_nano, 1_556_604_172_355_900_000);
+        assert_eq!(span.duration_nanos(), 800_000);
+        assert_eq!(span.status.code, StatusCode::Error);
+        assert_eq!(span.attributes.get_bool("error"), Some(true));
+        assert_eq!(span.service_name.as_deref(), Some("frontend"));
+        assert_eq!(
+            span.resource
+                .as_ref()
+                .unwrap()
+                .attributes
+                .get_string("jaeger.version"),
+            Some("Go-2.30.0")
+        );
+    }
+
+    #[test]
+    fn test_decode_thrift_batch_truncated() {
+        let buf = thrift_batch();
+        assert!(decode_thrift_batch(&buf[..buf.len() / 2]).is_err());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::convert::{
-    bytes_to_hex, convert_any_value_to_string, convert_attributes, convert_resource,
+    bytes_to_hex, convert_any_value_to_string, convert_attributes, convert_resource, convert_scope,
 };
 use crate::proto::opentelemetry::proto::logs::v1::{
     LogRecord, ResourceLogs, SeverityNumber as OtlSeveryNumber,
@@ -38,6 +38,20 @@ pub fn convert_log_severity_level(kind: i32) -> SeverityLevel {
     }
 }
 
+/// Convert a textual level, as used by logging libraries and Loki's `level` label, to internal
+/// SeverityLevel
+pub fn convert_severity_text(text: &str) -> SeverityLevel {
+    match text.to_ascii_lowercase().as_str() {
+        "trace" | "trc" => SeverityLevel::Trace,
+        "debug" | "dbg" => SeverityLevel::Debug,
+        "info" | "information" | "inf" | "notice" => SeverityLevel::Info,
+        "warn" | "warning" | "wrn" => SeverityLevel::Warn,
+        "error" | "err" | "eror" => SeverityLevel::Error,
+        "fatal" | "critical" | "crit" | "panic" | "alert" | "emerg" => SeverityLevel::Fatal,
+        _ => SeverityLevel::Unspecified,
+    }
+}
+
 // Convert OTLP LogRecord to internal Log
 fn convert_log(log: &LogRecord, service_name: Option<String>) -> FazeLog {
     let trace_id = Some(bytes_to_hex(&log.trace_id));
@@ -54,7 +68,7 @@ fn convert_log(log: &LogRecord, service_name: Option<String>) -> FazeLog {
         .and_then(convert_any_value_to_string)
         .unwrap_or_default();
 
-    FazeLog::new(
+    let log_entry = FazeLog::new(
         log.time_unix_nano as i64,
         severity_level,
         severity_text,
@@ -63,7 +77,13 @@ fn convert_log(log: &LogRecord, service_name: Option<String>) -> FazeLog {
         trace_id,
         span_id,
         service_name,
-    )
+    );
+
+    if log.observed_time_unix_nano > 0 {
+        log_entry.with_observed_time(log.observed_time_unix_nano as i64)
+    } else {
+        log_entry
+    }
 }
 
 /// Convert OTLP LogRecord to internal Log
@@ -71,15 +91,19 @@ pub fn convert_resource_logs(resource_logs: &[ResourceLogs]) -> Vec<FazeLog> {
     let mut logs = Vec::new();
 
     for rs in resource_logs {
-        let service_name = rs
-            .resource
+        let resource = rs.resource.as_ref().map(convert_resource);
+        let service_name = resource
             .as_ref()
-            .map(convert_resource)
             .and_then(|r| r.service_name().map(|s| s.to_string()));
 
         for scope_logs in &rs.scope_logs {
-            for span in &scope_logs.log_records {
-                logs.push(convert_log(span, service_name.clone()));
+            let scope = scope_logs.scope.as_ref().map(convert_scope);
+            for log in &scope_logs.log_records {
+                logs.push(FazeLog {
+                    resource: resource.clone(),
+                    scope: scope.clone(),
+                    ..convert_log(log, service_name.clone())
+                });
             }
         }
     }
@@ -0,0 +1,308 @@
+use crate::{
+    convert::{exposition::parse_labels, logs::convert_severity_text},
+    proto::loki::{PushRequest, StreamAdapter},
+};
+use faze::models::{Attributes, Log, Resource, SeverityLevel};
+use serde::Deserialize;
+use std::collections::HashMap;
+use thiserror::Error;
+
+/// Stream labels that name the service, in order of preference
+const SERVICE_LABELS: &[&str] = &["service_name", "job"];
+
+/// Stream label carrying the log level
+const LEVEL_LABEL: &str = "level";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum LokiError {
+    #[error("invalid stream labels {labels:?}: {message}")]
+    InvalidLabels { labels: String, message: String },
+
+    #[error("invalid timestamp {0:?}")]
+    InvalidTimestamp(String),
+}
+
+/// Push request in Loki's JSON encoding
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LokiPushRequest {
+    #[serde(default)]
+    pub streams: Vec<LokiStream>,
+}
+
+/// Stream of entries sharing one label set
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LokiStream {
+    #[serde(default)]
+    pub stream: HashMap<String, String>,
+    #[serde(default)]
+    pub values: Vec<LokiValue>,
+}
+
+/// Entry as `[timestamp, line]` or `[timestamp, line, structured metadata]`, with the timestamp
+/// in nanoseconds since epoch as a string
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum LokiValue {
+    WithMetadata(String, String, HashMap<String, String>),
+    Plain(String, String),
+}
+
+/// A log line with its timestamp and structured metadata, from either encoding
+struct Entry {
+    time_unix_nano: i64,
+    line: String,
+    metadata: Vec<(String, String)>,
+}
+
+/// Convert the entries of one stream to internal Logs
+fn convert_stream(labels: Vec<(String, String)>, entries: Vec<Entry>) -> Vec<Log> {
+    let service_name = SERVICE_LABELS.iter().find_map(|service_label| {
+        labels
+            .iter()
+            .find(|(name, _)| name == service_label)
+            .map(|(_, value)| value.clone())
+    });
+    let level = labels
+        .iter()
+        .find(|(name, _)| name == LEVEL_LABEL)
+        .map(|(_, value)| value.clone());
+    let severity_level = level
+        .as_deref()
+        .map_or(SeverityLevel::Unspecified, convert_severity_text);
+
+    let resource = service_name.as_ref().map(Resource::for_service);
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            let mut attributes = Attributes::new();
+            for (name, value) in labels.iter().chain(&entry.metadata) {
+                attributes.insert(name.clone(), value.clone());
+            }
+            let trace_id = attributes.get_string("trace_id").map(str::to_string);
+            let span_id = attributes.get_string("span_id").map(str::to_string);
+
+            let log = Log::new(
+                entry.time_unix_nano,
+                severity_level,
+                level.clone(),
+                entry.line,
+                attributes,
+                trace_id,
+                span_id,
+                service_name.clone(),
+            );
+            match &resource {
+                Some(resource) => log.with_resource(resource.clone()),
+                None => log,
+            }
+        })
+        .collect()
+}
+
+fn convert_stream_adapter(stream: StreamAdapter) -> Result<Vec<Log>, LokiError> {
+    let invalid = |message: String| LokiError::InvalidLabels {
+        labels: stream.labels.clone(),
+        message,
+    };
+    let labels = stream.labels.trim();
+    let labels = match labels.strip_prefix('{') {
+        Some(rest) => {
+            let (labels, rest) = parse_labels(rest).map_err(invalid)?;
+            if !rest.trim().is_empty() {
+                return Err(invalid("unexpected text after '}'".to_string()));
+            }
+            labels
+        }
+        None if labels.is_empty() => Vec::new(),
+        None => return Err(invalid("expected '{'".to_string())),
+    };
+
+    let entries = stream
+        .entries
+        .into_iter()
+        .map(|entry| {
+            let time_unix_nano = match entry.timestamp {
+                Some(t) => t
+                    .seconds
+                    .checked_mul(1_000_000_000)
+                    .and_then(|nanos| nanos.checked_add(i64::from(t.nanos)))
+                    .ok_or_else(|| LokiError::InvalidTimestamp(t.to_string()))?,
+                None => 0,
+            };
+            Ok(Entry {
+                time_unix_nano,
+                line: entry.line,
+                metadata: entry
+                    .structured_metadata
+                    .into_iter()
+                    .map(|pair| (pair.name, pair.value))
+                    .collect(),
+            })
+        })
+        .collect::<Result<Vec<Entry>, LokiError>>()?;
+
+    Ok(convert_stream(labels, entries))
+}
+
+/// Convert a protobuf push request to list of internal Logs
+pub fn convert_push_request(request: PushRequest) -> Result<Vec<Log>, LokiError> {
+    let mut logs = Vec::new();
+    for stream in request.streams {
+        logs.extend(convert_stream_adapter(stream)?);
+    }
+    Ok(logs)
+}
+
+/// Convert a JSON push request to list of internal Logs
+pub fn convert_json_push_request(request: LokiPushRequest) -> Result<Vec<Log>, LokiError> {
+    let mut logs = Vec::new();
+    for stream in request.streams {
+        let mut labels: Vec<(String, String)> = stream.stream.into_iter().collect();
+        labels.sort();
+
+        let entries = stream
+            .values
+            .into_iter()
+            .map(|value| {
+                let (timestamp, line, metadata) = match value {
+                    LokiValue::WithMetadata(timestamp, line, metadata) => {
+                        (timestamp, line, metadata)
+                    }
+                    LokiValue::Plain(timestamp, line) => (timestamp, line, HashMap::new()),
+                };
+                let time_unix_nano = timestamp
+                    .parse()
+                    .map_err(|_| LokiError::InvalidTimestamp(timestamp))?;
+                let mut metadata: Vec<(String, String)> = metadata.into_iter().collect();
+                metadata.sort();
+                Ok(Entry {
+                    time_unix_nano,
+                    line,
+                    metadata,
+                })
+            })
+            .collect::<Result<Vec<Entry>, LokiError>>()?;
+
+        logs.extend(convert_stream(labels, entries));
+    }
+    Ok(logs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::loki::{EntryAdapter, LabelPairAdapter};
+    use prost_types::Timestamp;
+
+    #[test]
+    fn test_convert_push_request() {
+        let request = PushRequest {
+            streams: vec![StreamAdapter {
+                labels: r#"{job="api", level="warn", env="dev"}"#.to_string(),
+                entries: vec![EntryAdapter {
+                    timestamp: Some(Timestamp {
+                        seconds: 1_700_000_000,
+                        nanos: 5,
+                    }),
+                    line: "slow query".to_string(),
+                    structured_metadata: vec![LabelPairAdapter {
+                        name: "trace_id".to_string(),
+                        value: "0af7651916cd43dd8448eb211c80319c".to_string(),
+                    }],
+                }],
+                hash: 0,
+            }],
+        };
+
+        let logs = convert_push_request(request).unwrap();
+
+        assert_eq!(logs.len(), 1);
+        let log = &logs[0];
+        assert_eq!(log.time_unix_nano, 1_700_000_000_000_000_005);
+        assert_eq!(log.body, "slow query");
+        assert_eq!(log.severity_level, SeverityLevel::Warn);
+        assert_eq!(log.severity_text.as_deref(), Some("warn"));
+        assert_eq!(log.service_name.as_deref(), Some("api"));
+        assert_eq!(log.attributes.get_string("env"), Some("dev"));
+        assert_eq!(
+            log.trace_id.as_deref(),
+            Some("0af7651916cd43dd8448eb211c80319c")
+        );
+        assert_eq!(log.resource.as_ref().unwrap().service_name(), Some("api"));
+    }
+
+    #[test]
+    fn test_convert_push_request_invalid_labels() {
+        let request = PushRequest {
+            streams: vec![StreamAdapter {
+                labels: r#"{job="api"#.to_string(),
+                ..Default::default()
+            }],
+        };
+
+        assert!(matches!(
+            convert_push_request(request),
+            Err(LokiError::InvalidLabels { .. })
+        ));
+    }
+
+    #[test]
+    fn test_convert_push_request_invalid_timestamp() {
+        let request = PushRequest {
+            streams: vec![StreamAdapter {
+                labels: r#"{job="api"}"#.to_string(),
+                entries: vec![EntryAdapter {
+                    timestamp: Some(Timestamp {
+                        seconds: i64::MAX,
+                        nanos: 0,
+                    }),
+                    line: "slow query".to_string(),
+                    ..Default::default()
+                }],
+                hash: 0,
+            }],
+        };
+
+        assert!(matches!(
+            convert_push_request(request),
+            Err(LokiError::InvalidTimestamp(_))
+        ));
+    }
+
+    #[test]
+    fn test_convert_json_push_request() {
+        let json = r#"{"streams": [{
+            "stream": {"job": "worker", "service_name": "billing", "level": "ERROR"},
+            "values": [
+                ["1700000000000000000", "payment failed"],
+                ["1700000000000000001", "retrying", {"attempt": "2"}]
+            ]
+        }]}"#;
+
+        let logs = convert_json_push_request(serde_json::from_str(json).unwrap()).unwrap();
+
+        assert_eq!(logs.len(), 2);
+        assert_eq!(logs[0].service_name.as_deref(), Some("billing"));
+        assert_eq!(logs[0].severity_level, SeverityLevel::Error);
+        assert!(logs[0].is_error());
+        assert_eq!(logs[1].time_unix_nano, 1_700_000_000_000_000_001);
+        assert_eq!(logs[1].attributes.get_string("attempt"), Some("2"));
+        assert_eq!(logs[1].attributes.get_string("job"), Some("worker"));
+    }
+
+    #[test]
+    fn test_convert_json_push_request_without_level() {
+        let json = r#"{"streams": [{"stream": {}, "values": [["1", "hello"]]}]}"#;
+        let logs = convert_json_push_request(serde_json::from_str(json).unwrap()).unwrap();
+
+        assert_eq!(logs[0].severity_level, SeverityLevel::Unspecified);
+        assert_eq!(logs[0].service_name, None);
+
+        let json = r#"{"streams": [{"stream": {}, "values": [["yesterday", "hello"]]}]}"#;
+        assert_eq!(
+            convert_json_push_request(serde_json::from_str(json).unwrap()).unwrap_err(),
+            LokiError::InvalidTimestamp("yesterday".to_string())
+        );
+    }
+}
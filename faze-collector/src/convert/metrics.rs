@@ -1,10 +1,13 @@
-use crate::convert::{convert_attributes, convert_resource};
+use crate::convert::{bytes_to_hex, convert_attributes, convert_resource, convert_scope};
 use crate::proto::opentelemetry::proto::metrics::v1::{
-    HistogramDataPoint, Metric as OtlpMetric, NumberDataPoint, ResourceMetrics, SummaryDataPoint,
-    metric,
+    Exemplar as OtlpExemplar, ExponentialHistogramDataPoint, HistogramDataPoint,
+    Metric as OtlpMetric, NumberDataPoint, ResourceMetrics, SummaryDataPoint, exemplar,
+    exponential_histogram_data_point::Buckets, metric,
 };
 use faze::models::metric::{
-    AggregationTemporality, Metric as FazeMetric, MetricDataPoint, MetricType as FazeMetricType,
+    AggregationTemporality, Exemplar, ExponentialBuckets, ExponentialHistogramData, HistogramData,
+    Metric as FazeMetric, MetricDataPoint, MetricType as FazeMetricType, QuantileValue,
+    SummaryData,
 };
 
 fn convert_metric(otlp_metric: OtlpMetric, service_name: Option<String>) -> Option<FazeMetric> {
@@ -68,6 +71,25 @@ fn convert_metric(otlp_metric: OtlpMetric, service_name: Option<String>) -> Opti
                 service_name,
             ))
         }
+        Some(metric::Data::ExponentialHistogram(hist)) => {
+            let data_points = hist
+                .data_points
+                .into_iter()
+                .filter(|dp| EXPONENTIAL_SCALES.contains(&dp.scale))
+                .map(convert_exponential_histogram_data_point)
+                .collect();
+            let temporality = convert_temporality(hist.aggregation_temporality);
+
+            Some(FazeMetric::new(
+                name,
+                description,
+                unit,
+                FazeMetricType::ExponentialHistogram,
+                temporality,
+                data_points,
+                service_name,
+            ))
+        }
         Some(metric::Data::Summary(summary)) => {
             let data_points = summary
                 .data_points
@@ -93,16 +115,20 @@ pub fn convert_resource_metrics(resource_metrics: Vec<ResourceMetrics>) -> Vec<F
     let mut faze_metrics = Vec::new();
 
     for rm in resource_metrics {
-        let service_name = rm
-            .resource
+        let resource = rm.resource.as_ref().map(convert_resource);
+        let service_name = resource
             .as_ref()
-            .map(convert_resource)
             .and_then(|r| r.service_name().map(|s| s.to_string()));
 
         for sm in rm.scope_metrics {
+            let scope = sm.scope.as_ref().map(convert_scope);
             for metric in sm.metrics {
                 if let Some(gm) = convert_metric(metric, service_name.clone()) {
-                    faze_metrics.push(gm);
+                    faze_metrics.push(FazeMetric {
+                        resource: resource.clone(),
+                        scope: scope.clone(),
+                        ..gm
+                    });
                 }
             }
         }
@@ -111,6 +137,31 @@ pub fn convert_resource_metrics(resource_metrics: Vec<ResourceMetrics>) -> Vec<F
     faze_metrics
 }
 
+fn convert_exemplar(exemplar: &OtlpExemplar) -> Exemplar {
+    let value = match exemplar.value {
+        Some(exemplar::Value::AsDouble(d)) => d,
+        Some(exemplar::Value::AsInt(i)) => i as f64,
+        None => 0.0,
+    };
+
+    let converted = Exemplar::new(exemplar.time_unix_nano as i64, value)
+        .with_filtered_attributes(convert_attributes(&exemplar.filtered_attributes));
+
+    // Exemplars recorded outside of a sampled span carry no trace context
+    if exemplar.trace_id.is_empty() {
+        converted
+    } else {
+        converted.with_trace(
+            bytes_to_hex(&exemplar.trace_id),
+            bytes_to_hex(&exemplar.span_id),
+        )
+    }
+}
+
+fn convert_exemplars(exemplars: &[OtlpExemplar]) -> Vec<Exemplar> {
+    exemplars.iter().map(convert_exemplar).collect()
+}
+
 fn convert_number_data_point(dp: NumberDataPoint) -> MetricDataPoint {
     let value = match dp.value {
         Some(v) => match v {
@@ -130,6 +181,7 @@ fn convert_number_data_point(dp: NumberDataPoint) -> MetricDataPoint {
         value,
         convert_attributes(&dp.attributes),
     )
+    .with_exemplars(convert_exemplars(&dp.exemplars))
 }
 
 fn convert_histogram_data_point(dp: HistogramDataPoint) -> MetricDataPoint {
@@ -141,15 +193,73 @@ fn convert_histogram_data_point(dp: HistogramDataPoint) -> MetricDataPoint {
         value,
         convert_attributes(&dp.attributes),
     )
+    .with_histogram(HistogramData {
+        count: dp.count,
+        sum: dp.sum,
+        min: dp.min,
+        max: dp.max,
+        explicit_bounds: dp.explicit_bounds,
+        bucket_counts: dp.bucket_counts,
+    })
+    .with_exemplars(convert_exemplars(&dp.exemplars))
+}
+
+/// Scales OTLP allows for exponential histograms; points with any other scale are dropped
+const EXPONENTIAL_SCALES: std::ops::RangeInclusive<i32> = -10..=20;
+
+fn convert_exponential_buckets(buckets: Option<Buckets>) -> ExponentialBuckets {
+    buckets
+        .map(|b| ExponentialBuckets {
+            offset: b.offset,
+            bucket_counts: b.bucket_counts,
+        })
+        .unwrap_or_default()
+}
+
+fn convert_exponential_histogram_data_point(dp: ExponentialHistogramDataPoint) -> MetricDataPoint {
+    let value = dp.sum.unwrap_or(dp.count as f64);
+
+    MetricDataPoint::new(
+        dp.time_unix_nano as i64,
+        Some(dp.start_time_unix_nano as i64),
+        value,
+        convert_attributes(&dp.attributes),
+    )
+    .with_exponential_histogram(ExponentialHistogramData {
+        count: dp.count,
+        sum: dp.sum,
+        min: dp.min,
+        max: dp.max,
+        scale: dp.scale,
+        zero_count: dp.zero_count,
+        zero_threshold: dp.zero_threshold,
+        positive: convert_exponential_buckets(dp.positive),
+        negative: convert_exponential_buckets(dp.negative),
+    })
+    .with_exemplars(convert_exemplars(&dp.exemplars))
 }
 
 fn convert_summary_data_point(dp: SummaryDataPoint) -> MetricDataPoint {
+    let summary = SummaryData {
+        count: dp.count,
+        sum: dp.sum,
+        quantile_values: dp
+            .quantile_values
+            .iter()
+            .map(|qv| QuantileValue {
+                quantile: qv.quantile,
+                value: qv.value,
+            })
+            .collect(),
+    };
+
     MetricDataPoint::new(
         dp.time_unix_nano as i64,
         Some(dp.start_time_unix_nano as i64),
         dp.sum,
         convert_attributes(&dp.attributes),
     )
+    .with_summary(summary)
 }
 
 fn convert_temporality(t: i32) -> AggregationTemporality {
@@ -159,3 +269,231 @@ fn convert_temporality(t: i32) -> AggregationTemporality {
         _ => AggregationTemporality::Unspecified,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::opentelemetry::proto::common::v1::{AnyValue, KeyValue, any_value};
+    use crate::proto::opentelemetry::proto::metrics::v1::{
+        ExponentialHistogram, Gauge, Histogram, Summary, number_data_point,
+        summary_data_point::ValueAtQuantile,
+    };
+
+    fn create_histogram_metric(data_points: Vec<HistogramDataPoint>) -> OtlpMetric {
+        OtlpMetric {
+            name: "http.server.duration".to_string(),
+            description: String::new(),
+            unit: "ms".to_string(),
+            data: Some(metric::Data::Histogram(Histogram {
+                data_points,
+                aggregation_temporality: 1,
+            })),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_convert_histogram_data_point() {
+        let metric = create_histogram_metric(vec![HistogramDataPoint {
+            time_unix_nano: 2_000_000_000,
+            start_time_unix_nano: 1_000_000_000,
+            count: 6,
+            sum: Some(123.5),
+            min: Some(1.5),
+            max: Some(80.0),
+            explicit_bounds: vec![5.0, 25.0, 50.0],
+            bucket_counts: vec![1, 3, 1, 1],
+            ..Default::default()
+        }]);
+
+        let result = convert_metric(metric, None).unwrap();
+        assert_eq!(result.metric_type, FazeMetricType::Histogram);
+        assert_eq!(result.temporality, AggregationTemporality::Delta);
+
+        let dp = &result.data_points[0];
+        assert_eq!(dp.value, 123.5);
+        let histogram = dp.histogram.as_ref().unwrap();
+        assert_eq!(histogram.count, 6);
+        assert_eq!(histogram.sum, Some(123.5));
+        assert_eq!(histogram.min, Some(1.5));
+        assert_eq!(histogram.max, Some(80.0));
+        assert_eq!(histogram.explicit_bounds, vec![5.0, 25.0, 50.0]);
+        assert_eq!(histogram.bucket_counts, vec![1, 3, 1, 1]);
+    }
+
+    #[test]
+    fn test_convert_histogram_data_point_without_sum() {
+        let metric = create_histogram_metric(vec![HistogramDataPoint {
+            count: 4,
+            bucket_counts: vec![4],
+            ..Default::default()
+        }]);
+
+        let result = convert_metric(metric, None).unwrap();
+        let dp = &result.data_points[0];
+        assert_eq!(dp.value, 4.0);
+        assert_eq!(dp.histogram.as_ref().unwrap().sum, None);
+    }
+
+    #[test]
+    fn test_convert_exponential_histogram_data_point() {
+        let metric = OtlpMetric {
+            name: "rpc.server.duration".to_string(),
+            data: Some(metric::Data::ExponentialHistogram(ExponentialHistogram {
+                data_points: vec![ExponentialHistogramDataPoint {
+                    time_unix_nano: 2_000_000_000,
+                    count: 6,
+                    sum: Some(14.0),
+                    min: Some(0.0),
+                    max: Some(7.0),
+                    scale: 0,
+                    zero_count: 1,
+                    positive: Some(Buckets {
+                        offset: 0,
+                        bucket_counts: vec![2, 3],
+                    }),
+                    negative: None,
+                    ..Default::default()
+                }],
+                aggregation_temporality: 2,
+            })),
+            ..Default::default()
+        };
+
+        let result = convert_metric(metric, None).unwrap();
+        assert_eq!(result.metric_type, FazeMetricType::ExponentialHistogram);
+        assert_eq!(result.temporality, AggregationTemporality::Cumulative);
+
+        let dp = &result.data_points[0];
+        assert_eq!(dp.value, 14.0);
+        let exponential = dp.exponential_histogram.as_ref().unwrap();
+        assert_eq!(exponential.scale, 0);
+        assert_eq!(exponential.zero_count, 1);
+        assert_eq!(exponential.positive.bucket_counts, vec![2, 3]);
+        assert!(exponential.negative.bucket_counts.is_empty());
+
+        let explicit = dp.histogram.as_ref().unwrap();
+        assert_eq!(explicit.explicit_bounds, vec![0.0, 2.0]);
+        assert_eq!(explicit.bucket_counts, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_convert_exponential_histogram_drops_invalid_scale() {
+        let point = |scale| ExponentialHistogramDataPoint {
+            time_unix_nano: 2_000_000_000,
+            count: 1,
+            scale,
+            positive: Some(Buckets {
+                offset: 0,
+                bucket_counts: vec![1],
+            }),
+            ..Default::default()
+        };
+        let metric = OtlpMetric {
+            name: "rpc.server.duration".to_string(),
+            data: Some(metric::Data::ExponentialHistogram(ExponentialHistogram {
+                data_points: vec![point(i32::MIN), point(20), point(21)],
+                aggregation_temporality: 2,
+            })),
+            ..Default::default()
+        };
+
+        let result = convert_metric(metric, None).unwrap();
+        assert_eq!(result.data_points.len(), 1);
+        assert_eq!(
+            result.data_points[0]
+                .exponential_histogram
+                .as_ref()
+                .unwrap()
+                .scale,
+            20
+        );
+    }
+
+    #[test]
+    fn test_convert_exemplars() {
+        let metric = OtlpMetric {
+            name: "queue.depth".to_string(),
+            data: Some(metric::Data::Gauge(Gauge {
+                data_points: vec![NumberDataPoint {
+                    time_unix_nano: 2_000_000_000,
+                    value: Some(number_data_point::Value::AsInt(17)),
+                    exemplars: vec![
+                        OtlpExemplar {
+                            time_unix_nano: 1_900_000_000,
+                            value: Some(exemplar::Value::AsInt(17)),
+                            trace_id: vec![0x12, 0x34],
+                            span_id: vec![0xab, 0xcd],
+                            filtered_attributes: vec![KeyValue {
+                                key: "queue".to_string(),
+                                value: Some(AnyValue {
+                                    value: Some(any_value::Value::StringValue(
+                                        "orders".to_string(),
+                                    )),
+                                }),
+                            }],
+                        },
+                        OtlpExemplar {
+                            time_unix_nano: 1_950_000_000,
+                            value: Some(exemplar::Value::AsDouble(3.5)),
+                            ..Default::default()
+                        },
+                    ],
+                    ..Default::default()
+                }],
+            })),
+            ..Default::default()
+        };
+
+        let result = convert_metric(metric, None).unwrap();
+        let exemplars = &result.data_points[0].exemplars;
+        assert_eq!(exemplars.len(), 2);
+        assert_eq!(exemplars[0].value, 17.0);
+        assert_eq!(exemplars[0].trace_id.as_deref(), Some("1234"));
+        assert_eq!(exemplars[0].span_id.as_deref(), Some("abcd"));
+        assert_eq!(
+            exemplars[0].filtered_attributes.get_string("queue"),
+            Some("orders")
+        );
+        assert_eq!(exemplars[1].value, 3.5);
+        assert_eq!(exemplars[1].trace_id, None);
+        assert_eq!(exemplars[1].span_id, None);
+    }
+
+    #[test]
+    fn test_convert_summary_data_point() {
+        let metric = OtlpMetric {
+            name: "rpc.client.duration".to_string(),
+            data: Some(metric::Data::Summary(Summary {
+                data_points: vec![SummaryDataPoint {
+                    time_unix_nano: 2_000_000_000,
+                    count: 40,
+                    sum: 310.0,
+                    quantile_values: vec![
+                        ValueAtQuantile {
+                            quantile: 0.5,
+                            value: 6.5,
+                        },
+                        ValueAtQuantile {
+                            quantile: 0.99,
+                            value: 42.0,
+                        },
+                    ],
+                    ..Default::default()
+                }],
+            })),
+            ..Default::default()
+        };
+
+        let result = convert_metric(metric, None).unwrap();
+        assert_eq!(result.metric_type, FazeMetricType::Summary);
+
+        let dp = &result.data_points[0];
+        assert_eq!(dp.value, 310.0);
+        let summary = dp.summary.as_ref().unwrap();
+        assert_eq!(summary.count, 40);
+        assert_eq!(summary.sum, 310.0);
+        assert_eq!(summary.quantile_values.len(), 2);
+        assert_eq!(summary.value_at(0.99), Some(42.0));
+    }
+}
@@ -1,27 +1,38 @@
 use crate::proto::opentelemetry::proto::{
-    common::v1::{AnyValue, KeyValue, any_value},
+    common::v1::{AnyValue, InstrumentationScope, KeyValue, any_value},
     resource::v1::Resource,
 };
-use faze::models::{AttributeValue, Attributes, Resource as FazeResource};
+use faze::models::{
+    AttributeValue, Attributes, InstrumentationScope as FazeScope, Resource as FazeResource,
+};
 
+pub mod exposition;
+pub mod fluent;
+pub mod jaeger;
 pub mod logs;
+pub mod loki;
 pub mod metrics;
+pub mod prometheus;
+pub mod statsd;
+pub mod syslog;
+pub mod tail;
 pub mod traces;
+pub mod zipkin;
 
 /// Convert OTLP AnyValue to internal AttributeValue
 pub fn convert_any_value(value: &AnyValue) -> Option<AttributeValue> {
-    value.value.as_ref().and_then(|v| match v {
-        any_value::Value::StringValue(s) => Some(AttributeValue::String(s.clone())),
-        any_value::Value::BoolValue(b) => Some(AttributeValue::Bool(*b)),
-        any_value::Value::IntValue(i) => Some(AttributeValue::Int(*i)),
-        any_value::Value::DoubleValue(d) => Some(AttributeValue::Double(*d)),
-        any_value::Value::BytesValue(b) => Some(AttributeValue::Bytes(b.clone())),
+    value.value.as_ref().map(|v| match v {
+        any_value::Value::StringValue(s) => AttributeValue::String(s.clone()),
+        any_value::Value::BoolValue(b) => AttributeValue::Bool(*b),
+        any_value::Value::IntValue(i) => AttributeValue::Int(*i),
+        any_value::Value::DoubleValue(d) => AttributeValue::Double(*d),
+        any_value::Value::BytesValue(b) => AttributeValue::Bytes(b.clone()),
         any_value::Value::ArrayValue(arr) => {
-            let values: Vec<AttributeValue> =
-                arr.values.iter().filter_map(convert_any_value).collect();
-            Some(AttributeValue::Array(values))
+            AttributeValue::Array(arr.values.iter().filter_map(convert_any_value).collect())
+        }
+        any_value::Value::KvlistValue(kvlist) => {
+            AttributeValue::Map(convert_attributes(&kvlist.values))
         }
-        any_value::Value::KvlistValue(_) => None, // Not supported for now
     })
 }
 
@@ -41,7 +52,9 @@ pub fn convert_any_value_to_string(value: &AnyValue) -> Option<String> {
                 .collect();
             format!("[{}]", values.join(","))
         }
-        any_value::Value::KvlistValue(_) => "<kvlist unsupported>".to_string(),
+        any_value::Value::KvlistValue(kvlist) => {
+            serde_json::to_string(&convert_attributes(&kvlist.values)).unwrap_or_default()
+        }
     })
 }
 
@@ -63,6 +76,21 @@ pub fn convert_resource(resource: &Resource) -> FazeResource {
     FazeResource::new(attributes)
 }
 
+/// Convert OTLP InstrumentationScope to internal InstrumentationScope
+pub fn convert_scope(scope: &InstrumentationScope) -> FazeScope {
+    let version = if scope.version.is_empty() {
+        None
+    } else {
+        Some(scope.version.clone())
+    };
+
+    FazeScope::new(
+        scope.name.clone(),
+        version,
+        convert_attributes(&scope.attributes),
+    )
+}
+
 /// Convert bytes to hex string
 pub fn bytes_to_hex(bytes: &[u8]) -> String {
     bytes.iter().map(|b| format!("{:02x}", b)).collect()
@@ -71,6 +99,7 @@ pub fn bytes_to_hex(bytes: &[u8]) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::proto::opentelemetry::proto::common::v1::KeyValueList;
     use std;
 
     #[test]
@@ -144,6 +173,35 @@ mod tests {
         assert_eq!(result.service_name(), Some("my-service"));
     }
 
+    #[test]
+    fn test_convert_scope() {
+        let scope = InstrumentationScope {
+            name: "opentelemetry-http".to_string(),
+            version: "0.27.0".to_string(),
+            attributes: vec![KeyValue {
+                key: "library.language".to_string(),
+                value: Some(AnyValue {
+                    value: Some(any_value::Value::StringValue("rust".to_string())),
+                }),
+            }],
+            dropped_attributes_count: 0,
+        };
+
+        let result = convert_scope(&scope);
+        assert_eq!(result.name, "opentelemetry-http");
+        assert_eq!(result.version, Some("0.27.0".to_string()));
+        assert_eq!(
+            result.attributes.get_string("library.language"),
+            Some("rust")
+        );
+
+        let unversioned = convert_scope(&InstrumentationScope {
+            name: "manual".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(unversioned.version, None);
+    }
+
     #[test]
     fn test_convert_any_value_double() {
         let value = AnyValue {
@@ -204,6 +262,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_convert_any_value_kvlist() {
+        let kv = |key: &str, value: any_value::Value| KeyValue {
+            key: key.to_string(),
+            value: Some(AnyValue { value: Some(value) }),
+        };
+        let value = AnyValue {
+            value: Some(any_value::Value::KvlistValue(KeyValueList {
+                values: vec![
+                    kv("path", any_value::Value::StringValue("/cart".to_string())),
+                    kv(
+                        "user",
+                        any_value::Value::KvlistValue(KeyValueList {
+                            values: vec![kv("id", any_value::Value::IntValue(7))],
+                        }),
+                    ),
+                ],
+            })),
+        };
+
+        let result = convert_any_value(&value).unwrap();
+        let AttributeValue::Map(map) = result else {
+            panic!("expected a map, got {:?}", result);
+        };
+        assert_eq!(map.get_string("path"), Some("/cart"));
+        assert_eq!(map.get_map("user").unwrap().get_int("id"), Some(7));
+
+        let rendered = convert_any_value_to_string(&value).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&rendered).unwrap();
+        assert_eq!(json["path"], "/cart");
+        assert_eq!(json["user"]["id"], 7);
+    }
+
     #[test]
     fn test_convert_any_value_none() {
         let value = AnyValue { value: None };
@@ -0,0 +1,265 @@
+use crate::proto::prometheus::{
+    MetricMetadata, TimeSeries, WriteRequest, metric_metadata::MetricType as PromMetricType,
+};
+use faze::models::{Attributes, Metric, MetricDataPoint, Resource};
+use std::collections::HashMap;
+
+/// Label holding the metric name
+const NAME_LABEL: &str = "__name__";
+
+/// Suffixes of the series Prometheus splits histograms and summaries into
+const AGGREGATE_SUFFIXES: &[&str] = &["_bucket", "_count", "_sum"];
+
+/// Suffix OpenMetrics leaves out of counter family names
+const COUNTER_SUFFIX: &str = "_total";
+
+/// Find the metadata for a series, which is keyed by metric family rather than series name
+fn find_metadata<'a>(
+    metadata: &'a HashMap<&str, &MetricMetadata>,
+    name: &str,
+) -> Option<&'a MetricMetadata> {
+    if let Some(m) = metadata.get(name) {
+        return Some(m);
+    }
+    AGGREGATE_SUFFIXES
+        .iter()
+        .chain([&COUNTER_SUFFIX])
+        .filter_map(|suffix| name.strip_suffix(suffix))
+        .find_map(|family| metadata.get(family).copied())
+}
+
+/// Decide whether a series counts up, from its metadata or the `_total` naming convention
+fn is_counter(name: &str, metadata: Option<&MetricMetadata>) -> bool {
+    match metadata.map(|m| m.r#type()) {
+        Some(PromMetricType::Counter) => true,
+        // Bucket counts, sums and counts of a histogram or summary only ever grow
+        Some(PromMetricType::Histogram | PromMetricType::Summary) => AGGREGATE_SUFFIXES
+            .iter()
+            .any(|suffix| name.ends_with(suffix)),
+        Some(PromMetricType::Unknown) | None => name.ends_with(COUNTER_SUFFIX),
+        Some(_) => false,
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Convert a remote-write series to internal Metric
+///
+/// Returns `None` for series without a name or without any storable samples.
+pub fn convert_time_series(
+    series: &TimeSeries,
+    metadata: &HashMap<&str, &MetricMetadata>,
+) -> Option<Metric> {
+    let mut name = None;
+    let mut attributes = Attributes::new();
+    for label in &series.labels {
+        if label.name == NAME_LABEL {
+            name = Some(label.value.clone());
+        } else {
+            attributes.insert(label.name.clone(), label.value.clone());
+        }
+    }
+    let name = name?;
+
+    let data_points: Vec<MetricDataPoint> = series
+        .samples
+        .iter()
+        // Samples with out-of-range timestamps are dropped
+        .filter_map(|sample| {
+            Some(MetricDataPoint::new(
+                sample.timestamp.checked_mul(1_000_000)?,
+                None,
+                sample.value,
+                attributes.clone(),
+            ))
+        })
+        .collect();
+    if data_points.is_empty() {
+        return None;
+    }
+
+    let service_name = attributes
+        .get_string("service_name")
+        .or_else(|| attributes.get_string("job"))
+        .map(str::to_string);
+    let family = find_metadata(metadata, &name);
+
+    let mut metric = if is_counter(&name, family) {
+        Metric::counter(name, data_points, service_name.clone())
+    } else {
+        Metric::gauge(name, data_points, service_name.clone())
+    };
+    if let Some(family) = family {
+        metric.description = non_empty(&family.help);
+        metric.unit = non_empty(&family.unit);
+    }
+
+    match service_name {
+        Some(service_name) => Some(metric.with_resource(Resource::for_service(service_name))),
+        None => Some(metric),
+    }
+}
+
+/// Convert a remote-write request to list of internal Metrics, one per series
+pub fn convert_write_request(request: &WriteRequest) -> Vec<Metric> {
+    let metadata: HashMap<&str, &MetricMetadata> = request
+        .metadata
+        .iter()
+        .map(|m| (m.metric_family_name.as_str(), m))
+        .collect();
+
+    request
+        .timeseries
+        .iter()
+        .filter_map(|series| convert_time_series(series, &metadata))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::prometheus::{Label, Sample};
+    use faze::models::{AggregationTemporality, MetricType};
+
+    fn series(labels: &[(&str, &str)], samples: &[(i64, f64)]) -> TimeSeries {
+        TimeSeries {
+            labels: labels
+                .iter()
+                .map(|(name, value)| Label {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                })
+                .collect(),
+            samples: samples
+                .iter()
+                .map(|&(timestamp, value)| Sample { value, timestamp })
+                .collect(),
+        }
+    }
+
+    fn metadata(family: &str, metric_type: PromMetricType) -> MetricMetadata {
+        MetricMetadata {
+            r#type: metric_type as i32,
+            metric_family_name: family.to_string(),
+            help: format!("Help for {family}"),
+            unit: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_convert_write_request() {
+        let request = WriteRequest {
+            timeseries: vec![
+                series(
+                    &[
+                        ("__name__", "http_requests_total"),
+                        ("job", "api"),
+                        ("method", "GET"),
+                    ],
+                    &[(1_700_000_000_000, 10.0), (1_700_000_015_000, 12.0)],
+                ),
+                series(
+                    &[
+                        ("__name__", "process_open_fds"),
+                        ("job", "api"),
+                        ("service_name", "checkout"),
+                    ],
+                    &[(1_700_000_000_000, 42.0)],
+                ),
+            ],
+            metadata: vec![metadata("http_requests", PromMetricType::Counter)],
+        };
+
+        let metrics = convert_write_request(&request);
+
+        assert_eq!(metrics.len(), 2);
+        let requests = &metrics[0];
+        assert_eq!(requests.name, "http_requests_total");
+        assert_eq!(requests.metric_type, MetricType::Sum);
+        assert_eq!(requests.temporality, AggregationTemporality::Cumulative);
+        assert_eq!(requests.service_name.as_deref(), Some("api"));
+        assert_eq!(
+            requests.description.as_deref(),
+            Some("Help for http_requests")
+        );
+        assert_eq!(requests.data_points.len(), 2);
+        assert_eq!(
+            requests.data_points[1].time_unix_nano,
+            1_700_000_015_000_000_000
+        );
+        assert_eq!(requests.data_points[1].value, 12.0);
+        assert_eq!(
+            requests.data_points[0].attributes.get_string("method"),
+            Some("GET")
+        );
+        assert!(requests.data_points[0].attributes.get("__name__").is_none());
+
+        let fds = &metrics[1];
+        assert_eq!(fds.metric_type, MetricType::Gauge);
+        assert_eq!(fds.service_name.as_deref(), Some("checkout"));
+        assert_eq!(
+            fds.resource.as_ref().unwrap().service_name(),
+            Some("checkout")
+        );
+    }
+
+    #[test]
+    fn test_convert_histogram_series() {
+        let request = WriteRequest {
+            timeseries: vec![
+                series(
+                    &[("__name__", "rpc_duration_seconds_bucket"), ("le", "0.5")],
+                    &[(1_000, 3.0)],
+                ),
+                series(&[("__name__", "temperature")], &[(1_000, 21.5)]),
+            ],
+            metadata: vec![
+                metadata("rpc_duration_seconds", PromMetricType::Histogram),
+                metadata("temperature", PromMetricType::Gauge),
+            ],
+        };
+
+        let metrics = convert_write_request(&request);
+
+        assert_eq!(metrics[0].metric_type, MetricType::Sum);
+        assert_eq!(
+            metrics[0].description.as_deref(),
+            Some("Help for rpc_duration_seconds")
+        );
+        assert_eq!(metrics[1].metric_type, MetricType::Gauge);
+        assert_eq!(metrics[1].service_name, None);
+    }
+
+    #[test]
+    fn test_convert_skips_unnamed_series() {
+        let request = WriteRequest {
+            timeseries: vec![
+                series(&[("__name__", "up")], &[(1_000, f64::NAN)]),
+                series(&[("job", "api")], &[(1_000, 1.0)]),
+            ],
+            metadata: vec![],
+        };
+
+        // Storage leaves out the stale marker's NaN point
+        let metrics = convert_write_request(&request);
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].name, "up");
+    }
+
+    #[test]
+    fn test_convert_skips_out_of_range_timestamps() {
+        let request = WriteRequest {
+            timeseries: vec![series(
+                &[("__name__", "up")],
+                &[(i64::MAX, 1.0), (1_000, 1.0)],
+            )],
+            metadata: vec![],
+        };
+
+        let metrics = convert_write_request(&request);
+        assert_eq!(metrics[0].data_points.len(), 1);
+        assert_eq!(metrics[0].data_points[0].time_unix_nano, 1_000_000_000);
+    }
+}
@@ -0,0 +1,448 @@
+use faze::models::{
+    AggregationTemporality, Attributes, HistogramData, Metric, MetricDataPoint, MetricType,
+    Resource,
+};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use thiserror::Error;
+
+/// Bucket bounds for timers and histograms, matching the OpenTelemetry SDK defaults
+const DEFAULT_BOUNDS: &[f64] = &[
+    0.0, 5.0, 10.0, 25.0, 50.0, 75.0, 100.0, 250.0, 500.0, 750.0, 1000.0, 2500.0, 5000.0, 7500.0,
+    10000.0,
+];
+
+/// DogStatsD tag carrying the service name
+const SERVICE_TAG: &str = "service";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum StatsdError {
+    #[error("missing ':' between name and value")]
+    MissingValue,
+
+    #[error("missing '|' before metric type")]
+    MissingType,
+
+    #[error("unknown metric type {0:?}")]
+    UnknownType(String),
+
+    #[error("invalid value {0:?}")]
+    InvalidValue(String),
+
+    #[error("invalid sample rate {0:?}")]
+    InvalidSampleRate(String),
+}
+
+/// StatsD metric type, from the field after the first `|`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum StatsdType {
+    Counter,
+    Gauge,
+    Timer,
+    Histogram,
+    Distribution,
+    Set,
+}
+
+impl StatsdType {
+    fn parse(value: &str) -> Result<Self, StatsdError> {
+        match value {
+            "c" => Ok(Self::Counter),
+            "g" => Ok(Self::Gauge),
+            "ms" => Ok(Self::Timer),
+            "h" => Ok(Self::Histogram),
+            "d" => Ok(Self::Distribution),
+            "s" => Ok(Self::Set),
+            other => Err(StatsdError::UnknownType(other.to_string())),
+        }
+    }
+}
+
+/// Value carried by a StatsD line
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatsdValue {
+    Number(f64),
+    /// Signed gauge value, which adjusts the current gauge rather than replacing it
+    GaugeDelta(f64),
+    /// Member of a set, which is only ever counted
+    SetMember(String),
+}
+
+/// A single measurement parsed from a StatsD or DogStatsD line
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatsdSample {
+    pub name: String,
+    pub metric_type: StatsdType,
+    pub value: StatsdValue,
+    pub sample_rate: f64,
+    pub tags: Vec<(String, String)>,
+}
+
+fn parse_number(value: &str) -> Result<f64, StatsdError> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|v| v.is_finite())
+        .ok_or_else(|| StatsdError::InvalidValue(value.to_string()))
+}
+
+fn parse_tags(tags: &str) -> Vec<(String, String)> {
+    tags.split(',')
+        .filter(|tag| !tag.is_empty())
+        .map(|tag| match tag.split_once(':') {
+            Some((key, value)) => (key.to_string(), value.to_string()),
+            None => (tag.to_string(), String::new()),
+        })
+        .collect()
+}
+
+/// Parse one StatsD line, such as `api.requests:1|c|@0.5|#env:dev,route:/users`
+///
+/// DogStatsD lines may carry several values (`name:1:2:3|h`), so a line can yield more than
+/// one sample. Events (`_e{...}`) and service checks (`_sc|...`) aren't metrics and yield none.
+pub fn parse_line(line: &str) -> Result<Vec<StatsdSample>, StatsdError> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with("_e{") || line.starts_with("_sc|") {
+        return Ok(Vec::new());
+    }
+
+    let (name, rest) = line.split_once(':').ok_or(StatsdError::MissingValue)?;
+    let mut fields = rest.split('|');
+    let values = fields.next().unwrap_or_default();
+    let metric_type = StatsdType::parse(fields.next().ok_or(StatsdError::MissingType)?)?;
+
+    let mut sample_rate = 1.0;
+    let mut tags = Vec::new();
+    for field in fields {
+        if let Some(rate) = field.strip_prefix('@') {
+            sample_rate = rate
+                .parse::<f64>()
+                .ok()
+                .filter(|r| *r > 0.0 && *r <= 1.0)
+                .ok_or_else(|| StatsdError::InvalidSampleRate(rate.to_string()))?;
+        } else if let Some(field_tags) = field.strip_prefix('#') {
+            tags.extend(parse_tags(field_tags));
+        }
+        // Container IDs (`c:`) and client timestamps (`T`) are not used
+    }
+    tags.sort();
+
+    let values: Vec<&str> = match metric_type {
+        // Set members may contain ':' themselves
+        StatsdType::Set => vec![values],
+        _ => values.split(':').collect(),
+    };
+
+    values
+        .into_iter()
+        .map(|value| {
+            let value = match metric_type {
+                StatsdType::Set => StatsdValue::SetMember(value.to_string()),
+                StatsdType::Gauge if value.starts_with(['+', '-']) => {
+                    StatsdValue::GaugeDelta(parse_number(value)?)
+                }
+                _ => StatsdValue::Number(parse_number(value)?),
+            };
+            Ok(StatsdSample {
+                name: name.to_string(),
+                metric_type,
+                value,
+                sample_rate,
+                tags: tags.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Series are identified by name, type and tags
+type SeriesKey = (String, StatsdType, Vec<(String, String)>);
+
+/// Values recorded for one series during a flush interval
+#[derive(Debug)]
+enum Series {
+    Counter(f64),
+    Gauge(f64),
+    /// (value, weight) pairs, where the weight undoes the client's sampling
+    Distribution(Vec<(f64, f64)>),
+    Set(HashSet<String>),
+}
+
+/// Aggregates StatsD samples between flushes, as the StatsD daemon does
+///
+/// Counters and distributions are reset on every flush and reported as deltas. Gauges keep
+/// their value, so a later `+1` adjusts it, but are only reported in intervals that updated
+/// them.
+#[derive(Debug, Default)]
+pub struct StatsdAggregator {
+    series: BTreeMap<SeriesKey, Series>,
+    gauges: HashMap<SeriesKey, f64>,
+    last_flush_unix_nano: Option<i64>,
+}
+
+impl StatsdAggregator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a sample in the current interval
+    pub fn record(&mut self, sample: StatsdSample) {
+        let key = (sample.name, sample.metric_type, sample.tags);
+        let weight = 1.0 / sample.sample_rate;
+
+        match (sample.metric_type, sample.value) {
+            (StatsdType::Counter, StatsdValue::Number(value)) => {
+                if let Series::Counter(total) =
+                    self.series.entry(key).or_insert(Series::Counter(0.0))
+                {
+                    *total += value * weight;
+                }
+            }
+            (StatsdType::Gauge, value) => {
+                let current = self.gauges.entry(key.clone()).or_insert(0.0);
+                match value {
+                    StatsdValue::GaugeDelta(delta) => *current += delta,
+                    StatsdValue::Number(value) => *current = value,
+                    StatsdValue::SetMember(_) => {}
+                }
+                self.series.insert(key, Series::Gauge(*current));
+            }
+            (StatsdType::Set, StatsdValue::SetMember(member)) => {
+                if let Series::Set(members) = self
+                    .series
+                    .entry(key)
+                    .or_insert_with(|| Series::Set(HashSet::new()))
+                {
+                    members.insert(member);
+                }
+            }
+            (_, StatsdValue::Number(value)) => {
+                if let Series::Distribution(values) = self
+                    .series
+                    .entry(key)
+                    .or_insert_with(|| Series::Distribution(Vec::new()))
+                {
+                    values.push((value, weight));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Turn everything recorded since the last flush into metrics, stamped with `now_unix_nano`
+    pub fn flush(&mut self, now_unix_nano: i64) -> Vec<Metric> {
+        let start_time = self.last_flush_unix_nano.replace(now_unix_nano);
+
+        std::mem::take(&mut self.series)
+            .into_iter()
+            .map(|((name, statsd_type, tags), series)| {
+                let service_name = tags
+                    .iter()
+                    .find(|(key, _)| key == SERVICE_TAG)
+                    .map(|(_, value)| value.clone());
+                let attributes: Attributes = tags
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect();
+
+                let (metric_type, temporality, data_point) = match series {
+                    Series::Counter(total) => (
+                        MetricType::Sum,
+                        AggregationTemporality::Delta,
+                        MetricDataPoint::new(now_unix_nano, start_time, total, attributes),
+                    ),
+                    Series::Gauge(value) => (
+                        MetricType::Gauge,
+                        AggregationTemporality::Unspecified,
+                        MetricDataPoint::new(now_unix_nano, None, value, attributes),
+                    ),
+                    Series::Set(members) => (
+                        MetricType::Gauge,
+                        AggregationTemporality::Unspecified,
+                        MetricDataPoint::new(now_unix_nano, None, members.len() as f64, attributes),
+                    ),
+                    Series::Distribution(values) => {
+                        let histogram = to_histogram(&values);
+                        (
+                            MetricType::Histogram,
+                            AggregationTemporality::Delta,
+                            MetricDataPoint::new(
+                                now_unix_nano,
+                                start_time,
+                                histogram.sum.unwrap_or_default(),
+                                attributes,
+                            )
+                            .with_histogram(histogram),
+                        )
+                    }
+                };
+
+                let unit = (statsd_type == StatsdType::Timer).then(|| "ms".to_string());
+                let metric = Metric::new(
+                    name,
+                    None,
+                    unit,
+                    metric_type,
+                    temporality,
+                    vec![data_point],
+                    service_name.clone(),
+                );
+                match service_name {
+                    Some(service_name) => metric.with_resource(Resource::for_service(service_name)),
+                    None => metric,
+                }
+            })
+            .collect()
+    }
+}
+
+fn to_histogram(values: &[(f64, f64)]) -> HistogramData {
+    let mut bucket_counts = vec![0.0; DEFAULT_BOUNDS.len() + 1];
+    let (mut count, mut sum) = (0.0, 0.0);
+    let (mut min, mut max) = (f64::INFINITY, f64::NEG_INFINITY);
+
+    for &(value, weight) in values {
+        let bucket = DEFAULT_BOUNDS.partition_point(|&bound| bound < value);
+        bucket_counts[bucket] += weight;
+        count += weight;
+        sum += value * weight;
+        min = min.min(value);
+        max = max.max(value);
+    }
+
+    HistogramData {
+        count: count.round() as u64,
+        sum: Some(sum),
+        min: Some(min),
+        max: Some(max),
+        explicit_bounds: DEFAULT_BOUNDS.to_vec(),
+        bucket_counts: bucket_counts
+            .into_iter()
+            .map(|c| c.round() as u64)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record_all(aggregator: &mut StatsdAggregator, lines: &[&str]) {
+        for line in lines {
+            for sample in parse_line(line).unwrap() {
+                aggregator.record(sample);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let samples = parse_line("api.requests:2|c|@0.5|#env:dev,canary").unwrap();
+
+        assert_eq!(
+            samples,
+            vec![StatsdSample {
+                name: "api.requests".to_string(),
+                metric_type: StatsdType::Counter,
+                value: StatsdValue::Number(2.0),
+                sample_rate: 0.5,
+                tags: vec![
+                    ("canary".to_string(), String::new()),
+                    ("env".to_string(), "dev".to_string()),
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parse_line_variants() {
+        assert_eq!(
+            parse_line("queue.depth:-3|g").unwrap()[0].value,
+            StatsdValue::GaugeDelta(-3.0)
+        );
+        assert_eq!(
+            parse_line("users.seen:urn:user:1|s").unwrap()[0].value,
+            StatsdValue::SetMember("urn:user:1".to_string())
+        );
+        assert_eq!(parse_line("db.query:1:2:3|h|#db:pg").unwrap().len(), 3);
+        assert!(parse_line("_e{5,4}:title|text").unwrap().is_empty());
+        assert!(parse_line("_sc|redis.can_connect|0").unwrap().is_empty());
+
+        assert_eq!(parse_line("api.requests"), Err(StatsdError::MissingValue));
+        assert_eq!(parse_line("api.requests:1"), Err(StatsdError::MissingType));
+        assert_eq!(
+            parse_line("api.requests:1|x"),
+            Err(StatsdError::UnknownType("x".to_string()))
+        );
+        assert_eq!(
+            parse_line("api.requests:one|c"),
+            Err(StatsdError::InvalidValue("one".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_flush_counters_and_gauges() {
+        let mut aggregator = StatsdAggregator::new();
+        record_all(
+            &mut aggregator,
+            &[
+                "api.requests:1|c|#service:checkout",
+                "api.requests:1|c|@0.5|#service:checkout",
+                "queue.depth:10|g",
+                "queue.depth:+5|g",
+                "users.seen:alice|s",
+                "users.seen:bob|s",
+                "users.seen:alice|s",
+            ],
+        );
+
+        let metrics = aggregator.flush(1_000);
+
+        assert_eq!(metrics.len(), 3);
+        let requests = &metrics[0];
+        assert_eq!(requests.name, "api.requests");
+        assert_eq!(requests.metric_type, MetricType::Sum);
+        assert_eq!(requests.temporality, AggregationTemporality::Delta);
+        assert_eq!(requests.data_points[0].value, 3.0);
+        assert_eq!(requests.service_name.as_deref(), Some("checkout"));
+
+        assert_eq!(metrics[1].name, "queue.depth");
+        assert_eq!(metrics[1].metric_type, MetricType::Gauge);
+        assert_eq!(metrics[1].data_points[0].value, 15.0);
+        assert_eq!(metrics[2].data_points[0].value, 2.0);
+
+        // Gauges remember their value across flushes but are only reported when updated
+        assert!(aggregator.flush(2_000).is_empty());
+        record_all(&mut aggregator, &["queue.depth:-1|g"]);
+        let metrics = aggregator.flush(3_000);
+        assert_eq!(metrics[0].data_points[0].value, 14.0);
+    }
+
+    #[test]
+    fn test_flush_timers() {
+        let mut aggregator = StatsdAggregator::new();
+        aggregator.flush(1_000);
+        record_all(
+            &mut aggregator,
+            &["db.query:4|ms", "db.query:30|ms", "db.query:20000|ms|@0.5"],
+        );
+
+        let metrics = aggregator.flush(2_000);
+
+        assert_eq!(metrics.len(), 1);
+        let metric = &metrics[0];
+        assert_eq!(metric.metric_type, MetricType::Histogram);
+        assert_eq!(metric.temporality, AggregationTemporality::Delta);
+        assert_eq!(metric.unit.as_deref(), Some("ms"));
+
+        let dp = &metric.data_points[0];
+        assert_eq!(dp.start_time_unix_nano, Some(1_000));
+        assert_eq!(dp.time_unix_nano, 2_000);
+        let histogram = dp.histogram.as_ref().unwrap();
+        assert_eq!(histogram.count, 4);
+        assert_eq!(histogram.sum, Some(40034.0));
+        assert_eq!(histogram.min, Some(4.0));
+        assert_eq!(histogram.max, Some(20000.0));
+        assert_eq!(histogram.bucket_counts[1], 1);
+        assert_eq!(histogram.bucket_counts[4], 1);
+        assert_eq!(histogram.bucket_counts[15], 2);
+    }
+}
@@ -0,0 +1,429 @@
+use chrono::{DateTime, Datelike, Local, NaiveDateTime, TimeZone};
+use faze::models::{Attributes, Log, Resource, SeverityLevel};
+use thiserror::Error;
+
+/// Priority assumed for messages without one, as RFC 3164 has relays do: user.notice
+const DEFAULT_PRIORITY: u8 = 13;
+
+/// Highest valid priority: facility local7 with severity debug
+const MAX_PRIORITY: u8 = 191;
+
+/// Value of an RFC 5424 field that is left empty
+const NIL: &str = "-";
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SyslogError {
+    #[error("invalid priority {0:?}")]
+    InvalidPriority(String),
+
+    #[error("invalid timestamp {0:?}")]
+    InvalidTimestamp(String),
+
+    #[error("message ends before its {0}")]
+    Truncated(&'static str),
+
+    #[error("invalid structured data: {0}")]
+    InvalidStructuredData(&'static str),
+}
+
+/// Syslog severity keywords, indexed by the severity in the priority
+const SEVERITY_NAMES: [&str; 8] = [
+    "emerg", "alert", "crit", "err", "warning", "notice", "info", "debug",
+];
+
+/// Map a syslog severity onto the OTel severity range it falls in
+const fn convert_severity(severity: u8) -> SeverityLevel {
+    match severity {
+        0 => SeverityLevel::Fatal4,
+        1 => SeverityLevel::Fatal3,
+        2 => SeverityLevel::Fatal2,
+        3 => SeverityLevel::Error,
+        4 => SeverityLevel::Warn,
+        5 => SeverityLevel::Info2,
+        6 => SeverityLevel::Info,
+        _ => SeverityLevel::Debug,
+    }
+}
+
+/// Fields shared by both formats
+#[derive(Default)]
+struct Message<'a> {
+    time_unix_nano: Option<i64>,
+    hostname: Option<&'a str>,
+    app_name: Option<&'a str>,
+    proc_id: Option<&'a str>,
+    msg_id: Option<&'a str>,
+    structured_data: Vec<(String, String)>,
+    body: &'a str,
+}
+
+fn parse_priority(message: &str) -> Result<(u8, &str), SyslogError> {
+    let Some(rest) = message.strip_prefix('<') else {
+        return Ok((DEFAULT_PRIORITY, message));
+    };
+    let (priority, rest) = rest
+        .split_once('>')
+        .ok_or_else(|| SyslogError::InvalidPriority(message.chars().take(5).collect()))?;
+    match priority.parse() {
+        Ok(value) if value <= MAX_PRIORITY && priority.len() <= 3 => Ok((value, rest)),
+        _ => Err(SyslogError::InvalidPriority(priority.to_string())),
+    }
+}
+
+fn parse_rfc3339(timestamp: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .and_then(|time| time.timestamp_nanos_opt())
+}
+
+/// Split off the next space-delimited field
+fn next_field<'a>(rest: &mut &'a str, name: &'static str) -> Result<&'a str, SyslogError> {
+    if rest.is_empty() {
+        return Err(SyslogError::Truncated(name));
+    }
+    let (field, remainder) = rest.split_once(' ').unwrap_or((rest, ""));
+    *rest = remainder;
+    Ok(field)
+}
+
+fn nil_to_none(field: &str) -> Option<&str> {
+    (field != NIL).then_some(field)
+}
+
+/// Parse RFC 5424 structured data, `-` or one or more `[id name="value" ...]` elements, into
+/// `id.name` pairs
+fn parse_structured_data(rest: &mut &str) -> Result<Vec<(String, String)>, SyslogError> {
+    if let Some(remainder) = rest.strip_prefix(NIL) {
+        *rest = remainder.strip_prefix(' ').unwrap_or(remainder);
+        return Ok(Vec::new());
+    }
+
+    let mut params = Vec::new();
+    let mut elements = 0;
+    let mut chars = rest.char_indices().peekable();
+    while let Some((_, '[')) = chars.peek() {
+        chars.next();
+        elements += 1;
+        let mut id = String::new();
+        while let Some((_, c)) = chars.next_if(|(_, c)| *c != ' ' && *c != ']') {
+            id.push(c);
+        }
+        if id.is_empty() {
+            return Err(SyslogError::InvalidStructuredData("empty SD-ID"));
+        }
+
+        loop {
+            match chars.next() {
+                Some((_, ']')) => break,
+                Some((_, ' ')) => {}
+                _ => return Err(SyslogError::InvalidStructuredData("unterminated element")),
+            }
+            let mut name = String::new();
+            while let Some((_, c)) = chars.next_if(|(_, c)| *c != '=') {
+                name.push(c);
+            }
+            if chars.next().map(|(_, c)| c) != Some('=')
+                || chars.next().map(|(_, c)| c) != Some('"')
+            {
+                return Err(SyslogError::InvalidStructuredData(
+                    "expected name=\"value\"",
+                ));
+            }
+
+            let mut value = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, '"')) => break,
+                    // Only `"`, `\` and `]` are escaped; any other backslash is literal
+                    Some((_, '\\')) => {
+                        match chars.next_if(|(_, c)| matches!(c, '"' | '\\' | ']')) {
+                            Some((_, c)) => value.push(c),
+                            None => value.push('\\'),
+                        }
+                    }
+                    Some((_, c)) => value.push(c),
+                    None => {
+                        return Err(SyslogError::InvalidStructuredData("unterminated value"));
+                    }
+                }
+            }
+            params.push((format!("{id}.{name}"), value));
+        }
+    }
+    if elements == 0 {
+        return Err(SyslogError::InvalidStructuredData("expected '[' or '-'"));
+    }
+
+    let end = chars.peek().map_or(rest.len(), |(i, _)| *i);
+    let remainder = &rest[end..];
+    *rest = remainder.strip_prefix(' ').unwrap_or(remainder);
+    Ok(params)
+}
+
+/// Parse the part of an RFC 5424 message after `<PRI>1 `
+fn parse_rfc5424(mut rest: &str) -> Result<Message<'_>, SyslogError> {
+    let timestamp = next_field(&mut rest, "timestamp")?;
+    let time_unix_nano = match timestamp {
+        NIL => None,
+        _ => Some(
+            parse_rfc3339(timestamp)
+                .ok_or_else(|| SyslogError::InvalidTimestamp(timestamp.to_string()))?,
+        ),
+    };
+    let hostname = nil_to_none(next_field(&mut rest, "hostname")?);
+    let app_name = nil_to_none(next_field(&mut rest, "app name")?);
+    let proc_id = nil_to_none(next_field(&mut rest, "process ID")?);
+    let msg_id = nil_to_none(next_field(&mut rest, "message ID")?);
+    if rest.is_empty() {
+        return Err(SyslogError::Truncated("structured data"));
+    }
+    let structured_data = parse_structured_data(&mut rest)?;
+
+    Ok(Message {
+        time_unix_nano,
+        hostname,
+        app_name,
+        proc_id,
+        msg_id,
+        structured_data,
+        body: rest.strip_prefix('\u{feff}').unwrap_or(rest),
+    })
+}
+
+/// Parse a BSD timestamp such as `Oct  7 14:03:01`. It has no year or zone, so it's read as
+/// local time in the year the message was received, or the year before if that would put it
+/// more than a day in the future
+fn parse_bsd_timestamp(timestamp: &str, received_unix_nano: i64) -> Option<i64> {
+    let received = Local.timestamp_nanos(received_unix_nano);
+    let parse = |year: i32| {
+        NaiveDateTime::parse_from_str(&format!("{year} {timestamp}"), "%Y %b %e %H:%M:%S")
+            .ok()
+            .and_then(|time| Local.from_local_datetime(&time).earliest())
+            .and_then(|time| time.timestamp_nanos_opt())
+    };
+
+    let time = parse(received.year())?;
+    if time - received_unix_nano > 86_400 * 1_000_000_000 {
+        return parse(received.year() - 1);
+    }
+    Some(time)
+}
+
+/// Split `TAG[PID]: MSG` or `TAG: MSG` into its parts, or `None` if the content has no tag
+fn parse_tag(content: &str) -> Option<(&str, Option<&str>, &str)> {
+    let (tag, body) = content.split_once(':')?;
+    let (app_name, proc_id) = match tag.split_once('[') {
+        Some((app_name, pid)) => (app_name, Some(pid.strip_suffix(']')?)),
+        None => (tag, None),
+    };
+    if app_name.is_empty() || app_name.contains(' ') {
+        return None;
+    }
+    Some((app_name, proc_id, body.strip_prefix(' ').unwrap_or(body)))
+}
+
+/// Parse the part of an RFC 3164 message after `<PRI>`, leniently: senders differ on whether
+/// they include a timestamp, a hostname or a tag
+fn parse_rfc3164(mut rest: &str, received_unix_nano: i64) -> Message<'_> {
+    let mut message = Message::default();
+
+    if let Some(time) = rest
+        .get(..15)
+        .and_then(|t| parse_bsd_timestamp(t, received_unix_nano))
+    {
+        message.time_unix_nano = Some(time);
+        rest = rest[15..].trim_start_matches(' ');
+    } else if let Some((timestamp, remainder)) = rest.split_once(' ')
+        && let Some(time) = parse_rfc3339(timestamp)
+    {
+        // Some senders, such as rsyslog's RFC 3339 templates, use a full timestamp instead
+        message.time_unix_nano = Some(time);
+        rest = remainder;
+    }
+
+    // Messages from the local host usually leave out the hostname, going straight to the tag
+    if message.time_unix_nano.is_some()
+        && let Some((hostname, remainder)) = rest.split_once(' ')
+        && !hostname.ends_with(':')
+        && !hostname.contains('[')
+    {
+        message.hostname = Some(hostname);
+        rest = remainder;
+    }
+
+    match parse_tag(rest) {
+        Some((app_name, proc_id, body)) => {
+            message.app_name = Some(app_name);
+            message.proc_id = proc_id;
+            message.body = body;
+        }
+        None => message.body = rest,
+    }
+    message
+}
+
+/// Parse an RFC 5424 or RFC 3164 syslog message to an internal Log, using `received_unix_nano`
+/// when the message has no timestamp
+pub fn parse_message(message: &str, received_unix_nano: i64) -> Result<Log, SyslogError> {
+    let message = message.trim_end_matches(['\r', '\n']);
+    let (priority, rest) = parse_priority(message)?;
+    let parsed = match rest.strip_prefix("1 ") {
+        Some(rest) => parse_rfc5424(rest)?,
+        None => parse_rfc3164(rest, received_unix_nano),
+    };
+
+    let facility = priority / 8;
+    let severity = priority % 8;
+    let mut attributes = Attributes::new();
+    attributes.insert("syslog.facility", i64::from(facility));
+    if let Some(hostname) = parsed.hostname {
+        attributes.insert("syslog.hostname", hostname);
+    }
+    if let Some(proc_id) = parsed.proc_id {
+        attributes.insert("syslog.proc_id", proc_id);
+    }
+    if let Some(msg_id) = parsed.msg_id {
+        attributes.insert("syslog.msg_id", msg_id);
+    }
+    for (name, value) in parsed.structured_data {
+        attributes.insert(name, value);
+    }
+
+    let service_name = parsed.app_name.map(str::to_string);
+    let log = Log::new(
+        parsed.time_unix_nano.unwrap_or(received_unix_nano),
+        convert_severity(severity),
+        Some(SEVERITY_NAMES[usize::from(severity)].to_string()),
+        parsed.body.to_string(),
+        attributes,
+        None,
+        None,
+        service_name.clone(),
+    );
+    Ok(match service_name {
+        Some(name) => log.with_resource(Resource::for_service(name)),
+        None => log,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use faze::models::AttributeValue;
+
+    const RECEIVED: i64 = 1_700_000_000_000_000_000;
+
+    #[test]
+    fn test_parse_rfc5424() {
+        let message = r#"<165>1 2023-11-14T22:13:20.5Z db01 postgres 4321 CONN [origin ip="10.0.0.1"][meta sequence="7" note="a \"quoted\" \] value"] connection received"#;
+
+        let log = parse_message(message, 0).unwrap();
+
+        assert_eq!(log.time_unix_nano, 1_700_000_000_500_000_000);
+        assert_eq!(log.severity_level, SeverityLevel::Info2);
+        assert_eq!(log.severity_text.as_deref(), Some("notice"));
+        assert_eq!(log.body, "connection received");
+        assert_eq!(log.service_name.as_deref(), Some("postgres"));
+        assert_eq!(
+            log.resource.as_ref().unwrap().service_name(),
+            Some("postgres")
+        );
+        assert_eq!(log.attributes.get_string("syslog.hostname"), Some("db01"));
+        assert_eq!(log.attributes.get_string("syslog.proc_id"), Some("4321"));
+        assert_eq!(log.attributes.get_string("syslog.msg_id"), Some("CONN"));
+        assert_eq!(log.attributes.get_string("origin.ip"), Some("10.0.0.1"));
+        assert_eq!(log.attributes.get_string("meta.sequence"), Some("7"));
+        assert_eq!(
+            log.attributes.get_string("meta.note"),
+            Some(r#"a "quoted" ] value"#)
+        );
+    }
+
+    #[test]
+    fn test_parse_rfc5424_nil_fields() {
+        let log = parse_message("<11>1 - - - - - -\n", RECEIVED).unwrap();
+
+        assert_eq!(log.time_unix_nano, RECEIVED);
+        assert_eq!(log.severity_level, SeverityLevel::Error);
+        assert_eq!(log.body, "");
+        assert_eq!(log.service_name, None);
+        assert_eq!(log.attributes.get_string("syslog.hostname"), None);
+
+        let log = parse_message("<14>1 - host app - - - \u{feff}hello", RECEIVED).unwrap();
+        assert_eq!(log.body, "hello");
+    }
+
+    #[test]
+    fn test_parse_rfc3164() {
+        let log = parse_message(
+            "<34>Nov 14 22:13:20 mymachine su[230]: 'su root' failed for lonvick on /dev/pts/8",
+            RECEIVED,
+        )
+        .unwrap();
+
+        let expected = Local
+            .from_local_datetime(
+                &NaiveDateTime::parse_from_str("2023 Nov 14 22:13:20", "%Y %b %e %H:%M:%S")
+                    .unwrap(),
+            )
+            .unwrap()
+            .timestamp_nanos_opt()
+            .unwrap();
+        assert_eq!(log.time_unix_nano, expected);
+        assert_eq!(log.severity_level, SeverityLevel::Fatal2);
+        assert_eq!(
+            log.attributes.get("syslog.facility"),
+            Some(&AttributeValue::Int(4))
+        );
+        assert_eq!(
+            log.attributes.get_string("syslog.hostname"),
+            Some("mymachine")
+        );
+        assert_eq!(log.attributes.get_string("syslog.proc_id"), Some("230"));
+        assert_eq!(log.service_name.as_deref(), Some("su"));
+        assert_eq!(log.body, "'su root' failed for lonvick on /dev/pts/8");
+    }
+
+    #[test]
+    fn test_parse_rfc3164_variants() {
+        // No hostname, as sent by `logger` to the local socket
+        let log = parse_message("<13>Oct  7 09:01:02 myapp: started", RECEIVED).unwrap();
+        assert_eq!(log.service_name.as_deref(), Some("myapp"));
+        assert_eq!(log.attributes.get_string("syslog.hostname"), None);
+        assert_eq!(log.body, "started");
+
+        // RFC 3339 timestamp
+        let log = parse_message("<15>2023-11-14T22:13:20Z web nginx[7]: GET /", RECEIVED).unwrap();
+        assert_eq!(log.time_unix_nano, RECEIVED);
+        assert_eq!(log.severity_level, SeverityLevel::Debug);
+        assert_eq!(log.attributes.get_string("syslog.hostname"), Some("web"));
+        assert_eq!(log.service_name.as_deref(), Some("nginx"));
+
+        // No priority, timestamp or tag
+        let log = parse_message("just some text", RECEIVED).unwrap();
+        assert_eq!(log.time_unix_nano, RECEIVED);
+        assert_eq!(log.severity_level, SeverityLevel::Info2);
+        assert_eq!(log.service_name, None);
+        assert_eq!(log.body, "just some text");
+    }
+
+    #[test]
+    fn test_parse_invalid_message() {
+        assert_eq!(
+            parse_message("<192>1 - - - - - -", 0).unwrap_err(),
+            SyslogError::InvalidPriority("192".to_string())
+        );
+        assert_eq!(
+            parse_message("<14>1 yesterday - - - - -", 0).unwrap_err(),
+            SyslogError::InvalidTimestamp("yesterday".to_string())
+        );
+        assert_eq!(
+            parse_message("<14>1 - host app", 0).unwrap_err(),
+            SyslogError::Truncated("process ID")
+        );
+        assert!(matches!(
+            parse_message(r#"<14>1 - - - - - [id a="b"#, 0),
+            Err(SyslogError::InvalidStructuredData(_))
+        ));
+    }
+}
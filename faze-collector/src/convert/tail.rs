@@ -0,0 +1,383 @@
+use crate::convert::logs::convert_severity_text;
+use chrono::DateTime;
+use faze::models::{AttributeValue, Attributes, Log, Resource, SeverityLevel};
+use regex::Regex;
+use serde_json::Value;
+use std::{fmt, str::FromStr};
+
+/// Fields holding the log line, in order of preference
+const BODY_FIELDS: &[&str] = &["message", "msg", "body", "log"];
+
+/// Fields holding the timestamp, in order of preference
+const TIME_FIELDS: &[&str] = &["timestamp", "time", "ts", "@timestamp"];
+
+/// Fields holding the log level, in order of preference
+const LEVEL_FIELDS: &[&str] = &["level", "lvl", "severity", "log.level"];
+
+const TRACE_ID_FIELDS: &[&str] = &["trace_id", "traceId", "trace.id"];
+
+const SPAN_ID_FIELDS: &[&str] = &["span_id", "spanId", "span.id"];
+
+/// Fields naming the service, in order of preference
+const SERVICE_FIELDS: &[&str] = &["service.name", "service_name", "service"];
+
+/// How lines of a tailed file are parsed, written as `auto`, `json`, `logfmt` or
+/// `regex:PATTERN`
+#[derive(Debug, Clone, Default)]
+pub enum LineFormat {
+    /// JSON objects or logfmt, whichever the line looks like, otherwise plain text
+    #[default]
+    Auto,
+    Json,
+    Logfmt,
+    /// A pattern whose named captures become fields
+    Regex(Regex),
+}
+
+impl FromStr for LineFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "auto" => Ok(Self::Auto),
+            "json" => Ok(Self::Json),
+            "logfmt" => Ok(Self::Logfmt),
+            _ => {
+                let Some(pattern) = value.strip_prefix("regex:") else {
+                    return Err(format!(
+                        "invalid format {value:?} (expected auto, json, logfmt or regex:PATTERN)"
+                    ));
+                };
+                let regex =
+                    Regex::new(pattern).map_err(|e| format!("invalid pattern {pattern:?}: {e}"))?;
+                if regex.capture_names().flatten().next().is_none() {
+                    return Err(format!("pattern {pattern:?} has no named captures"));
+                }
+                Ok(Self::Regex(regex))
+            }
+        }
+    }
+}
+
+impl fmt::Display for LineFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Auto => write!(f, "auto"),
+            Self::Json => write!(f, "json"),
+            Self::Logfmt => write!(f, "logfmt"),
+            Self::Regex(regex) => write!(f, "regex:{}", regex.as_str()),
+        }
+    }
+}
+
+/// Fields of a parsed line, in the order they appeared
+type Fields = Vec<(String, Value)>;
+
+fn parse_json(line: &str) -> Option<Fields> {
+    match serde_json::from_str(line) {
+        Ok(Value::Object(object)) => Some(object.into_iter().collect()),
+        _ => None,
+    }
+}
+
+/// Parse `key=value key2="quoted value"` pairs. Bare keys are read as `true` unless `strict`, in
+/// which case the line isn't taken to be logfmt at all
+fn parse_logfmt(line: &str, strict: bool) -> Option<Fields> {
+    let mut fields = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut key = String::new();
+        while let Some(c) = chars.next_if(|c| *c != '=' && !c.is_whitespace()) {
+            key.push(c);
+        }
+        if chars.next_if_eq(&'=').is_none() {
+            if strict || key.is_empty() {
+                return None;
+            }
+            fields.push((key, Value::Bool(true)));
+            continue;
+        }
+        if key.is_empty() {
+            return None;
+        }
+
+        let mut value = String::new();
+        if chars.next_if_eq(&'"').is_some() {
+            loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => match chars.next()? {
+                        'n' => value.push('\n'),
+                        't' => value.push('\t'),
+                        c => value.push(c),
+                    },
+                    c => value.push(c),
+                }
+            }
+        } else {
+            while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                value.push(c);
+            }
+        }
+        fields.push((key, Value::String(value)));
+    }
+    (!fields.is_empty()).then_some(fields)
+}
+
+fn parse_regex(regex: &Regex, line: &str) -> Option<Fields> {
+    let captures = regex.captures(line)?;
+    Some(
+        regex
+            .capture_names()
+            .flatten()
+            .filter_map(|name| {
+                let value = captures.name(name)?;
+                Some((name.to_string(), Value::String(value.as_str().to_string())))
+            })
+            .collect(),
+    )
+}
+
+/// Remove and parse the first of `names` that's present and parses, leaving fields that fail
+/// to parse in place as attributes
+fn take_field<T>(
+    fields: &mut Fields,
+    names: &[&str],
+    parse: impl Fn(&Value) -> Option<T>,
+) -> Option<T> {
+    names.iter().find_map(|name| {
+        let index = fields.iter().position(|(key, _)| key == name)?;
+        let parsed = parse(&fields[index].1)?;
+        fields.remove(index);
+        Some(parsed)
+    })
+}
+
+fn as_string(value: &Value) -> Option<String> {
+    value.as_str().filter(|s| !s.is_empty()).map(str::to_string)
+}
+
+/// Read a timestamp as RFC 3339, or as seconds, milliseconds, microseconds or nanoseconds since
+/// epoch depending on its magnitude
+fn parse_timestamp(value: &Value) -> Option<i64> {
+    let epoch = match value {
+        Value::Number(number) => number.clone(),
+        Value::String(s) => match DateTime::parse_from_rfc3339(s) {
+            Ok(time) => return time.timestamp_nanos_opt(),
+            Err(_) => s.parse().ok()?,
+        },
+        _ => return None,
+    };
+
+    // Whole numbers are scaled exactly, since nanoseconds since epoch don't fit in an f64
+    if let Some(epoch) = epoch.as_i64() {
+        let scale = match epoch.unsigned_abs() {
+            e if e < 100_000_000_000 => 1_000_000_000,
+            e if e < 100_000_000_000_000 => 1_000_000,
+            e if e < 100_000_000_000_000_000 => 1_000,
+            _ => 1,
+        };
+        return epoch.checked_mul(scale);
+    }
+    let epoch = epoch.as_f64()?;
+    let nanos = match epoch.abs() {
+        e if e < 1e11 => epoch * 1e9,
+        e if e < 1e14 => epoch * 1e6,
+        e if e < 1e17 => epoch * 1e3,
+        _ => epoch,
+    };
+    Some(nanos as i64)
+}
+
+fn convert_value(value: Value) -> Option<AttributeValue> {
+    Some(match value {
+        Value::Null => return None,
+        Value::Bool(b) => AttributeValue::Bool(b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => AttributeValue::Int(i),
+            None => AttributeValue::Double(n.as_f64().unwrap_or_default()),
+        },
+        Value::String(s) => AttributeValue::String(s),
+        Value::Array(values) => {
+            AttributeValue::Array(values.into_iter().filter_map(convert_value).collect())
+        }
+        Value::Object(object) => AttributeValue::Map(convert_fields(object.into_iter())),
+    })
+}
+
+fn convert_fields(fields: impl Iterator<Item = (String, Value)>) -> Attributes {
+    let mut attributes = Attributes::new();
+    for (key, value) in fields {
+        if let Some(value) = convert_value(value) {
+            attributes.insert(key, value);
+        }
+    }
+    attributes
+}
+
+/// Parse one line of a tailed file to an internal Log, extracting its timestamp, level, trace
+/// and span IDs from well-known fields. Lines that don't match the format are kept as plain
+/// text, timestamped with `received_unix_nano` and stored under `default_service_name`
+pub fn parse_line(
+    format: &LineFormat,
+    line: &str,
+    default_service_name: Option<&str>,
+    received_unix_nano: i64,
+) -> Log {
+    let fields = match format {
+        LineFormat::Auto if line.trim_start().starts_with('{') => parse_json(line),
+        LineFormat::Auto => parse_logfmt(line, true),
+        LineFormat::Json => parse_json(line),
+        LineFormat::Logfmt => parse_logfmt(line, false),
+        LineFormat::Regex(regex) => parse_regex(regex, line),
+    };
+    let mut fields = fields.unwrap_or_default();
+
+    let time_unix_nano = take_field(&mut fields, TIME_FIELDS, parse_timestamp);
+    let level = take_field(&mut fields, LEVEL_FIELDS, as_string);
+    let trace_id = take_field(&mut fields, TRACE_ID_FIELDS, as_string);
+    let span_id = take_field(&mut fields, SPAN_ID_FIELDS, as_string);
+    let body = take_field(&mut fields, BODY_FIELDS, |value| {
+        value.as_str().map(str::to_string)
+    });
+    let service_name = take_field(&mut fields, SERVICE_FIELDS, as_string)
+        .or_else(|| default_service_name.map(str::to_string));
+
+    let severity_level = level
+        .as_deref()
+        .map_or(SeverityLevel::Unspecified, convert_severity_text);
+
+    let log = Log::new(
+        time_unix_nano.unwrap_or(received_unix_nano),
+        severity_level,
+        level,
+        body.unwrap_or_else(|| line.to_string()),
+        convert_fields(fields.into_iter()),
+        trace_id,
+        span_id,
+        service_name.clone(),
+    );
+    match service_name {
+        Some(name) => log.with_resource(Resource::for_service(name)),
+        None => log,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECEIVED: i64 = 1_700_000_000_000_000_000;
+
+    #[test]
+    fn test_parse_json_line() {
+        let line = r#"{"time":"2023-11-14T22:13:20.25Z","level":"error","msg":"payment failed","trace_id":"0af7651916cd43dd8448eb211c80319c","span_id":"b7ad6b7169203331","order":{"id":42},"retry":true}"#;
+
+        let log = parse_line(&LineFormat::Json, line, Some("billing"), 0);
+
+        assert_eq!(log.time_unix_nano, 1_700_000_000_250_000_000);
+        assert_eq!(log.severity_level, SeverityLevel::Error);
+        assert_eq!(log.severity_text.as_deref(), Some("error"));
+        assert_eq!(log.body, "payment failed");
+        assert_eq!(
+            log.trace_id.as_deref(),
+            Some("0af7651916cd43dd8448eb211c80319c")
+        );
+        assert_eq!(log.span_id.as_deref(), Some("b7ad6b7169203331"));
+        assert_eq!(log.service_name.as_deref(), Some("billing"));
+        assert_eq!(
+            log.attributes.get("retry"),
+            Some(&AttributeValue::Bool(true))
+        );
+        assert!(matches!(
+            log.attributes.get("order"),
+            Some(AttributeValue::Map(_))
+        ));
+        assert_eq!(log.attributes.get("msg"), None);
+        assert_eq!(log.attributes.get("level"), None);
+    }
+
+    #[test]
+    fn test_parse_logfmt_line() {
+        let line =
+            r#"ts=1700000000123 level=warn service=api msg="slow request \"GET /\"" duration=1.5s"#;
+
+        let log = parse_line(&LineFormat::Logfmt, line, Some("file"), 0);
+
+        assert_eq!(log.time_unix_nano, 1_700_000_000_123_000_000);
+        assert_eq!(log.severity_level, SeverityLevel::Warn);
+        assert_eq!(log.body, r#"slow request "GET /""#);
+        assert_eq!(log.service_name.as_deref(), Some("api"));
+        assert_eq!(log.attributes.get_string("duration"), Some("1.5s"));
+    }
+
+    #[test]
+    fn test_parse_regex_line() {
+        let format: LineFormat = r"regex:^\[(?P<time>[^\]]+)\] (?P<level>\w+): (?P<message>.*)$"
+            .parse()
+            .unwrap();
+
+        let log = parse_line(
+            &format,
+            "[2023-11-14T22:13:20Z] INFO: worker started",
+            None,
+            0,
+        );
+
+        assert_eq!(log.time_unix_nano, RECEIVED);
+        assert_eq!(log.severity_level, SeverityLevel::Info);
+        assert_eq!(log.body, "worker started");
+        assert_eq!(log.service_name, None);
+    }
+
+    #[test]
+    fn test_parse_auto_line() {
+        let log = parse_line(&LineFormat::Auto, r#"{"message":"hi"}"#, None, RECEIVED);
+        assert_eq!(log.body, "hi");
+
+        let log = parse_line(&LineFormat::Auto, "level=debug msg=hi", None, RECEIVED);
+        assert_eq!(log.severity_level, SeverityLevel::Debug);
+        assert_eq!(log.body, "hi");
+
+        // Plain text, even with an `=` in it, is kept as is
+        let line = "Listening on port 8080 with workers=4";
+        let log = parse_line(&LineFormat::Auto, line, None, RECEIVED);
+        assert_eq!(log.time_unix_nano, RECEIVED);
+        assert_eq!(log.body, line);
+        assert!(log.attributes.is_empty());
+    }
+
+    #[test]
+    fn test_parse_line_format() {
+        assert!(matches!("json".parse(), Ok(LineFormat::Json)));
+        assert_eq!(
+            "regex:(?P<msg>.*)"
+                .parse::<LineFormat>()
+                .unwrap()
+                .to_string(),
+            "regex:(?P<msg>.*)"
+        );
+        assert!("regex:(.*)".parse::<LineFormat>().is_err());
+        assert!("regex:(".parse::<LineFormat>().is_err());
+        assert!("xml".parse::<LineFormat>().is_err());
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp(&Value::from(1_700_000_000)), Some(RECEIVED));
+        assert_eq!(
+            parse_timestamp(&Value::from(1_700_000_000_000_000i64)),
+            Some(RECEIVED)
+        );
+        assert_eq!(
+            parse_timestamp(&Value::from("1700000000000000000")),
+            Some(RECEIVED)
+        );
+        assert_eq!(parse_timestamp(&Value::from("yesterday")), None);
+    }
+}
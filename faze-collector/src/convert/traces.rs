@@ -1,8 +1,11 @@
-use crate::convert::{bytes_to_hex, convert_attributes, convert_resource};
+use crate::convert::{bytes_to_hex, convert_attributes, convert_resource, convert_scope};
 use crate::proto::opentelemetry::proto::trace::v1::{
-    ResourceSpans, Span, SpanKind as OtlpSpanKind, Status, StatusCode as OtlpSpanStatusCode,
+    Event, Link, ResourceSpans, Span, SpanKind as OtlpSpanKind, Status,
+    StatusCode as OtlpSpanStatusCode,
+};
+use faze::models::{
+    Span as FazeSpan, SpanEvent, SpanKind, SpanLink, Status as FazeStatus, StatusCode,
 };
-use faze::models::{Span as FazeSpan, SpanKind, Status as FazeStatus, StatusCode};
 
 /// Convert OTLP SpanKind to internal SpanKind
 fn convert_span_kind(kind: i32) -> SpanKind {
@@ -17,6 +20,31 @@ fn convert_span_kind(kind: i32) -> SpanKind {
     }
 }
 
+/// Convert OTLP span Event to internal SpanEvent
+fn convert_event(event: &Event) -> SpanEvent {
+    SpanEvent::new(
+        event.name.clone(),
+        event.time_unix_nano as i64,
+        convert_attributes(&event.attributes),
+    )
+}
+
+/// Convert OTLP span Link to internal SpanLink
+fn convert_link(link: &Link) -> SpanLink {
+    let trace_state = if link.trace_state.is_empty() {
+        None
+    } else {
+        Some(link.trace_state.clone())
+    };
+
+    SpanLink::new(
+        bytes_to_hex(&link.trace_id),
+        bytes_to_hex(&link.span_id),
+        trace_state,
+        convert_attributes(&link.attributes),
+    )
+}
+
 /// Convert OTLP Span to internal Span
 fn convert_span(span: &Span, service_name: Option<String>) -> FazeSpan {
     let span_id = bytes_to_hex(&span.span_id);
@@ -30,6 +58,9 @@ fn convert_span(span: &Span, service_name: Option<String>) -> FazeSpan {
     let attributes = convert_attributes(&span.attributes);
     let kind = convert_span_kind(span.kind);
     let status = span.status.as_ref().map(convert_status).unwrap_or_default();
+    let mut events: Vec<SpanEvent> = span.events.iter().map(convert_event).collect();
+    events.sort_by_key(|event| event.time_unix_nano);
+    let links = span.links.iter().map(convert_link).collect();
 
     FazeSpan::new(
         span_id,
@@ -43,6 +74,8 @@ fn convert_span(span: &Span, service_name: Option<String>) -> FazeSpan {
         status,
         service_name,
     )
+    .with_events(events)
+    .with_links(links)
 }
 
 /// Convert OTLP ResourceSpans to list of internal Spans
@@ -50,15 +83,19 @@ pub fn convert_resource_spans(resource_spans: &[ResourceSpans]) -> Vec<FazeSpan>
     let mut spans = Vec::new();
 
     for rs in resource_spans {
-        let service_name = rs
-            .resource
+        let resource = rs.resource.as_ref().map(convert_resource);
+        let service_name = resource
             .as_ref()
-            .map(convert_resource)
             .and_then(|r| r.service_name().map(|s| s.to_string()));
 
         for scope_spans in &rs.scope_spans {
+            let scope = scope_spans.scope.as_ref().map(convert_scope);
             for span in &scope_spans.spans {
-                spans.push(convert_span(span, service_name.clone()));
+                spans.push(FazeSpan {
+                    resource: resource.clone(),
+                    scope: scope.clone(),
+                    ..convert_span(span, service_name.clone())
+                });
             }
         }
     }
@@ -88,7 +125,7 @@ fn convert_status(status: &Status) -> FazeStatus {
 mod tests {
     use super::*;
     use crate::proto::opentelemetry::proto::{
-        common::v1::{AnyValue, KeyValue, any_value},
+        common::v1::{AnyValue, InstrumentationScope, KeyValue, any_value},
         resource::v1::Resource,
         trace::v1::ScopeSpans,
     };
@@ -301,6 +338,126 @@ mod tests {
         assert_eq!(result.attributes.get_int("http.status_code"), Some(200));
     }
 
+    #[test]
+    fn test_convert_span_with_events() {
+        let span = Span {
+            trace_id: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16],
+            span_id: vec![1, 2, 3, 4, 5, 6, 7, 8],
+            parent_span_id: vec![],
+            name: "failing-span".to_string(),
+            kind: OtlpSpanKind::Server as i32,
+            start_time_unix_nano: 1_000_000_000,
+            end_time_unix_nano: 2_000_000_000,
+            attributes: vec![],
+            dropped_attributes_count: 0,
+            events: vec![
+                Event {
+                    time_unix_nano: 1_500_000_000,
+                    name: "exception".to_string(),
+                    attributes: vec![
+                        KeyValue {
+                            key: "exception.type".to_string(),
+                            value: Some(AnyValue {
+                                value: Some(any_value::Value::StringValue(
+                                    "ValueError".to_string(),
+                                )),
+                            }),
+                        },
+                        KeyValue {
+                            key: "exception.message".to_string(),
+                            value: Some(AnyValue {
+                                value: Some(any_value::Value::StringValue("bad value".to_string())),
+                            }),
+                        },
+                    ],
+                    dropped_attributes_count: 0,
+                },
+                Event {
+                    time_unix_nano: 1_200_000_000,
+                    name: "retry".to_string(),
+                    attributes: vec![],
+                    dropped_attributes_count: 0,
+                },
+            ],
+            dropped_events_count: 0,
+            links: vec![],
+            dropped_links_count: 0,
+            status: Some(Status {
+                code: OtlpSpanStatusCode::Error as i32,
+                message: "bad value".to_string(),
+            }),
+            trace_state: String::new(),
+        };
+
+        let result = convert_span(&span, Some("test-service".to_string()));
+        assert_eq!(result.events.len(), 2);
+        assert_eq!(result.events[0].name, "retry");
+        assert_eq!(result.events[1].name, "exception");
+        assert_eq!(result.events[1].time_unix_nano, 1_500_000_000);
+        assert_eq!(
+            result.events[1].attributes.get_string("exception.type"),
+            Some("ValueError")
+        );
+        assert_eq!(result.exceptions().count(), 1);
+    }
+
+    #[test]
+    fn test_convert_span_with_links() {
+        let span = Span {
+            trace_id: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16],
+            span_id: vec![1, 2, 3, 4, 5, 6, 7, 8],
+            parent_span_id: vec![],
+            name: "consume-batch".to_string(),
+            kind: OtlpSpanKind::Consumer as i32,
+            start_time_unix_nano: 1_000_000_000,
+            end_time_unix_nano: 2_000_000_000,
+            attributes: vec![],
+            dropped_attributes_count: 0,
+            events: vec![],
+            dropped_events_count: 0,
+            links: vec![
+                Link {
+                    trace_id: vec![16, 15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1],
+                    span_id: vec![9, 10, 11, 12, 13, 14, 15, 16],
+                    trace_state: "vendor=value".to_string(),
+                    attributes: vec![KeyValue {
+                        key: "messaging.message.id".to_string(),
+                        value: Some(AnyValue {
+                            value: Some(any_value::Value::StringValue("msg-1".to_string())),
+                        }),
+                    }],
+                    dropped_attributes_count: 0,
+                },
+                Link {
+                    trace_id: vec![1; 16],
+                    span_id: vec![2; 8],
+                    trace_state: String::new(),
+                    attributes: vec![],
+                    dropped_attributes_count: 0,
+                },
+            ],
+            dropped_links_count: 0,
+            status: None,
+            trace_state: String::new(),
+        };
+
+        let result = convert_span(&span, Some("worker".to_string()));
+        assert_eq!(result.links.len(), 2);
+        assert_eq!(result.links[0].trace_id, "100f0e0d0c0b0a090807060504030201");
+        assert_eq!(result.links[0].span_id, "090a0b0c0d0e0f10");
+        assert_eq!(
+            result.links[0].trace_state,
+            Some("vendor=value".to_string())
+        );
+        assert_eq!(
+            result.links[0]
+                .attributes
+                .get_string("messaging.message.id"),
+            Some("msg-1")
+        );
+        assert_eq!(result.links[1].trace_state, None);
+    }
+
     #[test]
     fn test_convert_resource_spans_multiple_scopes() {
         let resource_spans = vec![ResourceSpans {
@@ -445,6 +602,70 @@ mod tests {
         assert_eq!(spans[1].service_name, Some("service2".to_string()));
     }
 
+    #[test]
+    fn test_convert_resource_spans_keeps_resource_and_scope() {
+        let resource_spans = vec![ResourceSpans {
+            resource: Some(Resource {
+                attributes: vec![
+                    KeyValue {
+                        key: "service.name".to_string(),
+                        value: Some(AnyValue {
+                            value: Some(any_value::Value::StringValue("checkout".to_string())),
+                        }),
+                    },
+                    KeyValue {
+                        key: "service.version".to_string(),
+                        value: Some(AnyValue {
+                            value: Some(any_value::Value::StringValue("2.0.1".to_string())),
+                        }),
+                    },
+                    KeyValue {
+                        key: "host.name".to_string(),
+                        value: Some(AnyValue {
+                            value: Some(any_value::Value::StringValue("web-1".to_string())),
+                        }),
+                    },
+                ],
+                dropped_attributes_count: 0,
+            }),
+            scope_spans: vec![ScopeSpans {
+                scope: Some(InstrumentationScope {
+                    name: "tower-http".to_string(),
+                    version: "0.6.7".to_string(),
+                    attributes: vec![],
+                    dropped_attributes_count: 0,
+                }),
+                spans: vec![Span {
+                    trace_id: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16],
+                    span_id: vec![1, 2, 3, 4, 5, 6, 7, 8],
+                    parent_span_id: vec![],
+                    name: "GET /cart".to_string(),
+                    kind: OtlpSpanKind::Server as i32,
+                    start_time_unix_nano: 1_000_000_000,
+                    end_time_unix_nano: 2_000_000_000,
+                    attributes: vec![],
+                    dropped_attributes_count: 0,
+                    events: vec![],
+                    dropped_events_count: 0,
+                    links: vec![],
+                    dropped_links_count: 0,
+                    status: None,
+                    trace_state: String::new(),
+                }],
+                schema_url: String::new(),
+            }],
+            schema_url: String::new(),
+        }];
+
+        let spans = convert_resource_spans(&resource_spans);
+        let resource = spans[0].resource.as_ref().unwrap();
+        assert_eq!(resource.service_version(), Some("2.0.1"));
+        assert_eq!(resource.attributes.get_string("host.name"), Some("web-1"));
+        let scope = spans[0].scope.as_ref().unwrap();
+        assert_eq!(scope.name, "tower-http");
+        assert_eq!(scope.version, Some("0.6.7".to_string()));
+    }
+
     #[test]
     fn test_convert_resource_spans_empty() {
         let resource_spans: Vec<ResourceSpans> = vec![];
@@ -0,0 +1,223 @@
+use faze::models::{Attributes, Resource, Span, SpanEvent, SpanKind, Status};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Span in the Zipkin v2 JSON format
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZipkinSpan {
+    pub trace_id: String,
+    pub id: String,
+    pub parent_id: Option<String>,
+    pub name: Option<String>,
+    pub kind: Option<String>,
+    /// Start time in microseconds since epoch
+    pub timestamp: Option<i64>,
+    /// Duration in microseconds
+    pub duration: Option<i64>,
+    pub local_endpoint: Option<ZipkinEndpoint>,
+    #[serde(default)]
+    pub annotations: Vec<ZipkinAnnotation>,
+    #[serde(default)]
+    pub tags: HashMap<String, String>,
+}
+
+/// Network context of a Zipkin span
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZipkinEndpoint {
+    pub service_name: Option<String>,
+}
+
+/// Timestamped event of a Zipkin span
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ZipkinAnnotation {
+    /// Time in microseconds since epoch
+    pub timestamp: i64,
+    pub value: String,
+}
+
+/// Convert Zipkin span kind to internal SpanKind
+fn convert_span_kind(kind: Option<&str>) -> SpanKind {
+    match kind {
+        Some("SERVER") => SpanKind::Server,
+        Some("CLIENT") => SpanKind::Client,
+        Some("PRODUCER") => SpanKind::Producer,
+        Some("CONSUMER") => SpanKind::Consumer,
+        // Zipkin leaves the kind out for spans that stay within one process
+        _ => SpanKind::Internal,
+    }
+}
+
+/// Derive the span status from the `error` tag, or the `otel.status_code` tag set by
+/// OpenTelemetry's Zipkin exporter
+fn convert_status(tags: &HashMap<String, String>) -> Status {
+    if let Some(message) = tags.get("error") {
+        return Status::error(message.clone());
+    }
+
+    match tags.get("otel.status_code").map(String::as_str) {
+        Some("ERROR") => {
+            let message = tags
+                .get("otel.status_description")
+                .cloned()
+                .unwrap_or_default();
+            Status::error(message)
+        }
+        Some("OK") => Status::ok(),
+        _ => Status::unset(),
+    }
+}
+
+/// Normalize a Zipkin ID to lowercase hex, left-padding 64-bit trace IDs to 128 bits so they
+/// match the IDs of OTLP spans in the same trace
+fn normalize_id(id: &str, width: usize) -> String {
+    format!("{:0>width$}", id.to_ascii_lowercase())
+}
+
+/// Convert a Zipkin span to internal Span
+///
+/// Returns None when the span's start or end time is out of range.
+pub fn convert_span(span: ZipkinSpan) -> Option<Span> {
+    let start_time_unix_nano = span.timestamp.unwrap_or_default().checked_mul(1_000)?;
+    let end_time_unix_nano =
+        start_time_unix_nano.checked_add(span.duration.unwrap_or_default().checked_mul(1_000)?)?;
+    let service_name = span.local_endpoint.and_then(|e| e.service_name);
+    let status = convert_status(&span.tags);
+
+    let mut attributes = Attributes::new();
+    for (key, value) in span.tags {
+        attributes.insert(key, value);
+    }
+
+    // Annotations with out-of-range timestamps are left out rather than losing the whole span
+    let mut events: Vec<SpanEvent> = span
+        .annotations
+        .into_iter()
+        .filter_map(|a| {
+            let time_unix_nano = a.timestamp.checked_mul(1_000)?;
+            Some(SpanEvent::new(a.value, time_unix_nano, Attributes::new()))
+        })
+        .collect();
+    events.sort_by_key(|event| event.time_unix_nano);
+
+    let resource = service_name.as_ref().map(Resource::for_service);
+
+    let converted = Span::new(
+        normalize_id(&span.id, 16),
+        normalize_id(&span.trace_id, 32),
+        span.parent_id.map(|id| normalize_id(&id, 16)),
+        span.name.unwrap_or_default(),
+        convert_span_kind(span.kind.as_deref()),
+        start_time_unix_nano,
+        end_time_unix_nano,
+        attributes,
+        status,
+        service_name,
+    )
+    .with_events(events);
+
+    Some(match resource {
+        Some(resource) => converted.with_resource(resource),
+        None => converted,
+    })
+}
+
+/// Convert a Zipkin v2 JSON payload to list of internal Spans, dropping spans with
+/// out-of-range times
+pub fn convert_spans(spans: Vec<ZipkinSpan>) -> Vec<Span> {
+    spans.into_iter().filter_map(convert_span).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use faze::models::StatusCode;
+
+    #[test]
+    fn test_convert_span() {
+        let json = r#"{
+            "traceId": "5AF7183FB1D4CF5F",
+            "id": "352bff9a74ca9ad2",
+            "parentId": "6b221d5bc9e6496c",
+            "name": "get /api",
+            "kind": "SERVER",
+            "timestamp": 1556604172355737,
+            "duration": 1431,
+            "localEndpoint": {"serviceName": "backend", "ipv4": "192.168.99.1", "port": 3306},
+            "remoteEndpoint": {"ipv4": "172.19.0.2", "port": 58648},
+            "annotations": [
+                {"timestamp": 1556604172356000, "value": "ws"},
+                {"timestamp": 1556604172355800, "value": "wr"}
+            ],
+            "tags": {"http.method": "GET", "http.path": "/api"}
+        }"#;
+        let span = convert_span(serde_json::from_str(json).unwrap()).unwrap();
+
+        assert_eq!(span.trace_id, "00000000000000005af7183fb1d4cf5f");
+        assert_eq!(span.span_id, "352bff9a74ca9ad2");
+        assert_eq!(span.parent_span_id.as_deref(), Some("6b221d5bc9e6496c"));
+        assert_eq!(span.kind, SpanKind::Server);
+        assert_eq!(span.start_time_unix_nano, 1_556_604_172_355_737_000);
+        assert_eq!(span.end_time_unix_nano, 1_556_604_172_357_168_000);
+        assert_eq!(span.service_name.as_deref(), Some("backend"));
+        assert_eq!(
+            span.resource.as_ref().unwrap().service_name(),
+            Some("backend")
+        );
+        assert_eq!(span.attributes.get_string("http.path"), Some("/api"));
+        assert_eq!(span.status.code, StatusCode::Unset);
+
+        let events: Vec<&str> = span.events.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(events, vec!["wr", "ws"]);
+    }
+
+    #[test]
+    fn test_convert_span_kind() {
+        assert_eq!(convert_span_kind(Some("CLIENT")), SpanKind::Client);
+        assert_eq!(convert_span_kind(Some("PRODUCER")), SpanKind::Producer);
+        assert_eq!(convert_span_kind(Some("CONSUMER")), SpanKind::Consumer);
+        assert_eq!(convert_span_kind(None), SpanKind::Internal);
+    }
+
+    #[test]
+    fn test_convert_status() {
+        let mut tags = HashMap::new();
+        assert_eq!(convert_status(&tags).code, StatusCode::Unset);
+
+        tags.insert("otel.status_code".to_string(), "OK".to_string());
+        assert_eq!(convert_status(&tags).code, StatusCode::Ok);
+
+        tags.insert("error".to_string(), "connection refused".to_string());
+        let status = convert_status(&tags);
+        assert_eq!(status.code, StatusCode::Error);
+        assert_eq!(status.message.as_deref(), Some("connection refused"));
+    }
+
+    #[test]
+    fn test_convert_minimal_span() {
+        let span =
+            convert_span(serde_json::from_str(r#"{"traceId": "a", "id": "b"}"#).unwrap()).unwrap();
+
+        assert_eq!(span.name, "");
+        assert_eq!(span.kind, SpanKind::Internal);
+        assert_eq!(span.service_name, None);
+        assert!(span.resource.is_none());
+    }
+
+    #[test]
+    fn test_convert_spans_drops_out_of_range_times() {
+        let json = r#"[
+            {"traceId": "a", "id": "1", "timestamp": 9223372036854776},
+            {"traceId": "a", "id": "2", "timestamp": 9000000000000000, "duration": 1000000000000000},
+            {"traceId": "a", "id": "3", "timestamp": 1556604172355737, "annotations": [
+                {"timestamp": -9223372036854776, "value": "ws"}
+            ]}
+        ]"#;
+        let spans = convert_spans(serde_json::from_str(json).unwrap());
+
+        assert_eq!(spans.len(), 1);
+        assert_eq!(spans[0].span_id, "0000000000000003");
+        assert!(spans[0].events.is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use crate::{
+    convert::fluent::{FluentError, ack_response, convert_message},
+    ingest::{IngestQueue, log_failures},
+};
+use std::io;
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+use tracing::{debug, error, warn};
+
+/// Largest message accepted from a client, matching Fluentd's default chunk limit
+const MAX_MESSAGE_SIZE: usize = 8 * 1024 * 1024;
+
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Error)]
+enum ConnectionError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("invalid msgpack: {0}")]
+    Decode(#[from] rmpv::decode::Error),
+
+    #[error(transparent)]
+    Message(#[from] FluentError),
+
+    #[error("message exceeds {MAX_MESSAGE_SIZE} bytes")]
+    TooLarge,
+}
+
+/// Whether decoding failed only because the buffer ends partway through a message
+fn is_incomplete(e: &rmpv::decode::Error) -> bool {
+    match e {
+        rmpv::decode::Error::InvalidMarkerRead(e) | rmpv::decode::Error::InvalidDataRead(e) => {
+            e.kind() == io::ErrorKind::UnexpectedEof
+        }
+        rmpv::decode::Error::DepthLimitExceeded => false,
+    }
+}
+
+/// Store one message's events, acknowledging its chunk if the client asked for one
+async fn handle_message(
+    message: rmpv::Value,
+    stream: &mut TcpStream,
+    queue: &IngestQueue,
+) -> Result<(), ConnectionError> {
+    let message = convert_message(message)?;
+
+    match queue.submit_logs(message.logs).await {
+        Ok((logs, outcome)) => {
+            log_failures("log", &logs, &outcome, |log| &log.body);
+            if let Some(chunk) = &message.chunk {
+                stream.write_all(&ack_response(chunk)).await?;
+            }
+        }
+        // Without an ack, clients that asked for one resend the chunk
+        Err(e) => error!("Failed to write Fluent Forward logs: {}", e),
+    }
+    Ok(())
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    queue: &IngestQueue,
+) -> Result<(), ConnectionError> {
+    let mut buf = Vec::new();
+    let mut read_buf = vec![0u8; READ_BUFFER_SIZE];
+
+    loop {
+        // Clients write messages back to back, so a read may end partway through one
+        loop {
+            let mut remaining = buf.as_slice();
+            match rmpv::decode::read_value(&mut remaining) {
+                Ok(message) => {
+                    let consumed = buf.len() - remaining.len();
+                    buf.drain(..consumed);
+                    handle_message(message, &mut stream, queue).await?;
+                }
+                Err(e) if is_incomplete(&e) => break,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if buf.len() > MAX_MESSAGE_SIZE {
+            return Err(ConnectionError::TooLarge);
+        }
+
+        let len = stream.read(&mut read_buf).await?;
+        if len == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&read_buf[..len]);
+    }
+}
+
+/// Accept Fluent Forward connections on `listener`, writing their events through the ingest
+/// queue until the returned task is aborted
+pub fn spawn_fluent(listener: TcpListener, queue: IngestQueue) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("Failed to accept Fluent Forward connection: {}", e);
+                    continue;
+                }
+            };
+
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                match handle_connection(stream, &queue).await {
+                    Ok(()) => debug!("Fluent Forward client {} disconnected", peer),
+                    Err(e) => warn!("Closing Fluent Forward connection from {}: {}", peer, e),
+                }
+            });
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use faze::Storage;
+    use rmpv::Value;
+    use std::time::Duration;
+
+    fn encode(value: &Value) -> Vec<u8> {
+        let mut buf = Vec::new();
+        rmpv::encode::write_value(&mut buf, value).unwrap();
+        buf
+    }
+
+    async fn start() -> (Storage, TcpStream, JoinHandle<()>) {
+        let storage = Storage::new_in_memory().unwrap();
+        let queue = IngestQueue::spawn(storage.clone(), 4);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let receiver = spawn_fluent(listener, queue);
+        let client = TcpStream::connect(addr).await.unwrap();
+        (storage, client, receiver)
+    }
+
+    #[tokio::test]
+    async fn test_fluent_receiver_acks_chunk() {
+        let (storage, mut client, receiver) = start().await;
+        let message = encode(&Value::Array(vec![
+            Value::from("docker.api"),
+            Value::Array(vec![Value::Array(vec![
+                Value::from(1_700_000_000),
+                Value::Map(vec![
+                    (Value::from("log"), Value::from("listening on :8080")),
+                    (Value::from("container_name"), Value::from("/api")),
+                ]),
+            ])]),
+            Value::Map(vec![(Value::from("chunk"), Value::from("c1"))]),
+        ]));
+
+        // Split the message across writes to exercise buffering
+        let (head, tail) = message.split_at(message.len() / 2);
+        client.write_all(head).await.unwrap();
+        client.flush().await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        client.write_all(tail).await.unwrap();
+
+        let mut ack = vec![0u8; 64];
+        let len = tokio::time::timeout(Duration::from_secs(5), client.read(&mut ack))
+            .await
+            .unwrap()
+            .unwrap();
+        receiver.abort();
+
+        assert_eq!(&ack[..len], ack_response("c1").as_slice());
+        let logs = storage.list_logs(None, Some(10)).unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].body, "listening on :8080");
+        assert_eq!(logs[0].service_name.as_deref(), Some("api"));
+    }
+
+    #[tokio::test]
+    async fn test_fluent_receiver_without_ack() {
+        let (storage, mut client, receiver) = start().await;
+        for i in 0..3 {
+            let message = encode(&Value::Array(vec![
+                Value::from("app"),
+                Value::from(1_700_000_000 + i),
+                Value::Map(vec![(Value::from("message"), Value::from("tick"))]),
+            ]));
+            client.write_all(&message).await.unwrap();
+        }
+        client.shutdown().await.unwrap();
+
+        for _ in 0..100 {
+            if storage.count_logs().unwrap() == 3 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        receiver.abort();
+
+        assert_eq!(storage.count_logs().unwrap(), 3);
+    }
+}
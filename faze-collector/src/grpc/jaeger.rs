@@ -0,0 +1,111 @@
+use crate::ingest::{DEFAULT_QUEUE_DEPTH, IngestQueue, log_failures};
+use crate::{
+    convert::jaeger::convert_batch,
+    proto::jaeger::api_v2::{
+        PostSpansRequest, PostSpansResponse,
+        collector_service_server::{CollectorService, CollectorServiceServer},
+    },
+};
+use faze::Storage;
+use tonic::{Request, Response, Status, codec::CompressionEncoding};
+use tracing::error;
+
+/// Jaeger collector that receives spans via gRPC (`jaeger.api_v2.CollectorService`)
+pub struct JaegerCollector {
+    queue: IngestQueue,
+}
+
+impl JaegerCollector {
+    /// Create a collector with its own ingest queue
+    pub fn new(storage: Storage) -> Self {
+        Self::with_queue(IngestQueue::spawn(storage, DEFAULT_QUEUE_DEPTH))
+    }
+
+    /// Create a collector that writes through a queue shared with other receivers
+    pub fn with_queue(queue: IngestQueue) -> Self {
+        Self { queue }
+    }
+
+    pub fn into_service(self) -> CollectorServiceServer<Self> {
+        CollectorServiceServer::new(self)
+            .accept_compressed(CompressionEncoding::Gzip)
+            .accept_compressed(CompressionEncoding::Zstd)
+    }
+}
+
+#[tonic::async_trait]
+impl CollectorService for JaegerCollector {
+    async fn post_spans(
+        &self,
+        request: Request<PostSpansRequest>,
+    ) -> Result<Response<PostSpansResponse>, Status> {
+        let Some(batch) = request.into_inner().batch else {
+            return Ok(Response::new(PostSpansResponse {}));
+        };
+
+        let spans = convert_batch(&batch);
+        let (spans, outcome) = self.queue.submit_spans(spans).await.map_err(|e| {
+            error!("Failed to write span batch: {}", e);
+            Status::from(e)
+        })?;
+
+        log_failures("span", &spans, &outcome, |span| &span.span_id);
+
+        Ok(Response::new(PostSpansResponse {}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::jaeger::api_v2::{Batch, KeyValue, Process, Span as JaegerSpan, ValueType};
+
+    #[tokio::test]
+    async fn test_post_spans() {
+        let storage = Storage::new_in_memory().unwrap();
+        let collector = JaegerCollector::new(storage.clone());
+
+        let request = PostSpansRequest {
+            batch: Some(Batch {
+                spans: vec![JaegerSpan {
+                    trace_id: vec![1; 16],
+                    span_id: vec![2; 8],
+                    operation_name: "GET /api/users".to_string(),
+                    ..Default::default()
+                }],
+                process: Some(Process {
+                    service_name: "frontend".to_string(),
+                    tags: vec![KeyValue {
+                        key: "hostname".to_string(),
+                        v_type: ValueType::String as i32,
+                        v_str: "web-1".to_string(),
+                        ..Default::default()
+                    }],
+                }),
+            }),
+        };
+
+        collector.post_spans(Request::new(request)).await.unwrap();
+
+        let trace = storage
+            .get_trace_by_id("01010101010101010101010101010101")
+            .unwrap();
+        assert_eq!(trace.spans.len(), 1);
+        let resource = trace.spans[0].resource.as_ref().unwrap();
+        assert_eq!(resource.service_name(), Some("frontend"));
+        assert_eq!(resource.attributes.get_string("hostname"), Some("web-1"));
+    }
+
+    #[tokio::test]
+    async fn test_post_spans_without_batch() {
+        let storage = Storage::new_in_memory().unwrap();
+        let collector = JaegerCollector::new(storage.clone());
+
+        collector
+            .post_spans(Request::new(PostSpansRequest { batch: None }))
+            .await
+            .unwrap();
+
+        assert_eq!(storage.count_spans().unwrap(), 0);
+    }
+}
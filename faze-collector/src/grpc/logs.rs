@@ -1,3 +1,4 @@
+use crate::ingest::{DEFAULT_QUEUE_DEPTH, IngestQueue};
 use crate::{
     convert::logs::convert_resource_logs,
     proto::opentelemetry::proto::collector::logs::v1::{
@@ -6,24 +7,29 @@ use crate::{
     },
 };
 use faze::Storage;
-use std::sync::Arc;
-use tonic::{Request, Response, Status};
+use tonic::{Request, Response, Status, codec::CompressionEncoding};
 use tracing::error;
 
 /// OTLP collector that receives logs via gRPC
 pub struct OtlpLogsCollector {
-    storage: Arc<Storage>,
+    queue: IngestQueue,
 }
 
 impl OtlpLogsCollector {
+    /// Create a collector with its own ingest queue
     pub fn new(storage: Storage) -> Self {
-        Self {
-            storage: Arc::new(storage),
-        }
+        Self::with_queue(IngestQueue::spawn(storage, DEFAULT_QUEUE_DEPTH))
+    }
+
+    /// Create a collector that writes through a queue shared with other receivers
+    pub fn with_queue(queue: IngestQueue) -> Self {
+        Self { queue }
     }
 
     pub fn into_service(self) -> LogsServiceServer<Self> {
         LogsServiceServer::new(self)
+            .accept_compressed(CompressionEncoding::Gzip)
+            .accept_compressed(CompressionEncoding::Zstd)
     }
 }
 
@@ -36,15 +42,18 @@ impl LogsService for OtlpLogsCollector {
         let req = request.into_inner();
         let logs = convert_resource_logs(&req.resource_logs);
 
-        let mut rejected_log_records = 0;
+        let (logs, outcome) = self.queue.submit_logs(logs).await.map_err(|e| {
+            error!("Failed to write log batch: {}", e);
+            Status::from(e)
+        })?;
+
+        let rejected_log_records = outcome.rejected() as i64;
         let mut error_messages = Vec::new();
 
-        for log in &logs {
-            if let Err(e) = self.storage.insert_log(log) {
-                error!("Failed to insert span {:?}: {}", log.span_id, e);
-                rejected_log_records += 1;
-                error_messages.push(format!("span {:?}: {}", log.span_id, e));
-            }
+        for (index, e) in &outcome.failures {
+            let log = &logs[*index];
+            error!("Failed to insert span {:?}: {}", log.span_id, e);
+            error_messages.push(format!("span {:?}: {}", log.span_id, e));
         }
 
         let response = if rejected_log_records > 0 {
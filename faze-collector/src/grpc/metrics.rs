@@ -1,3 +1,4 @@
+use crate::ingest::{DEFAULT_QUEUE_DEPTH, IngestQueue};
 use crate::{
     convert::metrics::convert_resource_metrics,
     proto::opentelemetry::proto::collector::metrics::v1::{
@@ -6,24 +7,29 @@ use crate::{
     },
 };
 use faze::Storage;
-use std::sync::Arc;
-use tonic::{Request, Response, Status};
+use tonic::{Request, Response, Status, codec::CompressionEncoding};
 use tracing::error;
 
 /// OTLP collector that receives metrics via gRPC
 pub struct OtlpMetricsCollector {
-    storage: Arc<Storage>,
+    queue: IngestQueue,
 }
 
 impl OtlpMetricsCollector {
+    /// Create a collector with its own ingest queue
     pub fn new(storage: Storage) -> Self {
-        Self {
-            storage: Arc::new(storage),
-        }
+        Self::with_queue(IngestQueue::spawn(storage, DEFAULT_QUEUE_DEPTH))
+    }
+
+    /// Create a collector that writes through a queue shared with other receivers
+    pub fn with_queue(queue: IngestQueue) -> Self {
+        Self { queue }
     }
 
     pub fn into_service(self) -> MetricsServiceServer<Self> {
         MetricsServiceServer::new(self)
+            .accept_compressed(CompressionEncoding::Gzip)
+            .accept_compressed(CompressionEncoding::Zstd)
     }
 }
 
@@ -37,20 +43,21 @@ impl MetricsService for OtlpMetricsCollector {
 
         let metrics = convert_resource_metrics(req.resource_metrics);
 
+        let (metrics, outcome) = self.queue.submit_metrics(metrics).await.map_err(|e| {
+            error!("Failed to write metric batch: {}", e);
+            Status::from(e)
+        })?;
+
         let mut rejected_data_points = 0;
         let mut error_messages = Vec::new();
 
-        for metric in metrics {
-            let points_count = metric.data_points.len() as i64;
-
-            if let Err(e) = self.storage.insert_metric(&metric) {
-                error!("Failed to insert metric: {}", e);
+        for (index, e) in &outcome.failures {
+            error!("Failed to insert metric: {}", e);
 
-                rejected_data_points += points_count;
+            rejected_data_points += metrics[*index].data_points.len() as i64;
 
-                if error_messages.len() < 5 {
-                    error_messages.push(format!("Error inserting metric: {}", e));
-                }
+            if error_messages.len() < 5 {
+                error_messages.push(format!("Error inserting metric: {}", e));
             }
         }
 
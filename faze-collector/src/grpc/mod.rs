@@ -1,3 +1,4 @@
+pub mod jaeger;
 pub mod logs;
 pub mod metrics;
 pub mod traces;
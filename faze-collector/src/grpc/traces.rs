@@ -1,3 +1,4 @@
+use crate::ingest::{DEFAULT_QUEUE_DEPTH, IngestQueue};
 use crate::{
     convert::traces::convert_resource_spans,
     proto::opentelemetry::proto::collector::trace::v1::{
@@ -6,24 +7,29 @@ use crate::{
     },
 };
 use faze::Storage;
-use std::sync::Arc;
-use tonic::{Request, Response, Status};
+use tonic::{Request, Response, Status, codec::CompressionEncoding};
 use tracing::error;
 
 /// OTLP collector that receives traces via gRPC
 pub struct OtlpSpansCollector {
-    storage: Arc<Storage>,
+    queue: IngestQueue,
 }
 
 impl OtlpSpansCollector {
+    /// Create a collector with its own ingest queue
     pub fn new(storage: Storage) -> Self {
-        Self {
-            storage: Arc::new(storage),
-        }
+        Self::with_queue(IngestQueue::spawn(storage, DEFAULT_QUEUE_DEPTH))
+    }
+
+    /// Create a collector that writes through a queue shared with other receivers
+    pub fn with_queue(queue: IngestQueue) -> Self {
+        Self { queue }
     }
 
     pub fn into_service(self) -> TraceServiceServer<Self> {
         TraceServiceServer::new(self)
+            .accept_compressed(CompressionEncoding::Gzip)
+            .accept_compressed(CompressionEncoding::Zstd)
     }
 }
 
@@ -35,15 +41,18 @@ impl TraceService for OtlpSpansCollector {
     ) -> Result<Response<ExportTraceServiceResponse>, Status> {
         let req = request.into_inner();
         let spans = convert_resource_spans(&req.resource_spans);
-        let mut rejected_spans = 0;
+        let (spans, outcome) = self.queue.submit_spans(spans).await.map_err(|e| {
+            error!("Failed to write span batch: {}", e);
+            Status::from(e)
+        })?;
+
+        let rejected_spans = outcome.rejected() as i64;
         let mut error_messages = Vec::new();
 
-        for span in &spans {
-            if let Err(e) = self.storage.insert_span(span) {
-                error!("Failed to insert span {}: {}", span.span_id, e);
-                rejected_spans += 1;
-                error_messages.push(format!("span {}: {}", span.span_id, e));
-            }
+        for (index, e) in &outcome.failures {
+            let span = &spans[*index];
+            error!("Failed to insert span {}: {}", span.span_id, e);
+            error_messages.push(format!("span {}: {}", span.span_id, e));
         }
 
         let response = if rejected_spans > 0 {
@@ -144,6 +153,40 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[tokio::test]
+    async fn test_export_retried_request() {
+        let storage = Storage::new_in_memory().unwrap();
+        let collector = OtlpSpansCollector::new(storage.clone());
+
+        let request = ExportTraceServiceRequest {
+            resource_spans: vec![ResourceSpans {
+                resource: None,
+                scope_spans: vec![ScopeSpans {
+                    scope: None,
+                    spans: vec![create_test_otlp_span(
+                        &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16],
+                        &[1, 2, 3, 4, 5, 6, 7, 8],
+                        "GET /api/users",
+                    )],
+                    schema_url: String::new(),
+                }],
+                schema_url: String::new(),
+            }],
+        };
+
+        for _ in 0..2 {
+            let response = collector
+                .export(Request::new(request.clone()))
+                .await
+                .unwrap()
+                .into_inner();
+
+            assert!(response.partial_success.is_none());
+        }
+
+        assert_eq!(storage.count_spans().unwrap(), 1);
+    }
+
     #[tokio::test]
     async fn test_export_multiple_spans() {
         let storage = Storage::new_in_memory().unwrap();
@@ -1,59 +1,269 @@
 use crate::{
-    convert::traces::convert_resource_spans,
-    proto::opentelemetry::proto::collector::trace::v1::ExportTraceServiceRequest,
+    convert::{
+        logs::convert_resource_logs, metrics::convert_resource_metrics,
+        traces::convert_resource_spans,
+    },
+    ingest::{DEFAULT_QUEUE_DEPTH, IngestError, IngestQueue, RETRY_AFTER},
+    proto::opentelemetry::proto::collector::{
+        logs::v1::{ExportLogsPartialSuccess, ExportLogsServiceRequest, ExportLogsServiceResponse},
+        metrics::v1::{
+            ExportMetricsPartialSuccess, ExportMetricsServiceRequest, ExportMetricsServiceResponse,
+        },
+        trace::v1::{
+            ExportTracePartialSuccess, ExportTraceServiceRequest, ExportTraceServiceResponse,
+        },
+    },
 };
 use axum::{
     Router,
     body::Bytes,
     extract::State,
-    http::StatusCode,
+    http::{HeaderMap, StatusCode, header},
     response::{IntoResponse, Response},
     routing::post,
 };
 use faze::Storage;
 use prost::Message;
+use serde::{Serialize, de::DeserializeOwned};
 use std::sync::Arc;
+use tower_http::decompression::RequestDecompressionLayer;
 use tracing::error;
 
+const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+const JSON_CONTENT_TYPE: &str = "application/json";
+
+/// Body encoding of an OTLP/HTTP request, negotiated from its `Content-Type`
+///
+/// Responses are written back using the same encoding as the request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Protobuf,
+    Json,
+}
+
+impl Encoding {
+    /// Pick the encoding from the request headers, defaulting to protobuf
+    fn from_headers(headers: &HeaderMap) -> Result<Self, StatusCode> {
+        let Some(content_type) = headers.get(header::CONTENT_TYPE) else {
+            return Ok(Self::Protobuf);
+        };
+
+        let mime = content_type
+            .to_str()
+            .unwrap_or_default()
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+
+        match mime.as_str() {
+            PROTOBUF_CONTENT_TYPE | "application/protobuf" => Ok(Self::Protobuf),
+            JSON_CONTENT_TYPE => Ok(Self::Json),
+            other => {
+                error!("Unsupported content type: {}", other);
+                Err(StatusCode::UNSUPPORTED_MEDIA_TYPE)
+            }
+        }
+    }
+
+    /// Decode an OTLP export request body
+    fn decode<M: Message + Default + DeserializeOwned>(self, body: Bytes) -> Result<M, StatusCode> {
+        match self {
+            Self::Protobuf => M::decode(body).map_err(|e| {
+                error!("Failed to decode protobuf: {}", e);
+                StatusCode::BAD_REQUEST
+            }),
+            Self::Json => serde_json::from_slice(&body).map_err(|e| {
+                error!("Failed to decode JSON: {}", e);
+                StatusCode::BAD_REQUEST
+            }),
+        }
+    }
+
+    /// Encode an OTLP export response as an HTTP response
+    fn encode<M: Message + Serialize>(self, message: &M) -> Response {
+        let (content_type, body) = match self {
+            Self::Protobuf => (PROTOBUF_CONTENT_TYPE, message.encode_to_vec()),
+            Self::Json => match serde_json::to_vec(message) {
+                Ok(body) => (JSON_CONTENT_TYPE, body),
+                Err(e) => {
+                    error!("Failed to encode JSON response: {}", e);
+                    return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+                }
+            },
+        };
+
+        (StatusCode::OK, [(header::CONTENT_TYPE, content_type)], body).into_response()
+    }
+}
+
 /// HTTP handler for OTLP trace export
 async fn export_traces(
-    State(storage): State<Arc<Storage>>,
+    State(queue): State<IngestQueue>,
+    headers: HeaderMap,
     body: Bytes,
 ) -> Result<Response, StatusCode> {
-    let request = ExportTraceServiceRequest::decode(body).map_err(|e| {
-        error!("Failed to decode protobuf: {}", e);
-        StatusCode::BAD_REQUEST
-    })?;
+    let encoding = Encoding::from_headers(&headers)?;
+    let request: ExportTraceServiceRequest = encoding.decode(body)?;
 
     let spans = convert_resource_spans(&request.resource_spans);
-    let mut rejected_spans = 0;
+    let (spans, outcome) = match queue.submit_spans(spans).await {
+        Ok(written) => written,
+        Err(e) => {
+            error!("Failed to write span batch: {}", e);
+            return Ok(e.into_response());
+        }
+    };
+
+    let rejected_spans = outcome.rejected() as i64;
+    let mut error_messages = Vec::new();
+
+    for (index, e) in &outcome.failures {
+        let span = &spans[*index];
+        error!("Failed to insert span {}: {}", span.span_id, e);
+        error_messages.push(format!("span {}: {}", span.span_id, e));
+    }
+
+    let response = if rejected_spans > 0 {
+        ExportTraceServiceResponse {
+            partial_success: Some(ExportTracePartialSuccess {
+                rejected_spans,
+                error_message: error_messages.join("; "),
+            }),
+        }
+    } else {
+        ExportTraceServiceResponse {
+            partial_success: None,
+        }
+    };
+
+    Ok(encoding.encode(&response))
+}
+
+/// HTTP handler for OTLP logs export
+async fn export_logs(
+    State(queue): State<IngestQueue>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, StatusCode> {
+    let encoding = Encoding::from_headers(&headers)?;
+    let request: ExportLogsServiceRequest = encoding.decode(body)?;
+
+    let logs = convert_resource_logs(&request.resource_logs);
+    let (logs, outcome) = match queue.submit_logs(logs).await {
+        Ok(written) => written,
+        Err(e) => {
+            error!("Failed to write log batch: {}", e);
+            return Ok(e.into_response());
+        }
+    };
+
+    let rejected_log_records = outcome.rejected() as i64;
+    let mut error_messages = Vec::new();
+
+    for (index, e) in &outcome.failures {
+        let log = &logs[*index];
+        error!("Failed to insert log {:?}: {}", log.span_id, e);
+        error_messages.push(format!("log {:?}: {}", log.span_id, e));
+    }
+
+    let response = if rejected_log_records > 0 {
+        ExportLogsServiceResponse {
+            partial_success: Some(ExportLogsPartialSuccess {
+                rejected_log_records,
+                error_message: error_messages.join("; "),
+            }),
+        }
+    } else {
+        ExportLogsServiceResponse {
+            partial_success: None,
+        }
+    };
+
+    Ok(encoding.encode(&response))
+}
+
+/// HTTP handler for OTLP metrics export
+async fn export_metrics(
+    State(queue): State<IngestQueue>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, StatusCode> {
+    let encoding = Encoding::from_headers(&headers)?;
+    let request: ExportMetricsServiceRequest = encoding.decode(body)?;
+
+    let metrics = convert_resource_metrics(request.resource_metrics);
+    let (metrics, outcome) = match queue.submit_metrics(metrics).await {
+        Ok(written) => written,
+        Err(e) => {
+            error!("Failed to write metric batch: {}", e);
+            return Ok(e.into_response());
+        }
+    };
+
+    let mut rejected_data_points = 0;
     let mut error_messages = Vec::new();
 
-    for span in &spans {
-        if let Err(e) = storage.insert_span(span) {
-            error!("Failed to insert span {}: {}", span.span_id, e);
-            rejected_spans += 1;
-            error_messages.push(format!("span {}: {}", span.span_id, e));
+    for (index, e) in &outcome.failures {
+        let metric = &metrics[*index];
+        error!("Failed to insert metric {}: {}", metric.name, e);
+        rejected_data_points += metric.data_points.len() as i64;
+
+        if error_messages.len() < 5 {
+            error_messages.push(format!("metric {}: {}", metric.name, e));
         }
     }
 
-    if rejected_spans > 0 {
-        error!(
-            "Rejected {} spans: {}",
-            rejected_spans,
-            error_messages.join("; ")
-        );
-        Ok((StatusCode::INTERNAL_SERVER_ERROR, error_messages.join("; ")).into_response())
+    let response = if rejected_data_points > 0 {
+        ExportMetricsServiceResponse {
+            partial_success: Some(ExportMetricsPartialSuccess {
+                rejected_data_points,
+                error_message: error_messages.join("; "),
+            }),
+        }
     } else {
-        Ok(StatusCode::OK.into_response())
+        ExportMetricsServiceResponse {
+            partial_success: None,
+        }
+    };
+
+    Ok(encoding.encode(&response))
+}
+
+impl IntoResponse for IngestError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            IngestError::QueueFull => StatusCode::TOO_MANY_REQUESTS,
+            IngestError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+            IngestError::Storage(_) => return StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+        };
+
+        (
+            status,
+            [(header::RETRY_AFTER, RETRY_AFTER.as_secs().to_string())],
+        )
+            .into_response()
     }
 }
 
-/// Create HTTP router for OTLP collector
+/// Create HTTP router for OTLP collector, with its own ingest queue
 pub fn create_router(storage: Arc<Storage>) -> Router {
+    create_router_with_queue(IngestQueue::spawn(
+        Storage::clone(&storage),
+        DEFAULT_QUEUE_DEPTH,
+    ))
+}
+
+/// Create HTTP router for OTLP collector, writing through a queue shared with other receivers
+pub fn create_router_with_queue(queue: IngestQueue) -> Router {
     Router::new()
         .route("/v1/traces", post(export_traces))
-        .with_state(storage)
+        .route("/v1/logs", post(export_logs))
+        .route("/v1/metrics", post(export_metrics))
+        // Decompress `Content-Encoding: gzip|deflate|zstd` bodies, answering 415 for anything else
+        .layer(RequestDecompressionLayer::new())
+        .with_state(queue)
 }
 
 #[cfg(test)]
@@ -61,6 +271,11 @@ mod tests {
     use super::*;
     use crate::proto::opentelemetry::proto::{
         common::v1::{AnyValue, KeyValue, any_value},
+        logs::v1::{LogRecord, ResourceLogs, ScopeLogs, SeverityNumber},
+        metrics::v1::{
+            Exemplar, Gauge, Metric as OtlpMetric, NumberDataPoint, ResourceMetrics, ScopeMetrics,
+            exemplar, metric, number_data_point,
+        },
         resource::v1::Resource,
         trace::v1::{
             ResourceSpans, ScopeSpans, Span as OtlpSpan, SpanKind as OtlpSpanKind,
@@ -136,6 +351,34 @@ mod tests {
         assert_eq!(count, 1);
     }
 
+    #[tokio::test]
+    async fn test_export_traces_writer_unavailable() {
+        let (queue, writer) = IngestQueue::new(4);
+        drop(writer);
+        let app = create_router_with_queue(queue);
+        let mut buf = Vec::new();
+        create_test_request().encode(&mut buf).unwrap();
+        let request = Request::builder()
+            .uri("/v1/traces")
+            .method("POST")
+            .header("content-type", "application/x-protobuf")
+            .body(Body::from(buf))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+    }
+
+    #[test]
+    fn test_queue_full_response() {
+        let response = IngestError::QueueFull.into_response();
+
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()[header::RETRY_AFTER], "1");
+    }
+
     #[tokio::test]
     async fn test_export_traces_invalid_protobuf() {
         let storage = Arc::new(Storage::new_in_memory().unwrap());
@@ -477,4 +720,463 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
         assert_eq!(storage.count_spans().unwrap(), 100);
     }
+
+    fn create_test_logs_request() -> ExportLogsServiceRequest {
+        ExportLogsServiceRequest {
+            resource_logs: vec![ResourceLogs {
+                resource: Some(Resource {
+                    attributes: vec![KeyValue {
+                        key: "service.name".to_string(),
+                        value: Some(AnyValue {
+                            value: Some(any_value::Value::StringValue("log-service".to_string())),
+                        }),
+                    }],
+                    dropped_attributes_count: 0,
+                }),
+                scope_logs: vec![ScopeLogs {
+                    scope: None,
+                    log_records: vec![LogRecord {
+                        time_unix_nano: 1_000_000_000_000_000_000,
+                        observed_time_unix_nano: 1_000_000_000_000_000_000,
+                        severity_number: SeverityNumber::Info as i32,
+                        severity_text: "INFO".to_string(),
+                        body: Some(AnyValue {
+                            value: Some(any_value::Value::StringValue(
+                                "user logged in".to_string(),
+                            )),
+                        }),
+                        attributes: vec![],
+                        dropped_attributes_count: 0,
+                        flags: 0,
+                        trace_id: vec![1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16],
+                        span_id: vec![1, 2, 3, 4, 5, 6, 7, 8],
+                        event_name: String::new(),
+                    }],
+                    schema_url: String::new(),
+                }],
+                schema_url: String::new(),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_logs_http() {
+        let storage = Arc::new(Storage::new_in_memory().unwrap());
+        let app = create_router(storage.clone());
+        let mut buf = Vec::new();
+        create_test_logs_request().encode(&mut buf).unwrap();
+        let request = Request::builder()
+            .uri("/v1/logs")
+            .method("POST")
+            .header("content-type", "application/x-protobuf")
+            .body(Body::from(buf))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "application/x-protobuf"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let export_response = ExportLogsServiceResponse::decode(body).unwrap();
+        assert!(export_response.partial_success.is_none());
+
+        let logs = storage.list_logs(None, None).unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].body, "user logged in");
+        assert_eq!(logs[0].service_name, Some("log-service".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_export_logs_invalid_protobuf() {
+        let storage = Arc::new(Storage::new_in_memory().unwrap());
+        let app = create_router(storage.clone());
+        let request = Request::builder()
+            .uri("/v1/logs")
+            .method("POST")
+            .header("content-type", "application/x-protobuf")
+            .body(Body::from(&b"not a valid protobuf message"[..]))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(storage.count_logs().unwrap(), 0);
+    }
+
+    fn create_test_metrics_request() -> ExportMetricsServiceRequest {
+        ExportMetricsServiceRequest {
+            resource_metrics: vec![ResourceMetrics {
+                resource: Some(Resource {
+                    attributes: vec![KeyValue {
+                        key: "service.name".to_string(),
+                        value: Some(AnyValue {
+                            value: Some(any_value::Value::StringValue(
+                                "metric-service".to_string(),
+                            )),
+                        }),
+                    }],
+                    dropped_attributes_count: 0,
+                }),
+                scope_metrics: vec![ScopeMetrics {
+                    scope: None,
+                    metrics: vec![OtlpMetric {
+                        name: "process.memory.usage".to_string(),
+                        description: "Memory in use".to_string(),
+                        unit: "By".to_string(),
+                        metadata: vec![],
+                        data: Some(metric::Data::Gauge(Gauge {
+                            data_points: vec![
+                                NumberDataPoint {
+                                    attributes: vec![],
+                                    start_time_unix_nano: 0,
+                                    time_unix_nano: 1_000_000_000_000_000_000,
+                                    exemplars: vec![],
+                                    flags: 0,
+                                    value: Some(number_data_point::Value::AsInt(1024)),
+                                },
+                                NumberDataPoint {
+                                    attributes: vec![],
+                                    start_time_unix_nano: 0,
+                                    time_unix_nano: 1_000_000_001_000_000_000,
+                                    exemplars: vec![],
+                                    flags: 0,
+                                    value: Some(number_data_point::Value::AsDouble(2048.0)),
+                                },
+                            ],
+                        })),
+                    }],
+                    schema_url: String::new(),
+                }],
+                schema_url: String::new(),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_metrics_http() {
+        let storage = Arc::new(Storage::new_in_memory().unwrap());
+        let app = create_router(storage.clone());
+        let mut buf = Vec::new();
+        create_test_metrics_request().encode(&mut buf).unwrap();
+        let request = Request::builder()
+            .uri("/v1/metrics")
+            .method("POST")
+            .header("content-type", "application/x-protobuf")
+            .body(Body::from(buf))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let export_response = ExportMetricsServiceResponse::decode(body).unwrap();
+        assert!(export_response.partial_success.is_none());
+
+        assert_eq!(storage.count_metrics().unwrap(), 2);
+        let metrics = storage.list_metrics(Some("metric-service"), None).unwrap();
+        assert!(metrics.iter().all(|m| m.name == "process.memory.usage"));
+    }
+
+    #[tokio::test]
+    async fn test_export_metrics_partial_success() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("faze.db");
+        let storage = Arc::new(Storage::new_with_path(&db_path).unwrap());
+        // Fails exemplar inserts, which come after their metric row has been written
+        rusqlite::Connection::open(&db_path)
+            .unwrap()
+            .execute_batch(
+                "CREATE TRIGGER reject_exemplar BEFORE INSERT ON metric_exemplars
+                 WHEN NEW.value < 0 BEGIN SELECT RAISE(ABORT, 'rejected'); END",
+            )
+            .unwrap();
+        let app = create_router(storage.clone());
+        let mut export = create_test_metrics_request();
+        let point = |time_unix_nano: u64, value: f64| NumberDataPoint {
+            time_unix_nano,
+            value: Some(number_data_point::Value::AsDouble(1.0)),
+            exemplars: vec![Exemplar {
+                value: Some(exemplar::Value::AsDouble(value)),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        // The last exemplar is refused, so this whole metric is rejected after its first
+        // points were written
+        export.resource_metrics[0].scope_metrics[0]
+            .metrics
+            .push(OtlpMetric {
+                name: "queue.depth".to_string(),
+                data: Some(metric::Data::Gauge(Gauge {
+                    data_points: vec![
+                        point(1_000_000_000_000_000_000, 1.0),
+                        point(1_000_000_001_000_000_000, 2.0),
+                        point(1_000_000_002_000_000_000, -1.0),
+                    ],
+                })),
+                ..Default::default()
+            });
+        let mut buf = Vec::new();
+        export.encode(&mut buf).unwrap();
+        let request = Request::builder()
+            .uri("/v1/metrics")
+            .method("POST")
+            .header("content-type", "application/x-protobuf")
+            .body(Body::from(buf))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let export_response = ExportMetricsServiceResponse::decode(body).unwrap();
+        let partial_success = export_response.partial_success.unwrap();
+        assert_eq!(partial_success.rejected_data_points, 3);
+        assert!(partial_success.error_message.contains("queue.depth"));
+
+        assert_eq!(storage.count_metrics().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_export_metrics_invalid_protobuf() {
+        let storage = Arc::new(Storage::new_in_memory().unwrap());
+        let app = create_router(storage.clone());
+        let request = Request::builder()
+            .uri("/v1/metrics")
+            .method("POST")
+            .header("content-type", "application/x-protobuf")
+            .body(Body::from(&b"not a valid protobuf message"[..]))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(storage.count_metrics().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_export_traces_json() {
+        let storage = Arc::new(Storage::new_in_memory().unwrap());
+        let app = create_router(storage.clone());
+        let body = serde_json::json!({
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [
+                        {"key": "service.name", "value": {"stringValue": "json-service"}}
+                    ]
+                },
+                "scopeSpans": [{
+                    "spans": [{
+                        "traceId": "0102030405060708090a0b0c0d0e0f10",
+                        "spanId": "0102030405060708",
+                        "name": "json-span",
+                        "kind": 2,
+                        "startTimeUnixNano": "1000000000000000000",
+                        "endTimeUnixNano": "1000000000100000000",
+                        "attributes": [
+                            {"key": "http.status_code", "value": {"intValue": "201"}}
+                        ]
+                    }]
+                }]
+            }]
+        });
+        let request = Request::builder()
+            .uri("/v1/traces")
+            .method("POST")
+            .header("content-type", "application/json; charset=utf-8")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "application/json"
+        );
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"{}");
+
+        let trace = storage
+            .get_trace_by_id("0102030405060708090a0b0c0d0e0f10")
+            .unwrap();
+        let span = &trace.spans[0];
+        assert_eq!(span.name, "json-span");
+        assert_eq!(span.kind, faze::SpanKind::Server);
+        assert_eq!(span.service_name, Some("json-service".to_string()));
+        assert_eq!(span.attributes.get_int("http.status_code"), Some(201));
+    }
+
+    #[tokio::test]
+    async fn test_export_logs_json() {
+        let storage = Arc::new(Storage::new_in_memory().unwrap());
+        let app = create_router(storage.clone());
+        let body = serde_json::json!({
+            "resourceLogs": [{
+                "scopeLogs": [{
+                    "logRecords": [{
+                        "timeUnixNano": "1000000000000000000",
+                        "severityNumber": "SEVERITY_NUMBER_ERROR",
+                        "body": {"stringValue": "payment failed"}
+                    }]
+                }]
+            }]
+        });
+        let request = Request::builder()
+            .uri("/v1/logs")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let logs = storage.list_logs(None, None).unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].body, "payment failed");
+        assert!(logs[0].is_error());
+    }
+
+    #[tokio::test]
+    async fn test_export_metrics_json() {
+        let storage = Arc::new(Storage::new_in_memory().unwrap());
+        let app = create_router(storage.clone());
+        let body = serde_json::json!({
+            "resourceMetrics": [{
+                "scopeMetrics": [{
+                    "metrics": [{
+                        "name": "queue.depth",
+                        "gauge": {
+                            "dataPoints": [{"timeUnixNano": "1000000000000000000", "asInt": "7"}]
+                        }
+                    }]
+                }]
+            }]
+        });
+        let request = Request::builder()
+            .uri("/v1/metrics")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let metrics = storage.list_metrics(None, None).unwrap();
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].latest_value(), Some(7.0));
+    }
+
+    #[tokio::test]
+    async fn test_export_traces_invalid_json() {
+        let storage = Arc::new(Storage::new_in_memory().unwrap());
+        let app = create_router(storage.clone());
+        let request = Request::builder()
+            .uri("/v1/traces")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(Body::from("{\"resourceSpans\": 42}"))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_export_traces_unsupported_content_type() {
+        let storage = Arc::new(Storage::new_in_memory().unwrap());
+        let app = create_router(storage.clone());
+        let request = Request::builder()
+            .uri("/v1/traces")
+            .method("POST")
+            .header("content-type", "text/plain")
+            .body(Body::from("hello"))
+            .unwrap();
+
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    async fn export_compressed_traces(
+        content_encoding: &str,
+        body: Vec<u8>,
+    ) -> (StatusCode, Arc<Storage>) {
+        let storage = Arc::new(Storage::new_in_memory().unwrap());
+        let app = create_router(storage.clone());
+        let request = Request::builder()
+            .uri("/v1/traces")
+            .method("POST")
+            .header("content-type", "application/x-protobuf")
+            .header("content-encoding", content_encoding)
+            .body(Body::from(body))
+            .unwrap();
+
+        let status = app.oneshot(request).await.unwrap().status();
+        (status, storage)
+    }
+
+    #[tokio::test]
+    async fn test_export_traces_gzip() {
+        use flate2::{Compression, write::GzEncoder};
+        use std::io::Write;
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&create_test_request().encode_to_vec())
+            .unwrap();
+        let body = encoder.finish().unwrap();
+
+        let (status, storage) = export_compressed_traces("gzip", body).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(storage.count_spans().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_export_traces_deflate() {
+        use flate2::{Compression, write::ZlibEncoder};
+        use std::io::Write;
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(&create_test_request().encode_to_vec())
+            .unwrap();
+        let body = encoder.finish().unwrap();
+
+        let (status, storage) = export_compressed_traces("deflate", body).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(storage.count_spans().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_export_traces_zstd() {
+        let body = zstd::encode_all(&create_test_request().encode_to_vec()[..], 0).unwrap();
+
+        let (status, storage) = export_compressed_traces("zstd", body).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(storage.count_spans().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_export_traces_unsupported_content_encoding() {
+        let body = create_test_request().encode_to_vec();
+
+        let (status, storage) = export_compressed_traces("br", body).await;
+        assert_eq!(status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(storage.count_spans().unwrap(), 0);
+    }
 }
@@ -0,0 +1,239 @@
+use faze::{BatchOutcome, Log, Metric, Span, Storage, StorageError};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot};
+use tonic::{Code, Status};
+use tonic_types::{ErrorDetails, StatusExt};
+use tracing::{debug, error};
+
+/// Number of export batches that can wait for the writer before receivers push back
+pub const DEFAULT_QUEUE_DEPTH: usize = 1024;
+
+/// How long clients are asked to wait before retrying a rejected export
+pub const RETRY_AFTER: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Error)]
+pub enum IngestError {
+    #[error("ingest queue is full")]
+    QueueFull,
+
+    #[error("ingest writer is not running")]
+    Unavailable,
+
+    #[error("failed to store batch: {0}")]
+    Storage(#[from] StorageError),
+}
+
+impl From<IngestError> for Status {
+    fn from(e: IngestError) -> Self {
+        match e {
+            // OTLP clients only retry RESOURCE_EXHAUSTED when the status carries RetryInfo
+            IngestError::QueueFull => Status::with_error_details(
+                Code::ResourceExhausted,
+                e.to_string(),
+                ErrorDetails::with_retry_info(Some(RETRY_AFTER)),
+            ),
+            IngestError::Unavailable => Status::unavailable(e.to_string()),
+            IngestError::Storage(_) => Status::internal(e.to_string()),
+        }
+    }
+}
+
+/// Items handed back to the receiver once the writer is done with them, so failures can be
+/// reported per item
+pub type Written<T> = (Vec<T>, BatchOutcome);
+
+/// Log the items of a written batch that failed to store, labelling each one with `label`
+///
+/// Only OTLP can report partial failures back to clients. Other receivers accept the batch
+/// anyway, since rejecting it would only make clients resend the items that were stored, so
+/// the failed items are logged and dropped.
+pub fn log_failures<T>(
+    kind: &str,
+    items: &[T],
+    outcome: &BatchOutcome,
+    label: impl Fn(&T) -> &str,
+) {
+    for (index, e) in &outcome.failures {
+        error!("Failed to insert {} {}: {}", kind, label(&items[*index]), e);
+    }
+}
+
+type Reply<T> = oneshot::Sender<(Vec<T>, faze::storage::Result<BatchOutcome>)>;
+
+enum IngestJob {
+    Spans(Vec<Span>, Reply<Span>),
+    Logs(Vec<Log>, Reply<Log>),
+    Metrics(Vec<Metric>, Reply<Metric>),
+}
+
+/// Bounded queue between the receivers and storage
+///
+/// Receivers submit whole export batches and await the outcome, while a single writer thread
+/// drains the queue. When the queue is full, submissions fail straight away instead of
+/// waiting, so receivers can tell clients to back off.
+#[derive(Clone)]
+pub struct IngestQueue {
+    sender: mpsc::Sender<IngestJob>,
+}
+
+/// Receiving end of an [`IngestQueue`], which writes queued batches to storage
+pub struct IngestWriter {
+    receiver: mpsc::Receiver<IngestJob>,
+}
+
+impl IngestQueue {
+    /// Create a queue holding up to `depth` batches, along with the writer that drains it
+    pub fn new(depth: usize) -> (Self, IngestWriter) {
+        let (sender, receiver) = mpsc::channel(depth.max(1));
+        (Self { sender }, IngestWriter { receiver })
+    }
+
+    /// Create a queue and start its writer
+    pub fn spawn(storage: Storage, depth: usize) -> Self {
+        let (queue, writer) = Self::new(depth);
+        writer.spawn(storage);
+        queue
+    }
+
+    /// Queue spans for writing and wait for the outcome
+    pub async fn submit_spans(&self, spans: Vec<Span>) -> Result<Written<Span>, IngestError> {
+        self.submit(|reply| IngestJob::Spans(spans, reply)).await
+    }
+
+    /// Queue logs for writing and wait for the outcome
+    pub async fn submit_logs(&self, logs: Vec<Log>) -> Result<Written<Log>, IngestError> {
+        self.submit(|reply| IngestJob::Logs(logs, reply)).await
+    }
+
+    /// Queue metrics for writing and wait for the outcome
+    pub async fn submit_metrics(
+        &self,
+        metrics: Vec<Metric>,
+    ) -> Result<Written<Metric>, IngestError> {
+        self.submit(|reply| IngestJob::Metrics(metrics, reply))
+            .await
+    }
+
+    async fn submit<T>(
+        &self,
+        job: impl FnOnce(Reply<T>) -> IngestJob,
+    ) -> Result<Written<T>, IngestError> {
+        let (reply, outcome) = oneshot::channel();
+
+        self.sender.try_send(job(reply)).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => IngestError::QueueFull,
+            mpsc::error::TrySendError::Closed(_) => IngestError::Unavailable,
+        })?;
+
+        let (items, result) = outcome.await.map_err(|_| IngestError::Unavailable)?;
+        Ok((items, result?))
+    }
+}
+
+impl IngestWriter {
+    /// Run the writer on its own thread, so SQLite writes never block the async runtime
+    pub fn spawn(self, storage: Storage) -> std::thread::JoinHandle<()> {
+        std::thread::Builder::new()
+            .name("faze-ingest-writer".to_string())
+            .spawn(move || self.run(&storage))
+            .expect("failed to spawn ingest writer thread")
+    }
+
+    /// Write batches until every [`IngestQueue`] handle has been dropped
+    pub fn run(mut self, storage: &Storage) {
+        while let Some(job) = self.receiver.blocking_recv() {
+            // A receiver that stopped waiting (e.g. a cancelled request) leaves nobody to reply
+            // to, but its batch is still written
+            match job {
+                IngestJob::Spans(spans, reply) => {
+                    let result = storage.insert_spans(&spans);
+                    let _ = reply.send((spans, result));
+                }
+                IngestJob::Logs(logs, reply) => {
+                    let result = storage.insert_logs(&logs);
+                    let _ = reply.send((logs, result));
+                }
+                IngestJob::Metrics(metrics, reply) => {
+                    let result = storage.insert_metrics(&metrics);
+                    let _ = reply.send((metrics, result));
+                }
+            }
+        }
+
+        debug!("Ingest queue closed, writer stopping");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use faze::models::{Attributes, SpanKind, Status as SpanStatus};
+
+    fn create_test_span(span_id: &str) -> Span {
+        Span::new(
+            span_id.to_string(),
+            "trace1".to_string(),
+            None,
+            "test".to_string(),
+            SpanKind::Internal,
+            1_000_000_000,
+            2_000_000_000,
+            Attributes::new(),
+            SpanStatus::ok(),
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_submit_spans() {
+        let storage = Storage::new_in_memory().unwrap();
+        let queue = IngestQueue::spawn(storage.clone(), 4);
+
+        let (spans, outcome) = queue
+            .submit_spans(vec![create_test_span("span1"), create_test_span("span2")])
+            .await
+            .unwrap();
+
+        assert_eq!(spans.len(), 2);
+        assert_eq!(outcome.rejected(), 0);
+        assert_eq!(storage.count_spans().unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_submit_when_queue_full() {
+        let (queue, _writer) = IngestQueue::new(1);
+
+        // Nothing drains the queue, so the first batch takes the only slot
+        let waiting = {
+            let queue = queue.clone();
+            tokio::spawn(async move { queue.submit_spans(vec![create_test_span("span1")]).await })
+        };
+        tokio::task::yield_now().await;
+        while queue.sender.capacity() > 0 {
+            tokio::task::yield_now().await;
+        }
+
+        let result = queue.submit_spans(vec![create_test_span("span2")]).await;
+        assert!(matches!(result, Err(IngestError::QueueFull)));
+
+        let status = Status::from(result.unwrap_err());
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(
+            status.get_details_retry_info().unwrap().retry_delay,
+            Some(RETRY_AFTER)
+        );
+
+        waiting.abort();
+    }
+
+    #[tokio::test]
+    async fn test_submit_without_writer() {
+        let (queue, writer) = IngestQueue::new(4);
+        drop(writer);
+
+        let result = queue.submit_spans(vec![create_test_span("span1")]).await;
+        assert!(matches!(result, Err(IngestError::Unavailable)));
+        assert_eq!(Status::from(result.unwrap_err()).code(), Code::Unavailable);
+    }
+}
@@ -0,0 +1,141 @@
+use crate::{
+    convert::jaeger::{convert_batch, decode_thrift_batch},
+    ingest::{IngestQueue, log_failures},
+};
+use axum::{
+    Router,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use tower_http::decompression::RequestDecompressionLayer;
+use tracing::error;
+
+/// HTTP handler for Jaeger Thrift span export
+///
+/// Only the binary Thrift protocol is supported; other encodings are answered with 415.
+async fn export_spans(
+    State(queue): State<IngestQueue>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let is_binary_thrift = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| {
+            value.starts_with("application/x-thrift")
+                || value.starts_with("application/vnd.apache.thrift.binary")
+        });
+    if !is_binary_thrift {
+        return StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response();
+    }
+
+    let batch = match decode_thrift_batch(&body) {
+        Ok(batch) => batch,
+        Err(e) => {
+            error!("Failed to decode Jaeger batch: {}", e);
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    };
+
+    match queue.submit_spans(convert_batch(&batch)).await {
+        Ok((spans, outcome)) => {
+            log_failures("span", &spans, &outcome, |span| &span.span_id);
+            StatusCode::ACCEPTED.into_response()
+        }
+        Err(e) => {
+            error!("Failed to write span batch: {}", e);
+            e.into_response()
+        }
+    }
+}
+
+/// Create HTTP router for the Jaeger Thrift-over-HTTP receiver
+pub fn create_router(queue: IngestQueue) -> Router {
+    Router::new()
+        .route("/api/traces", post(export_spans))
+        .layer(RequestDecompressionLayer::new())
+        .with_state(queue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::thrift;
+    use axum::{body::Body, http::Request};
+    use faze::Storage;
+    use tower::ServiceExt;
+
+    fn jaeger_request(content_type: &str, body: impl Into<Body>) -> Request<Body> {
+        Request::builder()
+            .uri("/api/traces")
+            .method("POST")
+            .header("content-type", content_type)
+            .body(body.into())
+            .unwrap()
+    }
+
+    /// Thrift batch holding a single span with only its IDs and operation name set
+    fn thrift_batch() -> Vec<u8> {
+        let mut buf = vec![thrift::LIST, 0, 2, thrift::STRUCT, 0, 0, 0, 1];
+        for (id, value) in [(1i16, 1i64), (2, 0), (3, 2), (4, 0)] {
+            buf.push(thrift::I64);
+            buf.extend(id.to_be_bytes());
+            buf.extend(value.to_be_bytes());
+        }
+        buf.extend([thrift::STRING, 0, 5, 0, 0, 0, 3]);
+        buf.extend(b"GET");
+        buf.extend([thrift::STOP, thrift::STOP]);
+        buf
+    }
+
+    #[tokio::test]
+    async fn test_export_jaeger_thrift() {
+        let storage = Storage::new_in_memory().unwrap();
+        let app = create_router(IngestQueue::spawn(storage.clone(), 4));
+
+        let response = app
+            .oneshot(jaeger_request("application/x-thrift", thrift_batch()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let trace = storage
+            .get_trace_by_id("00000000000000000000000000000001")
+            .unwrap();
+        assert_eq!(trace.spans.len(), 1);
+        assert_eq!(trace.spans[0].name, "GET");
+    }
+
+    #[tokio::test]
+    async fn test_export_jaeger_invalid_thrift() {
+        let storage = Storage::new_in_memory().unwrap();
+        let app = create_router(IngestQueue::spawn(storage.clone(), 4));
+
+        let response = app
+            .oneshot(jaeger_request("application/x-thrift", vec![thrift::LIST]))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(storage.count_spans().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_export_jaeger_compact_thrift_unsupported() {
+        let storage = Storage::new_in_memory().unwrap();
+        let app = create_router(IngestQueue::spawn(storage, 4));
+
+        let response = app
+            .oneshot(jaeger_request(
+                "application/vnd.apache.thrift.compact",
+                thrift_batch(),
+            ))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+}
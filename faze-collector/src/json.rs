@@ -0,0 +1,392 @@
+//! Serde helpers implementing the OTLP/JSON encoding rules
+//!
+//! These are wired into the generated protobuf types from `build.rs`. OTLP/JSON
+//! mostly follows the proto3 JSON mapping, with the exception that trace and
+//! span IDs are hex strings instead of base64.
+
+use crate::proto::opentelemetry::proto::{
+    logs::v1::SeverityNumber,
+    metrics::v1::AggregationTemporality,
+    trace::v1::{SpanKind, StatusCode},
+};
+use serde::{Deserialize, Deserializer, Serializer, de};
+use std::fmt;
+
+/// Hex-encoded byte fields (trace and span IDs)
+pub mod hex_bytes {
+    use super::*;
+    use crate::convert::bytes_to_hex;
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&bytes_to_hex(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let hex = Option::<String>::deserialize(deserializer)?.unwrap_or_default();
+        hex_to_bytes(&hex).ok_or_else(|| de::Error::custom(format!("invalid hex id: {hex}")))
+    }
+
+    fn hex_to_bytes(hex: &str) -> Option<Vec<u8>> {
+        if !hex.len().is_multiple_of(2) {
+            return None;
+        }
+
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+            .collect()
+    }
+}
+
+/// Base64-encoded byte fields
+pub mod base64_bytes {
+    use super::*;
+    use base64::{Engine, engine::general_purpose::STANDARD};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(encoded).map_err(de::Error::custom)
+    }
+}
+
+/// `fixed64`/`uint64` fields, written as strings and read from strings or numbers
+pub mod u64_string {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        deserializer.deserialize_any(IntVisitor::<u64>::new())
+    }
+}
+
+/// Repeated `fixed64`/`uint64` fields
+pub mod u64_list {
+    use super::*;
+    use serde::ser::SerializeSeq;
+
+    pub fn serialize<S: Serializer>(values: &[u64], serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(values.len()))?;
+        for value in values {
+            seq.serialize_element(&value.to_string())?;
+        }
+        seq.end()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u64>, D::Error> {
+        #[derive(Deserialize)]
+        struct Item(#[serde(with = "super::u64_string")] u64);
+
+        let items = Option::<Vec<Item>>::deserialize(deserializer)?.unwrap_or_default();
+        Ok(items.into_iter().map(|Item(value)| value).collect())
+    }
+}
+
+/// `int64` fields, written as strings and read from strings or numbers
+pub mod i64_string {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(value: &i64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i64, D::Error> {
+        deserializer.deserialize_any(IntVisitor::<i64>::new())
+    }
+}
+
+/// Accepts an integer encoded either as a JSON number or as a decimal string
+struct IntVisitor<T>(std::marker::PhantomData<T>);
+
+impl<T> IntVisitor<T> {
+    fn new() -> Self {
+        Self(std::marker::PhantomData)
+    }
+}
+
+impl<T> de::Visitor<'_> for IntVisitor<T>
+where
+    T: TryFrom<u64> + TryFrom<i64> + std::str::FromStr,
+{
+    type Value = T;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an integer or a string containing an integer")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<T, E> {
+        T::try_from(value).map_err(|_| E::custom(format!("integer out of range: {value}")))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<T, E> {
+        T::try_from(value).map_err(|_| E::custom(format!("integer out of range: {value}")))
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<T, E> {
+        if value.fract() == 0.0 {
+            self.visit_i64(value as i64)
+        } else {
+            Err(E::custom(format!("expected an integer, got {value}")))
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        value
+            .parse()
+            .map_err(|_| E::custom(format!("invalid integer: {value}")))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<T, E> {
+        self.visit_u64(0)
+    }
+}
+
+/// Accepts an enum encoded either as its integer value or its proto name
+struct EnumVisitor(fn(&str) -> Option<i32>);
+
+impl de::Visitor<'_> for EnumVisitor {
+    type Value = i32;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an enum integer value or name")
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<i32, E> {
+        i32::try_from(value).map_err(|_| E::custom(format!("enum value out of range: {value}")))
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<i32, E> {
+        i32::try_from(value).map_err(|_| E::custom(format!("enum value out of range: {value}")))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<i32, E> {
+        (self.0)(value).ok_or_else(|| E::custom(format!("unknown enum value: {value}")))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<i32, E> {
+        Ok(0)
+    }
+}
+
+macro_rules! enum_deserializer {
+    ($name:ident, $ty:ty) => {
+        #[doc = concat!("Deserialize a [`", stringify!($ty), "`] from its integer value or name")]
+        pub fn $name<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+            deserializer.deserialize_any(EnumVisitor(|name| {
+                <$ty>::from_str_name(name).map(|value| value as i32)
+            }))
+        }
+    };
+}
+
+enum_deserializer!(span_kind, SpanKind);
+enum_deserializer!(status_code, StatusCode);
+enum_deserializer!(severity_number, SeverityNumber);
+enum_deserializer!(aggregation_temporality, AggregationTemporality);
+
+#[cfg(test)]
+mod tests {
+    use crate::proto::opentelemetry::proto::{
+        collector::{
+            logs::v1::ExportLogsServiceRequest, metrics::v1::ExportMetricsServiceRequest,
+            trace::v1::ExportTraceServiceRequest,
+        },
+        common::v1::any_value,
+        metrics::v1::{metric, number_data_point},
+        trace::v1::SpanKind,
+    };
+
+    #[test]
+    fn test_decode_trace_request_json() {
+        let json = r#"{
+            "resourceSpans": [{
+                "resource": {
+                    "attributes": [
+                        {"key": "service.name", "value": {"stringValue": "json-service"}}
+                    ]
+                },
+                "scopeSpans": [{
+                    "spans": [{
+                        "traceId": "5b8efff798038103d269b633813fc60c",
+                        "spanId": "eee19b7ec3c1b174",
+                        "parentSpanId": "",
+                        "name": "GET /users",
+                        "kind": "SPAN_KIND_SERVER",
+                        "startTimeUnixNano": "1544712660000000000",
+                        "endTimeUnixNano": 1544712661000000000,
+                        "attributes": [
+                            {"key": "http.status_code", "value": {"intValue": "200"}},
+                            {"key": "retry", "value": {"intValue": 3}},
+                            {"key": "payload", "value": {"bytesValue": "AQID"}}
+                        ],
+                        "events": [{
+                            "timeUnixNano": "1544712660500000000",
+                            "name": "exception",
+                            "attributes": [
+                                {"key": "exception.type", "value": {"stringValue": "IOError"}}
+                            ]
+                        }],
+                        "status": {"code": 2, "message": "boom"}
+                    }]
+                }]
+            }]
+        }"#;
+
+        let request: ExportTraceServiceRequest = serde_json::from_str(json).unwrap();
+        let span = &request.resource_spans[0].scope_spans[0].spans[0];
+
+        assert_eq!(span.trace_id[..4], [0x5b, 0x8e, 0xff, 0xf7]);
+        assert_eq!(span.span_id.len(), 8);
+        assert!(span.parent_span_id.is_empty());
+        assert_eq!(span.kind, SpanKind::Server as i32);
+        assert_eq!(span.start_time_unix_nano, 1_544_712_660_000_000_000);
+        assert_eq!(span.end_time_unix_nano, 1_544_712_661_000_000_000);
+        assert_eq!(span.status.as_ref().unwrap().code, 2);
+        assert_eq!(span.events[0].name, "exception");
+        assert_eq!(span.events[0].time_unix_nano, 1_544_712_660_500_000_000);
+
+        let values: Vec<_> = span
+            .attributes
+            .iter()
+            .map(|kv| kv.value.as_ref().unwrap().value.clone().unwrap())
+            .collect();
+        assert_eq!(values[0], any_value::Value::IntValue(200));
+        assert_eq!(values[1], any_value::Value::IntValue(3));
+        assert_eq!(values[2], any_value::Value::BytesValue(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_decode_span_events_and_links_json() {
+        let json = r#"{
+            "resourceSpans": [{
+                "scopeSpans": [{
+                    "spans": [{
+                        "traceId": "5b8efff798038103d269b633813fc60c",
+                        "spanId": "eee19b7ec3c1b174",
+                        "name": "consume",
+                        "startTimeUnixNano": "1544712660000000000",
+                        "endTimeUnixNano": "1544712661000000000",
+                        "events": [{
+                            "timeUnixNano": "1544712660500000000",
+                            "name": "exception"
+                        }],
+                        "links": [{
+                            "traceId": "0af7651916cd43dd8448eb211c80319c",
+                            "spanId": "b7ad6b7169203331"
+                        }]
+                    }]
+                }]
+            }]
+        }"#;
+
+        let request: ExportTraceServiceRequest = serde_json::from_str(json).unwrap();
+        let span = &request.resource_spans[0].scope_spans[0].spans[0];
+
+        assert_eq!(span.events[0].name, "exception");
+        assert_eq!(span.events[0].time_unix_nano, 1_544_712_660_500_000_000);
+        assert_eq!(span.links[0].trace_id[..4], [0x0a, 0xf7, 0x65, 0x19]);
+        assert_eq!(span.links[0].span_id.len(), 8);
+    }
+
+    #[test]
+    fn test_decode_logs_request_json() {
+        let json = r#"{
+            "resourceLogs": [{
+                "scopeLogs": [{
+                    "logRecords": [{
+                        "timeUnixNano": "1544712660300000000",
+                        "severityNumber": "SEVERITY_NUMBER_WARN",
+                        "severityText": "WARN",
+                        "body": {"stringValue": "disk almost full"},
+                        "traceId": "5B8EFFF798038103D269B633813FC60C"
+                    }]
+                }]
+            }]
+        }"#;
+
+        let request: ExportLogsServiceRequest = serde_json::from_str(json).unwrap();
+        let log = &request.resource_logs[0].scope_logs[0].log_records[0];
+
+        assert_eq!(log.severity_number, 13);
+        assert_eq!(log.time_unix_nano, 1_544_712_660_300_000_000);
+        assert_eq!(log.trace_id.len(), 16);
+        assert!(log.span_id.is_empty());
+    }
+
+    #[test]
+    fn test_decode_metrics_request_json() {
+        let json = r#"{
+            "resourceMetrics": [{
+                "scopeMetrics": [{
+                    "metrics": [{
+                        "name": "requests",
+                        "sum": {
+                            "aggregationTemporality": "AGGREGATION_TEMPORALITY_CUMULATIVE",
+                            "isMonotonic": true,
+                            "dataPoints": [{"timeUnixNano": "1000", "asInt": "42"}]
+                        }
+                    }, {
+                        "name": "latency",
+                        "histogram": {
+                            "aggregationTemporality": 1,
+                            "dataPoints": [{
+                                "count": "3",
+                                "bucketCounts": ["1", 2],
+                                "explicitBounds": [10.0]
+                            }]
+                        }
+                    }]
+                }]
+            }]
+        }"#;
+
+        let request: ExportMetricsServiceRequest = serde_json::from_str(json).unwrap();
+        let metrics = &request.resource_metrics[0].scope_metrics[0].metrics;
+
+        let Some(metric::Data::Sum(sum)) = &metrics[0].data else {
+            panic!("Expected sum data");
+        };
+        assert_eq!(sum.aggregation_temporality, 2);
+        assert_eq!(
+            sum.data_points[0].value,
+            Some(number_data_point::Value::AsInt(42))
+        );
+
+        let Some(metric::Data::Histogram(histogram)) = &metrics[1].data else {
+            panic!("Expected histogram data");
+        };
+        assert_eq!(histogram.aggregation_temporality, 1);
+        assert_eq!(histogram.data_points[0].count, 3);
+        assert_eq!(histogram.data_points[0].bucket_counts, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_decode_invalid_hex_id() {
+        let json = r#"{"resourceSpans": [{"scopeSpans": [{"spans": [{"traceId": "xyz"}]}]}]}"#;
+        let result: Result<ExportTraceServiceRequest, _> = serde_json::from_str(json);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_encode_trace_request_json() {
+        let json = r#"{"resourceSpans": [{"scopeSpans": [{"spans": [{
+            "traceId": "5b8efff798038103d269b633813fc60c",
+            "spanId": "eee19b7ec3c1b174",
+            "startTimeUnixNano": "1544712660000000000"
+        }]}]}]}"#;
+
+        let request: ExportTraceServiceRequest = serde_json::from_str(json).unwrap();
+        let value = serde_json::to_value(&request).unwrap();
+        let span = &value["resourceSpans"][0]["scopeSpans"][0]["spans"][0];
+
+        assert_eq!(span["traceId"], "5b8efff798038103d269b633813fc60c");
+        assert_eq!(span["spanId"], "eee19b7ec3c1b174");
+        assert_eq!(span["startTimeUnixNano"], "1544712660000000000");
+    }
+}
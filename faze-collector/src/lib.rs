@@ -1,8 +1,28 @@
 pub mod convert;
+pub mod fluent;
 pub mod grpc;
 pub mod http;
+pub mod ingest;
+pub mod jaeger;
+mod json;
+pub mod loki;
+pub mod prometheus;
+pub mod scrape;
+pub mod statsd;
+pub mod syslog;
+pub mod tail;
+mod thrift;
+pub mod zipkin;
 
 pub mod proto {
+    pub mod jaeger {
+        pub mod api_v2 {
+            tonic::include_proto!("jaeger.api_v2");
+        }
+    }
+    pub mod loki {
+        tonic::include_proto!("logproto");
+    }
     pub mod opentelemetry {
         pub mod proto {
             pub mod collector {
@@ -49,7 +69,19 @@ pub mod proto {
             }
         }
     }
+    pub mod prometheus {
+        tonic::include_proto!("prometheus");
+    }
 }
 
 pub use grpc::{logs, metrics, traces};
-pub use http::create_router;
+pub use http::{create_router, create_router_with_queue};
+pub use ingest::{DEFAULT_QUEUE_DEPTH, IngestQueue};
+
+/// Current time in nanoseconds since the Unix epoch, for timestamping what receivers observe
+pub fn now_unix_nano() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as i64
+}
@@ -0,0 +1,167 @@
+use crate::{
+    convert::loki::{LokiPushRequest, convert_json_push_request, convert_push_request},
+    ingest::{IngestQueue, log_failures},
+    proto::loki::PushRequest,
+};
+use axum::{
+    Router,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use prost::Message;
+use tower_http::decompression::RequestDecompressionLayer;
+use tracing::error;
+
+fn bad_request(what: &str, e: impl std::fmt::Display) -> Response {
+    error!("Failed to decode Loki {}: {}", what, e);
+    (StatusCode::BAD_REQUEST, e.to_string()).into_response()
+}
+
+/// HTTP handler for Loki pushes
+///
+/// JSON bodies may be gzip-compressed; protobuf bodies are always snappy block-compressed.
+async fn push(State(queue): State<IngestQueue>, headers: HeaderMap, body: Bytes) -> Response {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("application/x-protobuf");
+
+    let logs = if content_type.starts_with("application/json") {
+        let request: LokiPushRequest = match serde_json::from_slice(&body) {
+            Ok(request) => request,
+            Err(e) => return bad_request("JSON push", e),
+        };
+        convert_json_push_request(request)
+    } else if content_type.starts_with("application/x-protobuf") {
+        let decompressed = match snap::raw::Decoder::new().decompress_vec(&body) {
+            Ok(decompressed) => decompressed,
+            Err(e) => return bad_request("protobuf push", e),
+        };
+        match PushRequest::decode(decompressed.as_slice()) {
+            Ok(request) => convert_push_request(request),
+            Err(e) => return bad_request("protobuf push", e),
+        }
+    } else {
+        return StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response();
+    };
+    let logs = match logs {
+        Ok(logs) => logs,
+        Err(e) => return bad_request("push", e),
+    };
+
+    match queue.submit_logs(logs).await {
+        Ok((logs, outcome)) => {
+            log_failures("log", &logs, &outcome, |log| &log.body);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(e) => {
+            error!("Failed to write log batch: {}", e);
+            e.into_response()
+        }
+    }
+}
+
+/// Create HTTP router for the Loki push receiver
+pub fn create_router(queue: IngestQueue) -> Router {
+    Router::new()
+        .route("/loki/api/v1/push", post(push))
+        // Snappy is handled by the handler, so it's passed through rather than rejected
+        .layer(RequestDecompressionLayer::new().pass_through_unaccepted(true))
+        .with_state(queue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::loki::{EntryAdapter, StreamAdapter};
+    use axum::{body::Body, http::Request};
+    use faze::Storage;
+    use faze::models::SeverityLevel;
+    use prost_types::Timestamp;
+    use std::io::Write;
+    use tower::ServiceExt;
+
+    fn push_request(content_type: &str, body: impl Into<Body>) -> Request<Body> {
+        Request::builder()
+            .uri("/loki/api/v1/push")
+            .method("POST")
+            .header("content-type", content_type)
+            .body(body.into())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_push_protobuf() {
+        let storage = Storage::new_in_memory().unwrap();
+        let app = create_router(IngestQueue::spawn(storage.clone(), 4));
+        let request = PushRequest {
+            streams: vec![StreamAdapter {
+                labels: r#"{job="api", level="error"}"#.to_string(),
+                entries: vec![EntryAdapter {
+                    timestamp: Some(Timestamp {
+                        seconds: 1_700_000_000,
+                        nanos: 0,
+                    }),
+                    line: "connection refused".to_string(),
+                    structured_metadata: vec![],
+                }],
+                hash: 0,
+            }],
+        };
+        let body = snap::raw::Encoder::new()
+            .compress_vec(&request.encode_to_vec())
+            .unwrap();
+
+        let response = app
+            .oneshot(push_request("application/x-protobuf", body))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        let logs = storage.list_logs(None, Some(10)).unwrap();
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].severity_level, SeverityLevel::Error);
+        assert_eq!(logs[0].service_name.as_deref(), Some("api"));
+    }
+
+    #[tokio::test]
+    async fn test_push_gzip_json() {
+        let storage = Storage::new_in_memory().unwrap();
+        let app = create_router(IngestQueue::spawn(storage.clone(), 4));
+        let json = r#"{"streams": [{"stream": {"job": "api"}, "values": [["1700000000000000000", "hello"]]}]}"#;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(json.as_bytes()).unwrap();
+
+        let mut request = push_request("application/json", encoder.finish().unwrap());
+        request
+            .headers_mut()
+            .insert("content-encoding", "gzip".parse().unwrap());
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(storage.count_logs().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_push_invalid_body() {
+        let storage = Storage::new_in_memory().unwrap();
+        let app = create_router(IngestQueue::spawn(storage.clone(), 4));
+
+        let response = app
+            .clone()
+            .oneshot(push_request("application/json", "{not json"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .oneshot(push_request("application/x-protobuf", "not snappy"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(storage.count_logs().unwrap(), 0);
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    convert::prometheus::convert_write_request,
+    ingest::{IngestQueue, log_failures},
+    proto::prometheus::WriteRequest,
+};
+use axum::{
+    Router,
+    body::Bytes,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use prost::Message;
+use tracing::error;
+
+/// HTTP handler for Prometheus remote-write requests
+///
+/// Bodies are always snappy block-compressed protobuf, so the usual decompression layer is not
+/// used here.
+async fn remote_write(State(queue): State<IngestQueue>, body: Bytes) -> Response {
+    let decompressed = match snap::raw::Decoder::new().decompress_vec(&body) {
+        Ok(decompressed) => decompressed,
+        Err(e) => {
+            error!("Failed to decompress remote-write request: {}", e);
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    };
+
+    let request = match WriteRequest::decode(decompressed.as_slice()) {
+        Ok(request) => request,
+        Err(e) => {
+            error!("Failed to decode remote-write request: {}", e);
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    };
+
+    match queue.submit_metrics(convert_write_request(&request)).await {
+        Ok((metrics, outcome)) => {
+            log_failures("metric", &metrics, &outcome, |metric| &metric.name);
+            StatusCode::NO_CONTENT.into_response()
+        }
+        Err(e) => {
+            error!("Failed to write metric batch: {}", e);
+            e.into_response()
+        }
+    }
+}
+
+/// Create HTTP router for the Prometheus remote-write receiver
+pub fn create_router(queue: IngestQueue) -> Router {
+    Router::new()
+        .route("/api/v1/write", post(remote_write))
+        .with_state(queue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::proto::prometheus::{Label, Sample, TimeSeries};
+    use axum::{body::Body, http::Request};
+    use faze::Storage;
+    use tower::ServiceExt;
+
+    fn remote_write_request(body: impl Into<Body>) -> Request<Body> {
+        Request::builder()
+            .uri("/api/v1/write")
+            .method("POST")
+            .header("content-type", "application/x-protobuf")
+            .header("content-encoding", "snappy")
+            .header("x-prometheus-remote-write-version", "0.1.0")
+            .body(body.into())
+            .unwrap()
+    }
+
+    fn write_request() -> WriteRequest {
+        WriteRequest {
+            timeseries: vec![TimeSeries {
+                labels: vec![
+                    Label {
+                        name: "__name__".to_string(),
+                        value: "up".to_string(),
+                    },
+                    Label {
+                        name: "job".to_string(),
+                        value: "api".to_string(),
+                    },
+                ],
+                samples: vec![Sample {
+                    value: 1.0,
+                    timestamp: 1_700_000_000_000,
+                }],
+            }],
+            metadata: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_write() {
+        let storage = Storage::new_in_memory().unwrap();
+        let app = create_router(IngestQueue::spawn(storage.clone(), 4));
+        let body = snap::raw::Encoder::new()
+            .compress_vec(&write_request().encode_to_vec())
+            .unwrap();
+
+        let response = app.oneshot(remote_write_request(body)).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(storage.count_metrics().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_remote_write_uncompressed_body() {
+        let storage = Storage::new_in_memory().unwrap();
+        let app = create_router(IngestQueue::spawn(storage.clone(), 4));
+
+        let response = app
+            .oneshot(remote_write_request(write_request().encode_to_vec()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(storage.count_metrics().unwrap(), 0);
+    }
+}
@@ -0,0 +1,297 @@
+use crate::{
+    convert::exposition::{ExpositionError, parse_exposition},
+    ingest::{IngestError, IngestQueue, log_failures},
+    now_unix_nano,
+};
+use axum::body::Bytes;
+use axum::http::{Request, StatusCode, Uri, header};
+use faze::models::{Metric, Resource};
+use http_body_util::{BodyExt, Empty, Limited};
+use hyper_util::{
+    client::legacy::{Client, connect::HttpConnector},
+    rt::TokioExecutor,
+};
+use std::{fmt, str::FromStr, time::Duration};
+use thiserror::Error;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Longest a single scrape may take, capped at the target's interval
+const SCRAPE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest response body accepted from a target
+const MAX_BODY_SIZE: usize = 16 * 1024 * 1024;
+
+const ACCEPT: &str = "text/plain;version=0.0.4";
+
+#[derive(Debug, Error)]
+pub enum ScrapeError {
+    #[error("request failed: {0}")]
+    Request(#[from] hyper_util::client::legacy::Error),
+
+    #[error("target answered with {0}")]
+    Status(StatusCode),
+
+    #[error("failed to read response: {0}")]
+    Body(Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("timed out after {0:?}")]
+    Timeout(Duration),
+
+    #[error("invalid exposition format: {0}")]
+    Parse(#[from] ExpositionError),
+
+    #[error(transparent)]
+    Ingest(#[from] IngestError),
+}
+
+/// Parse an interval such as `500ms`, `15s`, `1m` or `1h`
+pub fn parse_interval(value: &str) -> Result<Duration, String> {
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+    let amount: u64 = amount
+        .parse()
+        .map_err(|_| format!("invalid interval {value:?}"))?;
+
+    let interval = match unit {
+        "ms" => Duration::from_millis(amount),
+        "s" | "" => Duration::from_secs(amount),
+        "m" => Duration::from_secs(amount * 60),
+        "h" => Duration::from_secs(amount * 3600),
+        _ => {
+            return Err(format!(
+                "invalid interval unit {unit:?} (expected ms, s, m or h)"
+            ));
+        }
+    };
+    if interval.is_zero() {
+        return Err("interval must be greater than zero".to_string());
+    }
+    Ok(interval)
+}
+
+/// A `/metrics` endpoint to scrape, written as `URL` or `URL,INTERVAL`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScrapeTarget {
+    pub url: Uri,
+    /// Scrape interval, falling back to the default given to [`spawn_scraper`]
+    pub interval: Option<Duration>,
+}
+
+impl ScrapeTarget {
+    /// Service name the target's metrics are stored under: the `host:port` being scraped,
+    /// like Prometheus' `instance` label
+    pub fn service_name(&self) -> String {
+        self.url
+            .authority()
+            .map(|authority| authority.to_string())
+            .unwrap_or_default()
+    }
+}
+
+impl FromStr for ScrapeTarget {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let (url, interval) = match value.rsplit_once(',') {
+            Some((url, interval)) => (url, Some(parse_interval(interval)?)),
+            None => (value, None),
+        };
+
+        let url: Uri = url
+            .parse()
+            .map_err(|e| format!("invalid URL {url:?}: {e}"))?;
+        if url.scheme_str() != Some("http") || url.authority().is_none() {
+            return Err(format!("scrape targets must be http:// URLs, got {url:?}"));
+        }
+
+        Ok(Self { url, interval })
+    }
+}
+
+impl fmt::Display for ScrapeTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.url)
+    }
+}
+
+/// Client used to fetch targets
+pub type ScrapeClient = Client<HttpConnector, Empty<Bytes>>;
+
+/// Create a client for scraping targets
+pub fn scrape_client() -> ScrapeClient {
+    Client::builder(TokioExecutor::new()).build_http()
+}
+
+/// Fetch a target once and parse its metrics, tagging them with the target as their service
+pub async fn scrape(
+    client: &ScrapeClient,
+    target: &ScrapeTarget,
+) -> Result<Vec<Metric>, ScrapeError> {
+    let timeout = target
+        .interval
+        .map_or(SCRAPE_TIMEOUT, |interval| interval.min(SCRAPE_TIMEOUT));
+    let scrape_time = now_unix_nano();
+
+    let request = Request::get(target.url.clone())
+        .header(header::ACCEPT, ACCEPT)
+        .body(Empty::new())
+        .expect("scrape request is valid");
+
+    let fetch = async {
+        let response = client.request(request).await?;
+        if !response.status().is_success() {
+            return Err(ScrapeError::Status(response.status()));
+        }
+        let body = Limited::new(response.into_body(), MAX_BODY_SIZE)
+            .collect()
+            .await
+            .map_err(ScrapeError::Body)?;
+        Ok(body.to_bytes())
+    };
+    let body = tokio::time::timeout(timeout, fetch)
+        .await
+        .map_err(|_| ScrapeError::Timeout(timeout))??;
+
+    let service_name = target.service_name();
+    let resource = Resource::for_service(&service_name);
+
+    let metrics = parse_exposition(&String::from_utf8_lossy(&body), scrape_time)?
+        .into_iter()
+        .map(|metric| {
+            Metric {
+                service_name: Some(service_name.clone()),
+                ..metric
+            }
+            .with_resource(resource.clone())
+        })
+        .collect();
+    Ok(metrics)
+}
+
+/// Scrape a target and write its metrics through the ingest queue
+pub async fn scrape_into(
+    client: &ScrapeClient,
+    target: &ScrapeTarget,
+    queue: &IngestQueue,
+) -> Result<(), ScrapeError> {
+    let metrics = scrape(client, target).await?;
+    let (metrics, outcome) = queue.submit_metrics(metrics).await?;
+    log_failures("metric", &metrics, &outcome, |metric| &metric.name);
+    Ok(())
+}
+
+/// Scrape a target on its interval until the returned task is aborted
+pub fn spawn_scraper(
+    target: ScrapeTarget,
+    default_interval: Duration,
+    queue: IngestQueue,
+) -> JoinHandle<()> {
+    let client = scrape_client();
+    let interval = target.interval.unwrap_or(default_interval);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            // A failed scrape is retried on the next tick, as Prometheus does
+            if let Err(e) = scrape_into(&client, &target, &queue).await {
+                warn!("Failed to scrape {}: {}", target, e);
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Router, routing::get};
+    use faze::Storage;
+    use faze::models::MetricType;
+
+    const METRICS: &str = "\
+# HELP jobs_processed_total Jobs processed.
+# TYPE jobs_processed_total counter
+jobs_processed_total{queue=\"default\"} 7
+# TYPE queue_depth gauge
+queue_depth 3
+";
+
+    async fn serve(router: Router) -> std::net::SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+        addr
+    }
+
+    #[test]
+    fn test_parse_scrape_target() {
+        let target: ScrapeTarget = "http://localhost:8080/metrics".parse().unwrap();
+        assert_eq!(target.interval, None);
+        assert_eq!(target.service_name(), "localhost:8080");
+
+        let target: ScrapeTarget = "http://127.0.0.1:9100/metrics,5s".parse().unwrap();
+        assert_eq!(target.url.path(), "/metrics");
+        assert_eq!(target.interval, Some(Duration::from_secs(5)));
+
+        assert!("localhost:8080/metrics".parse::<ScrapeTarget>().is_err());
+        assert!(
+            "https://example.com/metrics"
+                .parse::<ScrapeTarget>()
+                .is_err()
+        );
+        assert!(
+            "http://localhost/metrics,5d"
+                .parse::<ScrapeTarget>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_parse_interval() {
+        assert_eq!(parse_interval("250ms"), Ok(Duration::from_millis(250)));
+        assert_eq!(parse_interval("30"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_interval("2m"), Ok(Duration::from_secs(120)));
+        assert!(parse_interval("0s").is_err());
+        assert!(parse_interval("s").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_scrape_into_storage() {
+        let addr = serve(Router::new().route("/metrics", get(|| async { METRICS }))).await;
+        let target: ScrapeTarget = format!("http://{addr}/metrics").parse().unwrap();
+        let storage = Storage::new_in_memory().unwrap();
+        let queue = IngestQueue::spawn(storage.clone(), 4);
+
+        scrape_into(&scrape_client(), &target, &queue)
+            .await
+            .unwrap();
+
+        assert_eq!(storage.count_metrics().unwrap(), 2);
+        let metrics = scrape(&scrape_client(), &target).await.unwrap();
+        assert_eq!(metrics[0].metric_type, MetricType::Sum);
+        assert_eq!(
+            metrics[0].service_name.as_deref(),
+            Some(addr.to_string().as_str())
+        );
+        assert_eq!(
+            metrics[1].resource.as_ref().unwrap().service_name(),
+            Some(addr.to_string().as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scrape_error_status() {
+        let addr = serve(Router::new()).await;
+        let target: ScrapeTarget = format!("http://{addr}/metrics").parse().unwrap();
+
+        let result = scrape(&scrape_client(), &target).await;
+        assert!(matches!(
+            result,
+            Err(ScrapeError::Status(StatusCode::NOT_FOUND))
+        ));
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{
+    convert::statsd::{StatsdAggregator, parse_line},
+    ingest::{IngestQueue, log_failures},
+    now_unix_nano,
+};
+use std::time::Duration;
+use tokio::{net::UdpSocket, task::JoinHandle};
+use tracing::{debug, error, warn};
+
+/// Largest datagram a UDP socket can receive
+const MAX_DATAGRAM_SIZE: usize = 65_535;
+
+async fn flush(aggregator: &mut StatsdAggregator, queue: &IngestQueue) {
+    let metrics = aggregator.flush(now_unix_nano());
+    if metrics.is_empty() {
+        return;
+    }
+
+    match queue.submit_metrics(metrics).await {
+        Ok((metrics, outcome)) => {
+            log_failures("metric", &metrics, &outcome, |metric| &metric.name);
+        }
+        // StatsD has no way to push back on clients, so the interval is lost
+        Err(e) => error!("Failed to write StatsD metrics: {}", e),
+    }
+}
+
+/// Receive StatsD and DogStatsD datagrams on `socket`, writing aggregated metrics through the
+/// ingest queue every `flush_interval` until the returned task is aborted
+pub fn spawn_statsd(
+    socket: UdpSocket,
+    flush_interval: Duration,
+    queue: IngestQueue,
+) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut aggregator = StatsdAggregator::new();
+        let mut buf = vec![0u8; MAX_DATAGRAM_SIZE];
+        let mut ticker = tokio::time::interval(flush_interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick completes straight away and marks the start of the first interval
+        ticker.tick().await;
+        aggregator.flush(now_unix_nano());
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => flush(&mut aggregator, &queue).await,
+                received = socket.recv_from(&mut buf) => {
+                    let len = match received {
+                        Ok((len, _)) => len,
+                        Err(e) => {
+                            warn!("Failed to receive StatsD datagram: {}", e);
+                            continue;
+                        }
+                    };
+
+                    // Clients batch several lines into one datagram
+                    for line in String::from_utf8_lossy(&buf[..len]).lines() {
+                        match parse_line(line) {
+                            Ok(samples) => samples.into_iter().for_each(|s| aggregator.record(s)),
+                            Err(e) => debug!("Dropping StatsD line {:?}: {}", line, e),
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use faze::Storage;
+    use faze::models::MetricType;
+
+    #[tokio::test]
+    async fn test_statsd_receiver() {
+        let storage = Storage::new_in_memory().unwrap();
+        let queue = IngestQueue::spawn(storage.clone(), 4);
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        let receiver = spawn_statsd(socket, Duration::from_millis(50), queue);
+
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        client
+            .send_to(
+                b"jobs.done:1|c|#service:worker\njobs.done:2|c|#service:worker",
+                addr,
+            )
+            .await
+            .unwrap();
+        client.send_to(b"jobs.latency:12|ms", addr).await.unwrap();
+
+        let mut metrics = Vec::new();
+        for _ in 0..100 {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            metrics = storage.list_metrics(None, Some(10)).unwrap();
+            if metrics.len() == 2 {
+                break;
+            }
+        }
+        receiver.abort();
+
+        assert_eq!(metrics.len(), 2);
+        let done = metrics.iter().find(|m| m.name == "jobs.done").unwrap();
+        assert_eq!(done.metric_type, MetricType::Sum);
+        assert_eq!(done.data_points[0].value, 3.0);
+        assert_eq!(done.service_name.as_deref(), Some("worker"));
+        let latency = metrics.iter().find(|m| m.name == "jobs.latency").unwrap();
+        assert_eq!(latency.metric_type, MetricType::Histogram);
+    }
+}
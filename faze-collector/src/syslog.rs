@@ -0,0 +1,250 @@
+use crate::{
+    convert::syslog::parse_message,
+    ingest::{IngestQueue, log_failures},
+    now_unix_nano,
+};
+use faze::models::Log;
+use thiserror::Error;
+use tokio::{
+    io::AsyncReadExt,
+    net::{TcpListener, TcpStream, UdpSocket},
+    task::JoinHandle,
+};
+use tracing::{debug, error, warn};
+
+/// Largest datagram a UDP socket can receive
+const MAX_DATAGRAM_SIZE: usize = 65_535;
+
+/// Largest message accepted over TCP
+const MAX_FRAME_SIZE: usize = 1024 * 1024;
+
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Error)]
+enum ConnectionError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid octet count {0:?}")]
+    InvalidOctetCount(String),
+
+    #[error("message exceeds {MAX_FRAME_SIZE} bytes")]
+    TooLarge,
+}
+
+fn parse(message: &[u8], logs: &mut Vec<Log>) {
+    let message = String::from_utf8_lossy(message);
+    if message.trim().is_empty() {
+        return;
+    }
+    match parse_message(&message, now_unix_nano()) {
+        Ok(log) => logs.push(log),
+        Err(e) => debug!("Dropping syslog message {:?}: {}", message, e),
+    }
+}
+
+async fn submit(logs: Vec<Log>, queue: &IngestQueue) {
+    if logs.is_empty() {
+        return;
+    }
+
+    match queue.submit_logs(logs).await {
+        Ok((logs, outcome)) => log_failures("log", &logs, &outcome, |log| &log.body),
+        // Syslog has no way to push back on senders, so the messages are lost
+        Err(e) => error!("Failed to write syslog messages: {}", e),
+    }
+}
+
+/// Receive syslog messages on `socket`, one per datagram, until the returned task is aborted
+pub fn spawn_syslog_udp(socket: UdpSocket, queue: IngestQueue) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut buf = vec![0u8; MAX_DATAGRAM_SIZE];
+        loop {
+            let len = match socket.recv_from(&mut buf).await {
+                Ok((len, _)) => len,
+                Err(e) => {
+                    warn!("Failed to receive syslog datagram: {}", e);
+                    continue;
+                }
+            };
+
+            let mut logs = Vec::with_capacity(1);
+            parse(&buf[..len], &mut logs);
+            submit(logs, &queue).await;
+        }
+    })
+}
+
+/// Split the next frame off the front of `buf`, returning it and the number of bytes it took
+/// up, or `None` if the frame is incomplete. Frames are octet-counted (`LEN SP MSG`) or
+/// newline-terminated, as RFC 6587 describes
+fn next_frame(buf: &[u8]) -> Result<Option<(&[u8], usize)>, ConnectionError> {
+    if buf.first().is_some_and(u8::is_ascii_digit) {
+        let Some(space) = buf.iter().position(|b| *b == b' ') else {
+            return if buf.len() > 10 {
+                Err(ConnectionError::InvalidOctetCount(
+                    String::from_utf8_lossy(&buf[..10]).into_owned(),
+                ))
+            } else {
+                Ok(None)
+            };
+        };
+        let count = &buf[..space];
+        let len: usize = std::str::from_utf8(count)
+            .ok()
+            .and_then(|count| count.parse().ok())
+            .ok_or_else(|| {
+                ConnectionError::InvalidOctetCount(String::from_utf8_lossy(count).into_owned())
+            })?;
+        if len > MAX_FRAME_SIZE {
+            return Err(ConnectionError::TooLarge);
+        }
+        let end = space + 1 + len;
+        return Ok(buf.get(space + 1..end).map(|frame| (frame, end)));
+    }
+
+    Ok(buf
+        .iter()
+        .position(|b| *b == b'\n')
+        .map(|newline| (&buf[..newline], newline + 1)))
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    queue: &IngestQueue,
+) -> Result<(), ConnectionError> {
+    let mut buf = Vec::new();
+    let mut read_buf = vec![0u8; READ_BUFFER_SIZE];
+
+    loop {
+        let len = stream.read(&mut read_buf).await?;
+        if len == 0 {
+            // A sender may close the connection without terminating its last message
+            let mut logs = Vec::new();
+            parse(&buf, &mut logs);
+            submit(logs, queue).await;
+            return Ok(());
+        }
+        buf.extend_from_slice(&read_buf[..len]);
+
+        let mut logs = Vec::new();
+        let mut consumed = 0;
+        while let Some((frame, len)) = next_frame(&buf[consumed..])? {
+            parse(frame, &mut logs);
+            consumed += len;
+        }
+        buf.drain(..consumed);
+        submit(logs, queue).await;
+
+        if buf.len() > MAX_FRAME_SIZE {
+            return Err(ConnectionError::TooLarge);
+        }
+    }
+}
+
+/// Accept syslog connections on `listener` until the returned task is aborted
+pub fn spawn_syslog_tcp(listener: TcpListener, queue: IngestQueue) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        loop {
+            let (stream, peer) = match listener.accept().await {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    warn!("Failed to accept syslog connection: {}", e);
+                    continue;
+                }
+            };
+
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                match handle_connection(stream, &queue).await {
+                    Ok(()) => debug!("Syslog client {} disconnected", peer),
+                    Err(e) => warn!("Closing syslog connection from {}: {}", peer, e),
+                }
+            });
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use faze::Storage;
+    use std::time::Duration;
+    use tokio::io::AsyncWriteExt;
+
+    async fn wait_for_logs(storage: &Storage, count: usize) -> Vec<Log> {
+        let mut logs = Vec::new();
+        for _ in 0..100 {
+            logs = storage.list_logs(None, Some(10)).unwrap();
+            if logs.len() >= count {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        logs
+    }
+
+    #[test]
+    fn test_next_frame() {
+        assert_eq!(
+            next_frame(b"11 <13>1 - - -rest").unwrap(),
+            Some((&b"<13>1 - - -"[..], 14))
+        );
+        assert_eq!(next_frame(b"11 <13>1").unwrap(), None);
+        assert_eq!(
+            next_frame(b"<13>hello\n<13>").unwrap(),
+            Some((&b"<13>hello"[..], 10))
+        );
+        assert_eq!(next_frame(b"<13>hello").unwrap(), None);
+        assert!(next_frame(b"12x <13>").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_syslog_udp_receiver() {
+        let storage = Storage::new_in_memory().unwrap();
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        let receiver = spawn_syslog_udp(socket, IngestQueue::spawn(storage.clone(), 4));
+
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        client
+            .send_to(b"<11>1 - host daemon 42 - - disk full\n", addr)
+            .await
+            .unwrap();
+
+        let logs = wait_for_logs(&storage, 1).await;
+        receiver.abort();
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].body, "disk full");
+        assert_eq!(logs[0].service_name.as_deref(), Some("daemon"));
+        assert!(logs[0].is_error());
+    }
+
+    #[tokio::test]
+    async fn test_syslog_tcp_receiver() {
+        let storage = Storage::new_in_memory().unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let receiver = spawn_syslog_tcp(listener, IngestQueue::spawn(storage.clone(), 4));
+
+        let mut client = TcpStream::connect(addr).await.unwrap();
+        let counted = "<14>1 - host app - - - one";
+        client
+            .write_all(format!("{} {}", counted.len(), counted).as_bytes())
+            .await
+            .unwrap();
+        client
+            .write_all(b"<14>Oct  7 09:01:02 app: two\n<14>app: three")
+            .await
+            .unwrap();
+        client.shutdown().await.unwrap();
+
+        let logs = wait_for_logs(&storage, 3).await;
+        receiver.abort();
+
+        let mut bodies: Vec<&str> = logs.iter().map(|log| log.body.as_str()).collect();
+        bodies.sort_unstable();
+        assert_eq!(bodies, ["one", "three", "two"]);
+    }
+}
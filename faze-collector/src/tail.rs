@@ -0,0 +1,657 @@
+use crate::{
+    convert::tail::{LineFormat, parse_line},
+    ingest::{IngestQueue, log_failures},
+    now_unix_nano,
+};
+use faze::models::Log;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, SeekFrom},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::{
+    fs::File,
+    io::{AsyncReadExt, AsyncSeekExt},
+    task::JoinHandle,
+};
+use tracing::{debug, error, warn};
+
+/// How often files are checked for new lines, rotation and new matches
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Most bytes read from one file per poll, so a large backlog is worked through in batches
+const MAX_READ_SIZE: u64 = 1024 * 1024;
+
+/// Files to follow and how to parse their lines
+#[derive(Debug, Clone, Default)]
+pub struct TailSource {
+    /// Glob patterns of files to follow; files that start matching later are picked up too
+    pub patterns: Vec<String>,
+    pub format: LineFormat,
+    /// Where to record how far each file has been read, so a restart resumes from there rather
+    /// than from the start of every file
+    pub checkpoint_path: Option<PathBuf>,
+}
+
+/// Check that a tail pattern is a valid glob
+pub fn parse_pattern(value: &str) -> Result<String, String> {
+    glob::Pattern::new(value)
+        .map(|_| value.to_string())
+        .map_err(|e| format!("invalid pattern {value:?}: {e}"))
+}
+
+/// Read offset of one file. The device and inode tell a file apart from one rotated into its
+/// place, and find it again once it has been renamed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+struct Checkpoint {
+    #[serde(default)]
+    device: u64,
+    inode: u64,
+    offset: u64,
+}
+
+impl Checkpoint {
+    fn file_id(&self) -> FileId {
+        FileId {
+            device: self.device,
+            inode: self.inode,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Checkpoints {
+    files: BTreeMap<PathBuf, Checkpoint>,
+}
+
+impl Checkpoints {
+    fn load(path: &Path) -> Self {
+        match std::fs::read(path) {
+            Ok(contents) => serde_json::from_slice(&contents).unwrap_or_else(|e| {
+                warn!(
+                    "Ignoring invalid tail checkpoints {}: {}",
+                    path.display(),
+                    e
+                );
+                Self::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                warn!("Failed to read tail checkpoints {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Write the checkpoints through a temporary file, so a crash never leaves them half written
+    fn save(&self, path: &Path) -> io::Result<()> {
+        let temp_path = path.with_extension("tmp");
+        std::fs::write(&temp_path, serde_json::to_vec(self)?)?;
+        std::fs::rename(temp_path, path)
+    }
+}
+
+/// Identity of a file that survives it being renamed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileId {
+    device: u64,
+    inode: u64,
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &std::fs::Metadata) -> FileId {
+    use std::os::unix::fs::MetadataExt;
+    FileId {
+        device: metadata.dev(),
+        inode: metadata.ino(),
+    }
+}
+
+/// Without inodes, rotation is only noticed when a file shrinks and renamed files are read again
+#[cfg(not(unix))]
+fn file_id(_metadata: &std::fs::Metadata) -> FileId {
+    FileId {
+        device: 0,
+        inode: 0,
+    }
+}
+
+struct TailedFile {
+    file: File,
+    id: FileId,
+    /// Offset of the first line not yet stored
+    offset: u64,
+    /// Service name for lines that don't name one: the file name without its extension
+    service_name: String,
+}
+
+impl TailedFile {
+    async fn open(path: &Path, checkpoint: Option<Checkpoint>) -> io::Result<Self> {
+        let file = File::open(path).await?;
+        let metadata = file.metadata().await?;
+        let id = file_id(&metadata);
+        let offset = checkpoint
+            .filter(|checkpoint| checkpoint.file_id() == id && checkpoint.offset <= metadata.len())
+            .map_or(0, |checkpoint| checkpoint.offset);
+        let service_name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        Ok(Self {
+            file,
+            id,
+            offset,
+            service_name,
+        })
+    }
+
+    fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            device: self.id.device,
+            inode: self.id.inode,
+            offset: self.offset,
+        }
+    }
+
+    /// Read and parse the complete lines after the offset, returning them with the number of
+    /// bytes they take up. With `to_end`, a trailing line without a newline is included too
+    async fn read_lines(
+        &mut self,
+        path: &Path,
+        format: &LineFormat,
+        to_end: bool,
+    ) -> io::Result<(Vec<Log>, u64)> {
+        self.file.seek(SeekFrom::Start(self.offset)).await?;
+        let mut buf = Vec::new();
+        (&mut self.file)
+            .take(MAX_READ_SIZE)
+            .read_to_end(&mut buf)
+            .await?;
+
+        let at_end = (buf.len() as u64) < MAX_READ_SIZE;
+        let complete = match buf.iter().rposition(|b| *b == b'\n') {
+            _ if to_end && at_end => buf.len(),
+            Some(newline) => newline + 1,
+            // A line longer than a whole read can't wait for its newline
+            None if !at_end => buf.len(),
+            None => 0,
+        };
+
+        let file_path = path.display().to_string();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let logs = buf[..complete]
+            .split(|b| *b == b'\n')
+            .map(|line| String::from_utf8_lossy(line.strip_suffix(b"\r").unwrap_or(line)))
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut log = parse_line(format, &line, Some(&self.service_name), now_unix_nano());
+                log.attributes.insert("log.file.path", file_path.clone());
+                log.attributes.insert("log.file.name", file_name.clone());
+                log
+            })
+            .collect();
+        Ok((logs, complete as u64))
+    }
+}
+
+struct Tailer {
+    source: TailSource,
+    files: HashMap<PathBuf, TailedFile>,
+    checkpoints: Checkpoints,
+    /// Whether the checkpoints changed since they were last saved
+    checkpoints_changed: bool,
+    queue: IngestQueue,
+}
+
+impl Tailer {
+    /// Files currently matching the patterns
+    fn scan(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+        for pattern in &self.source.patterns {
+            let entries = match glob::glob(pattern) {
+                Ok(entries) => entries,
+                Err(e) => {
+                    warn!("Invalid tail pattern {:?}: {}", pattern, e);
+                    continue;
+                }
+            };
+            for path in entries.flatten() {
+                if path.is_file()
+                    && let Ok(path) = std::path::absolute(&path)
+                    && !paths.contains(&path)
+                {
+                    paths.push(path);
+                }
+            }
+        }
+        paths
+    }
+
+    /// Store logs, returning whether they were accepted
+    async fn submit(&self, logs: Vec<Log>) -> bool {
+        if logs.is_empty() {
+            return true;
+        }
+
+        match self.queue.submit_logs(logs).await {
+            Ok((logs, outcome)) => {
+                log_failures("log", &logs, &outcome, |log| &log.body);
+                true
+            }
+            Err(e) => {
+                error!("Failed to write tailed logs: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Read a file's new lines, moving its offset past them once stored and returning how many
+    /// bytes that was. Lines that fail to be stored are read again on the next poll
+    async fn read(&mut self, path: &Path, to_end: bool) -> u64 {
+        let Some(tailed) = self.files.get_mut(path) else {
+            return 0;
+        };
+        let (logs, len) = match tailed.read_lines(path, &self.source.format, to_end).await {
+            Ok(read) => read,
+            Err(e) => {
+                warn!("Failed to read {}: {}", path.display(), e);
+                return 0;
+            }
+        };
+        if len == 0 || !self.submit(logs).await {
+            return 0;
+        }
+
+        let tailed = self.files.get_mut(path).expect("file is still tailed");
+        tailed.offset += len;
+        self.checkpoints_changed = true;
+        self.checkpoints
+            .files
+            .insert(path.to_path_buf(), tailed.checkpoint());
+        len
+    }
+
+    /// Read the rest of a file that is about to stop being followed, however many reads it takes
+    async fn finish(&mut self, path: &Path) {
+        while self.read(path, true).await > 0 {}
+
+        if let Some(tailed) = self.files.get_mut(path)
+            && let Ok(metadata) = tailed.file.metadata().await
+            && metadata.len() > tailed.offset
+        {
+            warn!(
+                "Stopped following {} with {} bytes unread",
+                path.display(),
+                metadata.len() - tailed.offset
+            );
+        }
+    }
+
+    async fn open(&mut self, path: PathBuf, checkpoint: Option<Checkpoint>) {
+        match TailedFile::open(&path, checkpoint).await {
+            Ok(tailed) => {
+                debug!("Tailing {} from offset {}", path.display(), tailed.offset);
+                self.checkpoints_changed = true;
+                self.checkpoints
+                    .files
+                    .insert(path.clone(), tailed.checkpoint());
+                self.files.insert(path, tailed);
+            }
+            Err(e) => warn!("Failed to open {}: {}", path.display(), e),
+        }
+    }
+
+    /// Carry on following a tailed file that was renamed to `path`, as by rotation to a name
+    /// that still matches, returning whether there was one
+    fn follow_rename(&mut self, path: &Path, id: FileId) -> bool {
+        if cfg!(not(unix)) {
+            return false;
+        }
+        let Some(old_path) = self
+            .files
+            .iter()
+            .find(|(_, tailed)| tailed.id == id)
+            .map(|(old_path, _)| old_path.clone())
+        else {
+            return false;
+        };
+
+        debug!("{} was renamed to {}", old_path.display(), path.display());
+        let tailed = self.files.remove(&old_path).expect("file is tailed");
+        self.checkpoints.files.remove(&old_path);
+        self.checkpoints_changed = true;
+        self.checkpoints
+            .files
+            .insert(path.to_path_buf(), tailed.checkpoint());
+        self.files.insert(path.to_path_buf(), tailed);
+        true
+    }
+
+    /// Checkpoint of the file at `path`, which may have been recorded under an earlier name
+    fn find_checkpoint(&self, path: &Path, id: FileId) -> Option<Checkpoint> {
+        if cfg!(not(unix)) {
+            return self.checkpoints.files.get(path).copied();
+        }
+        self.checkpoints
+            .files
+            .values()
+            .find(|checkpoint| checkpoint.file_id() == id)
+            .copied()
+    }
+
+    async fn poll(&mut self) {
+        let mut new_files = Vec::new();
+        for path in self.scan() {
+            if self.files.contains_key(&path) {
+                continue;
+            }
+            match tokio::fs::metadata(&path).await {
+                Ok(metadata) => new_files.push((path, file_id(&metadata))),
+                Err(e) => warn!("Failed to open {}: {}", path.display(), e),
+            }
+        }
+        // Every checkpoint is looked up before any file is opened, since opening one replaces
+        // the checkpoint under its path, which may be that of a file renamed since
+        let mut to_open = Vec::new();
+        for (path, id) in new_files {
+            if !self.follow_rename(&path, id) {
+                let checkpoint = self.find_checkpoint(&path, id);
+                to_open.push((path, checkpoint));
+            }
+        }
+        for (path, checkpoint) in to_open {
+            self.open(path, checkpoint).await;
+        }
+
+        let paths: Vec<PathBuf> = self.files.keys().cloned().collect();
+        for path in paths {
+            self.read(&path, false).await;
+
+            let offset = self.files[&path].offset;
+            match tokio::fs::metadata(&path).await {
+                // Rotated: finish the old file, whose handle is still open, then start the new one
+                Ok(metadata) if file_id(&metadata) != self.files[&path].id => {
+                    self.finish(&path).await;
+                    self.open(path, None).await;
+                }
+                // Truncated in place, as by copytruncate
+                Ok(metadata) if metadata.len() < offset => {
+                    debug!("{} was truncated, reading from the start", path.display());
+                    self.files.get_mut(&path).expect("file is tailed").offset = 0;
+                }
+                Ok(_) => {}
+                Err(_) => {
+                    self.finish(&path).await;
+                    self.files.remove(&path);
+                    self.checkpoints.files.remove(&path);
+                    self.checkpoints_changed = true;
+                }
+            }
+        }
+
+        if !self.checkpoints_changed {
+            return;
+        }
+        self.checkpoints_changed = false;
+        if let Some(checkpoint_path) = &self.source.checkpoint_path
+            && let Err(e) = self.checkpoints.save(checkpoint_path)
+        {
+            warn!(
+                "Failed to save tail checkpoints {}: {}",
+                checkpoint_path.display(),
+                e
+            );
+        }
+    }
+}
+
+/// Follow the files matching `source`'s patterns, writing each new line as a log through the
+/// ingest queue until the returned task is aborted. Files are read from their checkpoint, or
+/// from the start if they have none
+pub fn spawn_tail(source: TailSource, queue: IngestQueue) -> JoinHandle<()> {
+    let checkpoints = source
+        .checkpoint_path
+        .as_deref()
+        .map(Checkpoints::load)
+        .unwrap_or_default();
+    let mut tailer = Tailer {
+        source,
+        files: HashMap::new(),
+        checkpoints,
+        checkpoints_changed: false,
+        queue,
+    };
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(POLL_INTERVAL);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            tailer.poll().await;
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use faze::Storage;
+    use faze::models::SeverityLevel;
+    use std::io::Write;
+
+    async fn wait_for_logs(storage: &Storage, count: usize) -> Vec<Log> {
+        let mut logs = Vec::new();
+        for _ in 0..100 {
+            logs = storage.list_logs(None, Some(100)).unwrap();
+            if logs.len() >= count {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        logs
+    }
+
+    fn append(path: &Path, contents: &str) {
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .unwrap()
+            .write_all(contents.as_bytes())
+            .unwrap();
+    }
+
+    fn source(dir: &Path) -> TailSource {
+        TailSource {
+            patterns: vec![dir.join("*.log").display().to_string()],
+            format: LineFormat::Auto,
+            checkpoint_path: Some(dir.join("checkpoints.json")),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tail_follows_appends_and_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("api.log");
+        append(&path, "{\"level\":\"info\",\"msg\":\"one\"}\n");
+
+        let storage = Storage::new_in_memory().unwrap();
+        let tail = spawn_tail(source(dir.path()), IngestQueue::spawn(storage.clone(), 4));
+        let logs = wait_for_logs(&storage, 1).await;
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].body, "one");
+        assert_eq!(logs[0].service_name.as_deref(), Some("api"));
+        assert_eq!(logs[0].severity_level, SeverityLevel::Info);
+
+        // A partial line waits for its newline
+        append(&path, "level=error msg=two");
+        tokio::time::sleep(POLL_INTERVAL * 2).await;
+        assert_eq!(storage.count_logs().unwrap(), 1);
+        append(&path, "\n");
+        wait_for_logs(&storage, 2).await;
+
+        // Rotate by renaming, then keep writing to the new file
+        append(&path, "three\n");
+        std::fs::rename(&path, dir.path().join("api.log.1")).unwrap();
+        append(&path, "four\n");
+        let logs = wait_for_logs(&storage, 4).await;
+        tail.abort();
+
+        let mut bodies: Vec<&str> = logs.iter().map(|log| log.body.as_str()).collect();
+        bodies.sort_unstable();
+        assert_eq!(bodies, ["four", "one", "three", "two"]);
+        assert_eq!(
+            logs.iter()
+                .find(|log| log.body == "two")
+                .unwrap()
+                .severity_level,
+            SeverityLevel::Error
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tail_rotation_finishes_large_backlog() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("batch.log");
+        append(&path, "first\n");
+
+        let storage = Storage::new_in_memory().unwrap();
+        let tail = spawn_tail(source(dir.path()), IngestQueue::spawn(storage.clone(), 4));
+        wait_for_logs(&storage, 1).await;
+
+        // Several reads' worth of lines are still unread when the file is rotated
+        let lines = 60_000;
+        let backlog: String = (0..lines)
+            .map(|i| format!("line {:06} {}\n", i, "x".repeat(40)))
+            .collect();
+        assert!(backlog.len() as u64 > 2 * MAX_READ_SIZE);
+        append(&path, &backlog);
+        std::fs::rename(&path, dir.path().join("batch.log.1")).unwrap();
+        append(&path, "after\n");
+
+        for _ in 0..200 {
+            if storage.count_logs().unwrap() >= lines + 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        tokio::time::sleep(POLL_INTERVAL * 2).await;
+        tail.abort();
+
+        // Every line arrives whole, once
+        assert_eq!(storage.count_logs().unwrap(), lines + 2);
+    }
+
+    #[tokio::test]
+    async fn test_tail_resumes_from_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("worker.log");
+        append(&path, "first\n");
+
+        let storage = Storage::new_in_memory().unwrap();
+        let tail = spawn_tail(source(dir.path()), IngestQueue::spawn(storage.clone(), 4));
+        wait_for_logs(&storage, 1).await;
+        tail.abort();
+
+        append(&path, "second\n");
+        let storage = Storage::new_in_memory().unwrap();
+        let tail = spawn_tail(source(dir.path()), IngestQueue::spawn(storage.clone(), 4));
+        let logs = wait_for_logs(&storage, 1).await;
+        tokio::time::sleep(POLL_INTERVAL * 2).await;
+        tail.abort();
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].body, "second");
+        assert_eq!(storage.count_logs().unwrap(), 1);
+    }
+
+    /// Source following `app.log` and the files it is rotated to
+    fn rotated_source(dir: &Path) -> TailSource {
+        TailSource {
+            patterns: vec![dir.join("app.log*").display().to_string()],
+            ..source(dir)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tail_rotation_to_matching_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "one\n");
+
+        let storage = Storage::new_in_memory().unwrap();
+        let tail = spawn_tail(
+            rotated_source(dir.path()),
+            IngestQueue::spawn(storage.clone(), 4),
+        );
+        wait_for_logs(&storage, 1).await;
+
+        append(&path, "two\n");
+        std::fs::rename(&path, dir.path().join("app.log.1")).unwrap();
+        append(&path, "three\n");
+        wait_for_logs(&storage, 3).await;
+        tokio::time::sleep(POLL_INTERVAL * 2).await;
+        tail.abort();
+
+        let logs = storage.list_logs(None, Some(100)).unwrap();
+        let mut bodies: Vec<&str> = logs.iter().map(|log| log.body.as_str()).collect();
+        bodies.sort_unstable();
+        assert_eq!(bodies, ["one", "three", "two"]);
+    }
+
+    #[tokio::test]
+    async fn test_tail_resumes_rotated_file_from_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "first\n");
+
+        let storage = Storage::new_in_memory().unwrap();
+        let tail = spawn_tail(
+            rotated_source(dir.path()),
+            IngestQueue::spawn(storage.clone(), 4),
+        );
+        wait_for_logs(&storage, 1).await;
+        tail.abort();
+
+        // Rotated while nothing was following it
+        std::fs::rename(&path, dir.path().join("app.log.1")).unwrap();
+        append(&path, "second\n");
+        let storage = Storage::new_in_memory().unwrap();
+        let tail = spawn_tail(
+            rotated_source(dir.path()),
+            IngestQueue::spawn(storage.clone(), 4),
+        );
+        let logs = wait_for_logs(&storage, 1).await;
+        tokio::time::sleep(POLL_INTERVAL * 2).await;
+        tail.abort();
+
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].body, "second");
+        assert_eq!(storage.count_logs().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_tail_truncation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.log");
+        append(&path, "before truncation\n");
+
+        let storage = Storage::new_in_memory().unwrap();
+        let tail = spawn_tail(source(dir.path()), IngestQueue::spawn(storage.clone(), 4));
+        wait_for_logs(&storage, 1).await;
+
+        std::fs::write(&path, "").unwrap();
+        tokio::time::sleep(POLL_INTERVAL * 2).await;
+        append(&path, "after\n");
+        let logs = wait_for_logs(&storage, 2).await;
+        tail.abort();
+
+        assert!(logs.iter().any(|log| log.body == "after"));
+    }
+}
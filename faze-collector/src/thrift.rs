@@ -0,0 +1,248 @@
+//! Minimal reader for the Thrift binary protocol
+//!
+//! Only decoding is supported, which is all the receivers need. Callers walk structs field by
+//! field and skip the ones they don't know about.
+
+use thiserror::Error;
+
+pub const STOP: u8 = 0;
+pub const BOOL: u8 = 2;
+pub const BYTE: u8 = 3;
+pub const DOUBLE: u8 = 4;
+pub const I16: u8 = 6;
+pub const I32: u8 = 8;
+pub const I64: u8 = 10;
+pub const STRING: u8 = 11;
+pub const STRUCT: u8 = 12;
+pub const MAP: u8 = 13;
+pub const SET: u8 = 14;
+pub const LIST: u8 = 15;
+
+/// Deepest nesting of structs and containers accepted before decoding gives up
+const MAX_DEPTH: usize = 64;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ThriftError {
+    #[error("unexpected end of input")]
+    UnexpectedEof,
+
+    #[error("unknown field type {0}")]
+    UnknownType(u8),
+
+    #[error("invalid length {0}")]
+    InvalidLength(i64),
+
+    #[error("nesting deeper than {MAX_DEPTH} levels")]
+    TooDeep,
+}
+
+pub type Result<T> = std::result::Result<T, ThriftError>;
+
+/// Reader over a buffer holding a single binary-encoded Thrift value
+pub struct Reader<'a> {
+    buf: &'a [u8],
+    depth: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf, depth: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.buf.len() {
+            return Err(ThriftError::UnexpectedEof);
+        }
+        let (head, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(head)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self
+            .take(N)?
+            .try_into()
+            .expect("slice has requested length"))
+    }
+
+    /// Read a container size, rejecting sizes that can't fit in the remaining input
+    fn read_size(&mut self) -> Result<usize> {
+        let size = self.read_i32()?;
+        match usize::try_from(size) {
+            Ok(size) if size <= self.buf.len() => Ok(size),
+            _ => Err(ThriftError::InvalidLength(size.into())),
+        }
+    }
+
+    fn enter(&mut self) -> Result<()> {
+        if self.depth >= MAX_DEPTH {
+            return Err(ThriftError::TooDeep);
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub fn read_bool(&mut self) -> Result<bool> {
+        Ok(self.read_byte()? != 0)
+    }
+
+    pub fn read_byte(&mut self) -> Result<u8> {
+        Ok(self.take_array::<1>()?[0])
+    }
+
+    pub fn read_i16(&mut self) -> Result<i16> {
+        Ok(i16::from_be_bytes(self.take_array()?))
+    }
+
+    pub fn read_i32(&mut self) -> Result<i32> {
+        Ok(i32::from_be_bytes(self.take_array()?))
+    }
+
+    pub fn read_i64(&mut self) -> Result<i64> {
+        Ok(i64::from_be_bytes(self.take_array()?))
+    }
+
+    pub fn read_double(&mut self) -> Result<f64> {
+        Ok(f64::from_be_bytes(self.take_array()?))
+    }
+
+    pub fn read_binary(&mut self) -> Result<&'a [u8]> {
+        let len = self.read_size()?;
+        self.take(len)
+    }
+
+    /// Read a string, replacing invalid UTF-8 rather than failing the whole payload
+    pub fn read_string(&mut self) -> Result<String> {
+        Ok(String::from_utf8_lossy(self.read_binary()?).into_owned())
+    }
+
+    /// Read a struct, calling `field` with the ID and type of each field
+    ///
+    /// `field` must consume the field's value, using [`Reader::skip`] for fields it doesn't
+    /// recognise.
+    pub fn read_struct(
+        &mut self,
+        mut field: impl FnMut(&mut Self, i16, u8) -> Result<()>,
+    ) -> Result<()> {
+        self.enter()?;
+        loop {
+            let field_type = self.read_byte()?;
+            if field_type == STOP {
+                break;
+            }
+            let id = self.read_i16()?;
+            field(self, id, field_type)?;
+        }
+        self.depth -= 1;
+        Ok(())
+    }
+
+    /// Read a list, calling `element` with the element type for each element
+    pub fn read_list<T>(
+        &mut self,
+        mut element: impl FnMut(&mut Self, u8) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        self.enter()?;
+        let element_type = self.read_byte()?;
+        let size = self.read_size()?;
+        let items = (0..size)
+            .map(|_| element(self, element_type))
+            .collect::<Result<Vec<T>>>()?;
+        self.depth -= 1;
+        Ok(items)
+    }
+
+    /// Read a list of structs, skipping elements of any other type
+    pub fn read_struct_list<T>(
+        &mut self,
+        mut element: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        let items = self.read_list(|r, element_type| {
+            if element_type == STRUCT {
+                element(r).map(Some)
+            } else {
+                r.skip(element_type).map(|_| None)
+            }
+        })?;
+        Ok(items.into_iter().flatten().collect())
+    }
+
+    /// Consume a value of the given type without decoding it
+    pub fn skip(&mut self, value_type: u8) -> Result<()> {
+        match value_type {
+            BOOL | BYTE => self.take(1).map(drop),
+            I16 => self.take(2).map(drop),
+            I32 => self.take(4).map(drop),
+            DOUBLE | I64 => self.take(8).map(drop),
+            STRING => self.read_binary().map(drop),
+            STRUCT => self.read_struct(|r, _, field_type| r.skip(field_type)),
+            LIST | SET => self
+                .read_list(|r, element_type| r.skip(element_type))
+                .map(drop),
+            MAP => {
+                self.enter()?;
+                let key_type = self.read_byte()?;
+                let value_type = self.read_byte()?;
+                let size = self.read_size()?;
+                for _ in 0..size {
+                    self.skip(key_type)?;
+                    self.skip(value_type)?;
+                }
+                self.depth -= 1;
+                Ok(())
+            }
+            other => Err(ThriftError::UnknownType(other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_struct_skips_unknown_fields() {
+        let buf = [
+            I32, 0, 1, 0, 0, 0, 42, // 1: i32 = 42
+            MAP, 0, 2, STRING, BOOL, 0, 0, 0, 1, 0, 0, 0, 1, b'k', 1, // 2: map<string, bool>
+            STRING, 0, 3, 0, 0, 0, 2, b'h', b'i', // 3: string = "hi"
+            STOP,
+        ];
+        let mut reader = Reader::new(&buf);
+        let (mut number, mut text) = (0, String::new());
+        reader
+            .read_struct(|r, id, field_type| {
+                match (id, field_type) {
+                    (1, I32) => number = r.read_i32()?,
+                    (3, STRING) => text = r.read_string()?,
+                    _ => r.skip(field_type)?,
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        assert_eq!(number, 42);
+        assert_eq!(text, "hi");
+    }
+
+    #[test]
+    fn test_read_truncated_input() {
+        let mut reader = Reader::new(&[STRING, 0, 1, 0, 0, 0, 5, b'a']);
+        let result = reader.read_struct(|r, _, field_type| r.skip(field_type));
+        assert_eq!(result, Err(ThriftError::InvalidLength(5)));
+
+        let mut reader = Reader::new(&[I64, 0, 1, 0, 0]);
+        let result = reader.read_struct(|r, _, field_type| r.skip(field_type));
+        assert_eq!(result, Err(ThriftError::UnexpectedEof));
+    }
+
+    #[test]
+    fn test_read_deeply_nested_input() {
+        let buf: Vec<u8> = std::iter::repeat_n([STRUCT, 0, 1], MAX_DEPTH + 1)
+            .flatten()
+            .collect();
+        let mut reader = Reader::new(&buf);
+        let result = reader.read_struct(|r, _, field_type| r.skip(field_type));
+        assert_eq!(result, Err(ThriftError::TooDeep));
+    }
+}
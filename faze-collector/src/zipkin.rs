@@ -0,0 +1,145 @@
+use crate::{
+    convert::zipkin::{ZipkinSpan, convert_spans},
+    ingest::{IngestQueue, log_failures},
+};
+use axum::{
+    Router,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use tower_http::decompression::RequestDecompressionLayer;
+use tracing::error;
+
+/// HTTP handler for Zipkin v2 span export
+///
+/// Only the JSON encoding is supported; Zipkin's protobuf encoding is answered with 415.
+async fn export_spans(
+    State(queue): State<IngestQueue>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let is_json = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_none_or(|value| value.starts_with("application/json"));
+    if !is_json {
+        return StatusCode::UNSUPPORTED_MEDIA_TYPE.into_response();
+    }
+
+    let spans: Vec<ZipkinSpan> = match serde_json::from_slice(&body) {
+        Ok(spans) => spans,
+        Err(e) => {
+            error!("Failed to decode Zipkin spans: {}", e);
+            return StatusCode::BAD_REQUEST.into_response();
+        }
+    };
+
+    match queue.submit_spans(convert_spans(spans)).await {
+        Ok((spans, outcome)) => {
+            log_failures("span", &spans, &outcome, |span| &span.span_id);
+            StatusCode::ACCEPTED.into_response()
+        }
+        Err(e) => {
+            error!("Failed to write span batch: {}", e);
+            e.into_response()
+        }
+    }
+}
+
+/// Create HTTP router for the Zipkin v2 receiver
+pub fn create_router(queue: IngestQueue) -> Router {
+    Router::new()
+        .route("/api/v2/spans", post(export_spans))
+        .layer(RequestDecompressionLayer::new())
+        .with_state(queue)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use faze::Storage;
+    use tower::ServiceExt;
+
+    fn zipkin_request(content_type: &str, body: impl Into<Body>) -> Request<Body> {
+        Request::builder()
+            .uri("/api/v2/spans")
+            .method("POST")
+            .header("content-type", content_type)
+            .body(body.into())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_export_zipkin_spans() {
+        let storage = Storage::new_in_memory().unwrap();
+        let app = create_router(IngestQueue::spawn(storage.clone(), 4));
+        let body = serde_json::json!([
+            {
+                "traceId": "5af7183fb1d4cf5f",
+                "id": "6b221d5bc9e6496c",
+                "name": "get /api",
+                "kind": "SERVER",
+                "timestamp": 1556604172355737u64,
+                "duration": 1431,
+                "localEndpoint": {"serviceName": "frontend"}
+            },
+            {
+                "traceId": "5af7183fb1d4cf5f",
+                "parentId": "6b221d5bc9e6496c",
+                "id": "352bff9a74ca9ad2",
+                "name": "query",
+                "kind": "CLIENT",
+                "timestamp": 1556604172355900u64,
+                "duration": 800,
+                "localEndpoint": {"serviceName": "frontend"},
+                "tags": {"db.system": "mysql"}
+            }
+        ]);
+
+        let response = app
+            .oneshot(zipkin_request("application/json", body.to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        let trace = storage
+            .get_trace_by_id("00000000000000005af7183fb1d4cf5f")
+            .unwrap();
+        assert_eq!(trace.spans.len(), 2);
+        assert_eq!(
+            trace.spans[1].parent_span_id.as_deref(),
+            Some("6b221d5bc9e6496c")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_zipkin_invalid_json() {
+        let storage = Storage::new_in_memory().unwrap();
+        let app = create_router(IngestQueue::spawn(storage.clone(), 4));
+
+        let response = app
+            .oneshot(zipkin_request("application/json", "{not json"))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(storage.count_spans().unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_export_zipkin_protobuf_unsupported() {
+        let storage = Storage::new_in_memory().unwrap();
+        let app = create_router(IngestQueue::spawn(storage, 4));
+
+        let response = app
+            .oneshot(zipkin_request("application/x-protobuf", vec![0x0a, 0x00]))
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+}
@@ -6,7 +6,7 @@ use axum::{
 };
 use faze::Storage;
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 use tracing::{error, info};
 
 /// Shared application state
@@ -49,7 +49,7 @@ pub struct TraceListResponse {
 }
 
 /// Trace information for list view
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TraceInfo {
     pub trace_id: String,
     pub service_name: Option<String>,
@@ -61,6 +61,38 @@ pub struct TraceInfo {
     pub root_span_kind: Option<faze::SpanKind>,
 }
 
+/// Direction of a span link relative to the requested trace
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkDirection {
+    /// A span in this trace links to a span elsewhere
+    Outgoing,
+    /// A span in another trace links to a span in this trace
+    Incoming,
+}
+
+/// A span link joining the requested trace to another span
+#[derive(Debug, Serialize)]
+pub struct TraceLink {
+    pub direction: LinkDirection,
+    /// Span in the requested trace
+    pub span_id: String,
+    /// Trace on the other end of the link
+    pub linked_trace_id: String,
+    /// Span on the other end of the link
+    pub linked_span_id: String,
+    pub trace_state: Option<String>,
+    pub attributes: faze::Attributes,
+    /// Summary of the linked trace, if it has been collected
+    pub linked_trace: Option<TraceInfo>,
+}
+
+/// Response for trace links
+#[derive(Debug, Serialize)]
+pub struct TraceLinksResponse {
+    pub links: Vec<TraceLink>,
+}
+
 #[derive(Deserialize)]
 pub struct ListParams {
     service: Option<String>,
@@ -86,6 +118,21 @@ impl From<&faze::Trace> for TraceInfo {
     }
 }
 
+impl From<&faze::TraceSummary> for TraceInfo {
+    fn from(summary: &faze::TraceSummary) -> Self {
+        Self {
+            trace_id: summary.trace_id.clone(),
+            service_name: summary.service_name.clone(),
+            duration_ms: summary.duration_ms(),
+            span_count: summary.span_count,
+            has_errors: summary.has_errors,
+            start_time: Some(summary.start_time_unix_nano),
+            root_span_name: summary.root_span_name.clone(),
+            root_span_kind: summary.root_span_kind,
+        }
+    }
+}
+
 /// GET /api/traces - List all traces
 pub async fn list_traces(
     State(state): State<AppState>,
@@ -176,6 +223,93 @@ pub async fn get_trace(
     }
 }
 
+/// GET /api/traces/:id/links - Get links from and to a trace, with linked trace summaries
+pub async fn get_trace_links(
+    State(state): State<AppState>,
+    Path(trace_id): Path<String>,
+) -> impl IntoResponse {
+    info!("GET /api/traces/{}/links", trace_id);
+
+    let links = state.storage.get_trace_by_id(&trace_id).and_then(|trace| {
+        let incoming = state.storage.get_spans_linking_to(&trace_id)?;
+        let mut links = Vec::new();
+
+        for span in &trace.spans {
+            for link in &span.links {
+                links.push(TraceLink {
+                    direction: LinkDirection::Outgoing,
+                    span_id: span.span_id.clone(),
+                    linked_trace_id: link.trace_id.clone(),
+                    linked_span_id: link.span_id.clone(),
+                    trace_state: link.trace_state.clone(),
+                    attributes: link.attributes.clone(),
+                    linked_trace: None,
+                });
+            }
+        }
+
+        for span in &incoming {
+            for link in span.links.iter().filter(|l| l.trace_id == trace_id) {
+                links.push(TraceLink {
+                    direction: LinkDirection::Incoming,
+                    span_id: link.span_id.clone(),
+                    linked_trace_id: span.trace_id.clone(),
+                    linked_span_id: span.span_id.clone(),
+                    trace_state: link.trace_state.clone(),
+                    attributes: link.attributes.clone(),
+                    linked_trace: None,
+                });
+            }
+        }
+
+        // The other linked traces are summarized together rather than loaded one by one
+        let mut linked_trace_ids: Vec<&str> = links
+            .iter()
+            .map(|link| link.linked_trace_id.as_str())
+            .filter(|id| *id != trace_id)
+            .collect();
+        linked_trace_ids.sort_unstable();
+        linked_trace_ids.dedup();
+        let linked_traces: HashMap<String, TraceInfo> = state
+            .storage
+            .get_trace_summaries(&linked_trace_ids)?
+            .iter()
+            .map(|summary| (summary.trace_id.clone(), TraceInfo::from(summary)))
+            .collect();
+
+        for link in &mut links {
+            link.linked_trace = if link.linked_trace_id == trace_id {
+                Some(TraceInfo::from(&trace))
+            } else {
+                linked_traces.get(&link.linked_trace_id).cloned()
+            };
+        }
+
+        Ok(links)
+    });
+
+    match links {
+        Ok(links) => Json(TraceLinksResponse { links }).into_response(),
+        Err(faze::StorageError::NotFound(_)) => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": format!("Trace not found: {}", trace_id)
+            })),
+        )
+            .into_response(),
+        Err(e) => {
+            error!("Failed to get links for trace {}: {}", trace_id, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(serde_json::json!({
+                    "error": format!("Failed to get trace links: {}", e)
+                })),
+            )
+                .into_response()
+        }
+    }
+}
+
 /// GET /api/logs - List logs
 pub async fn list_logs(
     State(state): State<AppState>,
@@ -279,7 +413,7 @@ pub async fn get_project_info() -> impl IntoResponse {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use faze::models::{Attributes, Span, SpanKind, Status};
+    use faze::models::{Attributes, Span, SpanEvent, SpanKind, SpanLink, Status};
 
     #[tokio::test]
     async fn test_health_check() {
@@ -350,6 +484,128 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_get_trace_with_events() {
+        let storage = Storage::new_in_memory().unwrap();
+        let mut attrs = Attributes::new();
+        attrs.insert("exception.type", "ValueError");
+        attrs.insert("exception.message", "bad value");
+        let span = Span::new(
+            "span1".to_string(),
+            "trace1".to_string(),
+            None,
+            "test operation".to_string(),
+            SpanKind::Server,
+            1_000_000_000,
+            2_000_000_000,
+            Attributes::new(),
+            Status::error("bad value"),
+            Some("test-service".to_string()),
+        )
+        .with_events(vec![SpanEvent::new(
+            "exception".to_string(),
+            1_500_000_000,
+            attrs,
+        )]);
+        storage.insert_span(&span).unwrap();
+
+        let state = AppState {
+            storage: Arc::new(storage),
+        };
+
+        let response = get_trace(State(state), Path("trace1".to_string()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let events = &json["spans"][0]["events"];
+        assert_eq!(events.as_array().unwrap().len(), 1);
+        assert_eq!(events[0]["name"], "exception");
+        assert_eq!(events[0]["time_unix_nano"], 1_500_000_000);
+        assert_eq!(events[0]["attributes"]["exception.type"], "ValueError");
+    }
+
+    #[tokio::test]
+    async fn test_get_trace_links() {
+        let storage = Storage::new_in_memory().unwrap();
+        let span = |span_id: &str, trace_id: &str| {
+            Span::new(
+                span_id.to_string(),
+                trace_id.to_string(),
+                None,
+                format!("{} operation", span_id),
+                SpanKind::Internal,
+                1_000_000_000,
+                2_000_000_000,
+                Attributes::new(),
+                Status::ok(),
+                Some("test-service".to_string()),
+            )
+        };
+        let link = |trace_id: &str, span_id: &str| {
+            SpanLink::new(
+                trace_id.to_string(),
+                span_id.to_string(),
+                None,
+                Attributes::new(),
+            )
+        };
+
+        storage.insert_span(&span("producer", "trace1")).unwrap();
+        storage
+            .insert_span(
+                &span("consumer", "trace2")
+                    .with_links(vec![link("trace1", "producer"), link("missing", "gone")]),
+            )
+            .unwrap();
+
+        let state = AppState {
+            storage: Arc::new(storage),
+        };
+
+        let response = get_trace_links(State(state.clone()), Path("trace2".to_string()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let links = json["links"].as_array().unwrap();
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0]["direction"], "outgoing");
+        assert_eq!(links[0]["span_id"], "consumer");
+        assert_eq!(links[0]["linked_trace_id"], "trace1");
+        assert_eq!(
+            links[0]["linked_trace"]["root_span_name"],
+            "producer operation"
+        );
+        assert!(links[1]["linked_trace"].is_null());
+
+        let response = get_trace_links(State(state.clone()), Path("trace1".to_string()))
+            .await
+            .into_response();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let links = json["links"].as_array().unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0]["direction"], "incoming");
+        assert_eq!(links[0]["span_id"], "producer");
+        assert_eq!(links[0]["linked_trace_id"], "trace2");
+        assert_eq!(links[0]["linked_span_id"], "consumer");
+
+        let response = get_trace_links(State(state), Path("nonexistent".to_string()))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
     #[tokio::test]
     async fn test_list_traces_with_filters() {
         let storage = Storage::new_in_memory().unwrap();
@@ -536,6 +792,123 @@ mod tests {
         assert_eq!(response.status(), StatusCode::OK);
     }
 
+    #[tokio::test]
+    async fn test_list_metrics_with_histogram() {
+        use faze::models::{
+            AggregationTemporality, HistogramData, Metric, MetricDataPoint, MetricType,
+        };
+
+        let storage = Storage::new_in_memory().unwrap();
+        let metric = Metric::new(
+            "http.server.duration".to_string(),
+            None,
+            Some("ms".to_string()),
+            MetricType::Histogram,
+            AggregationTemporality::Delta,
+            vec![
+                MetricDataPoint::new(1_000_000_000, None, 42.0, Attributes::new()).with_histogram(
+                    HistogramData {
+                        count: 3,
+                        sum: Some(42.0),
+                        min: Some(4.0),
+                        max: Some(30.0),
+                        explicit_bounds: vec![10.0],
+                        bucket_counts: vec![2, 1],
+                    },
+                ),
+            ],
+            Some("api".to_string()),
+        );
+        storage.insert_metric(&metric).unwrap();
+
+        let state = AppState {
+            storage: Arc::new(storage),
+        };
+        let params = ListParams {
+            service: None,
+            limit: None,
+        };
+
+        let response = list_metrics(State(state), Query(params))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let histogram = &json["metrics"][0]["data_points"][0]["histogram"];
+        assert_eq!(histogram["count"], 3);
+        assert_eq!(histogram["max"], 30.0);
+        assert_eq!(histogram["explicit_bounds"], serde_json::json!([10.0]));
+        assert_eq!(histogram["bucket_counts"], serde_json::json!([2, 1]));
+    }
+
+    #[tokio::test]
+    async fn test_list_metrics_exemplar_links_to_trace() {
+        use faze::models::{AggregationTemporality, Exemplar, Metric, MetricDataPoint, MetricType};
+
+        let storage = Storage::new_in_memory().unwrap();
+        let span = Span::new(
+            "slow-span".to_string(),
+            "slow-trace".to_string(),
+            None,
+            "GET /checkout".to_string(),
+            SpanKind::Server,
+            1_000_000_000,
+            3_000_000_000,
+            Attributes::new(),
+            Status::ok(),
+            Some("api".to_string()),
+        );
+        storage.insert_span(&span).unwrap();
+
+        let metric = Metric::new(
+            "http.server.duration".to_string(),
+            None,
+            Some("ms".to_string()),
+            MetricType::Gauge,
+            AggregationTemporality::Unspecified,
+            vec![
+                MetricDataPoint::new(3_000_000_000, None, 2000.0, Attributes::new())
+                    .with_exemplars(vec![
+                        Exemplar::new(3_000_000_000, 2000.0)
+                            .with_trace("slow-trace".to_string(), "slow-span".to_string()),
+                    ]),
+            ],
+            Some("api".to_string()),
+        );
+        storage.insert_metric(&metric).unwrap();
+
+        let state = AppState {
+            storage: Arc::new(storage),
+        };
+        let params = ListParams {
+            service: None,
+            limit: None,
+        };
+
+        let response = list_metrics(State(state.clone()), Query(params))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let exemplar = &json["metrics"][0]["data_points"][0]["exemplars"][0];
+        assert_eq!(exemplar["value"], 2000.0);
+        assert_eq!(exemplar["span_id"], "slow-span");
+
+        let trace_id = exemplar["trace_id"].as_str().unwrap().to_string();
+        let response = get_trace(State(state), Path(trace_id))
+            .await
+            .into_response();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
     #[tokio::test]
     async fn test_get_trace_with_multiple_spans() {
         let storage = Storage::new_in_memory().unwrap();
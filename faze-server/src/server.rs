@@ -1,7 +1,7 @@
 use crate::{
     routes::{
-        AppState, get_project_info, get_trace, health_check, list_logs, list_metrics,
-        list_services, list_traces,
+        AppState, get_project_info, get_trace, get_trace_links, health_check, list_logs,
+        list_metrics, list_services, list_traces,
     },
     ui,
 };
@@ -38,6 +38,7 @@ impl ApiServer {
             .route("/api/project", get(get_project_info))
             .route("/api/traces", get(list_traces))
             .route("/api/traces/{id}", get(get_trace))
+            .route("/api/traces/{id}/links", get(get_trace_links))
             .route("/api/logs", get(list_logs))
             .route("/api/services", get(list_services))
             .route("/api/metrics", get(list_metrics))
@@ -62,6 +63,10 @@ impl ApiServer {
             "  Get trace:     http://localhost:{}/api/traces/:id",
             self.port
         );
+        info!(
+            "  Trace links:   http://localhost:{}/api/traces/:id/links",
+            self.port
+        );
         info!("  List logs:     http://localhost:{}/api/logs", self.port);
         info!(
             "  List metrics:  http://localhost:{}/api/metrics",
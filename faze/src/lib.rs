@@ -3,10 +3,12 @@ pub mod storage;
 
 // Re-exports
 pub use models::{
-    AttributeValue, Attributes, Log, Metric, MetricDataPoint, MetricType, Resource, SeverityLevel,
-    Span, SpanKind, Status, StatusCode, Trace,
+    AttributeValue, Attributes, Exemplar, ExponentialBuckets, ExponentialHistogramData,
+    HistogramData, InstrumentationScope, Log, Metric, MetricDataPoint, MetricType, QuantileValue,
+    Resource, SeverityLevel, Span, SpanEvent, SpanKind, SpanLink, Status, StatusCode, SummaryData,
+    Trace, TraceSummary,
 };
 pub use storage::{
-    Storage, StorageError, detect_project_root, get_config_dir, get_data_dir, get_default_db_path,
-    get_project_db_path,
+    BatchOutcome, Storage, StorageError, detect_project_root, get_config_dir, get_data_dir,
+    get_default_db_path, get_project_db_path,
 };
@@ -11,6 +11,7 @@ pub enum AttributeValue {
     Bool(bool),
     Bytes(Vec<u8>),
     Array(Vec<AttributeValue>),
+    Map(Attributes),
 }
 
 impl From<String> for AttributeValue {
@@ -43,6 +44,12 @@ impl From<bool> for AttributeValue {
     }
 }
 
+impl From<Attributes> for AttributeValue {
+    fn from(map: Attributes) -> Self {
+        AttributeValue::Map(map)
+    }
+}
+
 /// Collection of attributes (key-value pairs)
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct Attributes(HashMap<String, AttributeValue>);
@@ -99,6 +106,13 @@ impl Attributes {
             _ => None,
         }
     }
+
+    pub fn get_map(&self, key: &str) -> Option<&Attributes> {
+        match self.0.get(key) {
+            Some(AttributeValue::Map(m)) => Some(m),
+            _ => None,
+        }
+    }
 }
 
 impl From<HashMap<String, AttributeValue>> for Attributes {
@@ -176,4 +190,31 @@ mod tests {
         let deserialized: Attributes = serde_json::from_str(&json).unwrap();
         assert_eq!(attrs, deserialized);
     }
+
+    #[test]
+    fn test_attributes_nested_map_serde() {
+        let mut user = Attributes::new();
+        user.insert("id", 42i64);
+        user.insert("roles", AttributeValue::Array(vec!["admin".into()]));
+
+        let mut request = Attributes::new();
+        request.insert("user", user);
+        request.insert("path", "/checkout");
+
+        let mut attrs = Attributes::new();
+        attrs.insert("request", request);
+
+        let json = serde_json::to_value(&attrs).unwrap();
+        assert_eq!(json["request"]["user"]["id"], 42);
+        assert_eq!(json["request"]["path"], "/checkout");
+
+        let deserialized: Attributes = serde_json::from_value(json).unwrap();
+        assert_eq!(attrs, deserialized);
+
+        let user = deserialized
+            .get_map("request")
+            .and_then(|r| r.get_map("user"))
+            .unwrap();
+        assert_eq!(user.get_int("id"), Some(42));
+    }
 }
@@ -1,4 +1,6 @@
 use super::attributes::Attributes;
+use super::resource::Resource;
+use super::scope::InstrumentationScope;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -55,6 +57,9 @@ impl SeverityLevel {
 pub struct Log {
     /// Timestamp (nanoseconds since epoch)
     pub time_unix_nano: i64,
+    /// When the SDK observed the record (nanoseconds since epoch), if it reported it
+    #[serde(default)]
+    pub observed_time_unix_nano: Option<i64>,
     /// Severity level
     pub severity_level: SeverityLevel,
     /// Optional severity text (e.g., "INFO", "ERROR")
@@ -69,6 +74,12 @@ pub struct Log {
     pub span_id: Option<String>,
     /// Service name (denormalized from resource)
     pub service_name: Option<String>,
+    /// Resource that produced this log
+    #[serde(default)]
+    pub resource: Option<Resource>,
+    /// Instrumentation scope that produced this log
+    #[serde(default)]
+    pub scope: Option<InstrumentationScope>,
 }
 
 impl Log {
@@ -85,6 +96,7 @@ impl Log {
     ) -> Self {
         Self {
             time_unix_nano,
+            observed_time_unix_nano: None,
             severity_level,
             severity_text,
             body,
@@ -92,9 +104,29 @@ impl Log {
             trace_id,
             span_id,
             service_name,
+            resource: None,
+            scope: None,
         }
     }
 
+    /// Record when the SDK observed this log
+    pub fn with_observed_time(mut self, observed_time_unix_nano: i64) -> Self {
+        self.observed_time_unix_nano = Some(observed_time_unix_nano);
+        self
+    }
+
+    /// Attach the resource that produced this log
+    pub fn with_resource(mut self, resource: Resource) -> Self {
+        self.resource = Some(resource);
+        self
+    }
+
+    /// Attach the instrumentation scope that produced this log
+    pub fn with_scope(mut self, scope: InstrumentationScope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
     /// Get timestamp as DateTime
     pub fn timestamp(&self) -> DateTime<Utc> {
         DateTime::from_timestamp_nanos(self.time_unix_nano)
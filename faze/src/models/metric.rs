@@ -1,4 +1,6 @@
 use super::attributes::Attributes;
+use super::resource::Resource;
+use super::scope::InstrumentationScope;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -9,6 +11,7 @@ pub enum MetricType {
     Gauge,
     Sum,
     Histogram,
+    ExponentialHistogram,
     Summary,
 }
 
@@ -23,6 +26,217 @@ pub enum AggregationTemporality {
     Cumulative,
 }
 
+/// Bucketed distribution carried by a histogram data point
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistogramData {
+    /// Number of recorded values
+    pub count: u64,
+    /// Sum of recorded values (if known)
+    pub sum: Option<f64>,
+    /// Minimum recorded value (if known)
+    pub min: Option<f64>,
+    /// Maximum recorded value (if known)
+    pub max: Option<f64>,
+    /// Upper bounds of the buckets, in increasing order
+    pub explicit_bounds: Vec<f64>,
+    /// Counts per bucket, one more than `explicit_bounds` (the last is the overflow bucket)
+    pub bucket_counts: Vec<u64>,
+}
+
+impl HistogramData {
+    /// Estimate the value at quantile `q` (0.0 to 1.0) by interpolating within its bucket.
+    /// Returns `None` for a malformed histogram whose counts don't match its bounds
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        if self.bucket_counts.len() != self.explicit_bounds.len() + 1 {
+            return None;
+        }
+        let total = self
+            .bucket_counts
+            .iter()
+            .fold(0u64, |total, &count| total.saturating_add(count));
+        if total == 0 || !(0.0..=1.0).contains(&q) {
+            return None;
+        }
+
+        let rank = q * total as f64;
+        let mut seen = 0u64;
+        for (i, &bucket_count) in self.bucket_counts.iter().enumerate() {
+            if bucket_count == 0 || (seen.saturating_add(bucket_count) as f64) < rank {
+                seen = seen.saturating_add(bucket_count);
+                continue;
+            }
+
+            let upper = self.explicit_bounds.get(i).copied();
+            let lower = match i {
+                0 => self.min.unwrap_or_else(|| upper.unwrap_or(0.0).min(0.0)),
+                _ => *self.explicit_bounds.get(i - 1)?,
+            };
+            let upper = upper.or(self.max).unwrap_or(lower);
+
+            let fraction = (rank - seen as f64) / bucket_count as f64;
+            let estimate = lower + (upper - lower) * fraction;
+            let estimate = self.min.map_or(estimate, |min| estimate.max(min));
+            return Some(self.max.map_or(estimate, |max| estimate.min(max)));
+        }
+
+        None
+    }
+}
+
+/// A contiguous run of base-2 exponential buckets
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExponentialBuckets {
+    /// Index of the first bucket in `bucket_counts`
+    pub offset: i32,
+    /// Counts for buckets `offset`, `offset + 1`, ...
+    pub bucket_counts: Vec<u64>,
+}
+
+/// Base-2 exponential distribution carried by an exponential histogram data point
+///
+/// Bucket `i` covers `(base^i, base^(i+1)]` for positive values (mirrored for negative ones),
+/// where `base = 2^(2^-scale)`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ExponentialHistogramData {
+    /// Number of recorded values
+    pub count: u64,
+    /// Sum of recorded values (if known)
+    pub sum: Option<f64>,
+    /// Minimum recorded value (if known)
+    pub min: Option<f64>,
+    /// Maximum recorded value (if known)
+    pub max: Option<f64>,
+    /// Resolution of the buckets
+    pub scale: i32,
+    /// Number of values whose magnitude is at most `zero_threshold`
+    pub zero_count: u64,
+    /// Upper magnitude of the zero bucket
+    pub zero_threshold: f64,
+    /// Buckets for positive values
+    pub positive: ExponentialBuckets,
+    /// Buckets for negative values
+    pub negative: ExponentialBuckets,
+}
+
+impl ExponentialHistogramData {
+    /// Growth factor between consecutive bucket boundaries
+    pub fn base(&self) -> f64 {
+        2f64.powf(2f64.powi(self.scale.saturating_neg()))
+    }
+
+    /// Convert to explicit buckets, ordered from the most negative bucket up to the largest
+    /// positive one, with the zero bucket in between
+    pub fn to_explicit(&self) -> HistogramData {
+        let base = self.base();
+        // (upper bound, count) for each bucket in increasing order
+        let mut buckets: Vec<(f64, u64)> = Vec::new();
+
+        // Indexes saturate rather than overflow for offsets at the edge of the i32 range
+        let index = |offset: i32, k: usize| offset.saturating_add(k.try_into().unwrap_or(i32::MAX));
+        for (k, &count) in self.negative.bucket_counts.iter().enumerate().rev() {
+            let index = index(self.negative.offset, k);
+            buckets.push((-base.powi(index), count));
+        }
+        if self.zero_count > 0 || !self.negative.bucket_counts.is_empty() {
+            buckets.push((self.zero_threshold, self.zero_count));
+        }
+        for (k, &count) in self.positive.bucket_counts.iter().enumerate() {
+            let index = index(self.positive.offset, k);
+            buckets.push((base.powi(index.saturating_add(1)), count));
+        }
+
+        // The last bucket is open-ended, so its upper bound is not listed
+        let explicit_bounds = match buckets.split_last() {
+            Some((_, rest)) => rest.iter().map(|&(upper, _)| upper).collect(),
+            None => Vec::new(),
+        };
+
+        HistogramData {
+            count: self.count,
+            sum: self.sum,
+            min: self.min,
+            max: self.max,
+            explicit_bounds,
+            bucket_counts: buckets.into_iter().map(|(_, count)| count).collect(),
+        }
+    }
+
+    /// Estimate the value at quantile `q` (0.0 to 1.0)
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        self.to_explicit().quantile(q)
+    }
+}
+
+/// A pre-computed quantile reported by a summary data point
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QuantileValue {
+    /// Quantile (0.0 to 1.0, e.g. 0.99 for p99)
+    pub quantile: f64,
+    /// Value at the quantile
+    pub value: f64,
+}
+
+/// Quantile summary carried by a summary data point
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SummaryData {
+    /// Number of recorded values
+    pub count: u64,
+    /// Sum of recorded values
+    pub sum: f64,
+    /// Reported quantiles, in increasing quantile order
+    pub quantile_values: Vec<QuantileValue>,
+}
+
+impl SummaryData {
+    /// Get the reported value at quantile `q`, if the summary includes it
+    pub fn value_at(&self, q: f64) -> Option<f64> {
+        self.quantile_values
+            .iter()
+            .find(|qv| qv.quantile == q)
+            .map(|qv| qv.value)
+    }
+}
+
+/// A sample measurement recorded alongside a data point, linking it to the trace that produced it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exemplar {
+    /// Time the measurement was recorded (nanoseconds since epoch)
+    pub time_unix_nano: i64,
+    /// Measured value
+    pub value: f64,
+    /// Trace ID (hex encoded), if the measurement was recorded within a sampled span
+    pub trace_id: Option<String>,
+    /// Span ID (hex encoded), if the measurement was recorded within a sampled span
+    pub span_id: Option<String>,
+    /// Measurement attributes that were filtered out of the data point's attributes
+    pub filtered_attributes: Attributes,
+}
+
+impl Exemplar {
+    pub fn new(time_unix_nano: i64, value: f64) -> Self {
+        Self {
+            time_unix_nano,
+            value,
+            trace_id: None,
+            span_id: None,
+            filtered_attributes: Attributes::new(),
+        }
+    }
+
+    /// Link the exemplar to the span it was recorded in
+    pub fn with_trace(mut self, trace_id: String, span_id: String) -> Self {
+        self.trace_id = Some(trace_id);
+        self.span_id = Some(span_id);
+        self
+    }
+
+    /// Set the attributes filtered out of the data point
+    pub fn with_filtered_attributes(mut self, filtered_attributes: Attributes) -> Self {
+        self.filtered_attributes = filtered_attributes;
+        self
+    }
+}
+
 /// Represents a metric data point
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MetricDataPoint {
@@ -34,6 +248,18 @@ pub struct MetricDataPoint {
     pub value: f64,
     /// Data point attributes
     pub attributes: Attributes,
+    /// Bucketed distribution (for histogram metrics)
+    #[serde(default)]
+    pub histogram: Option<HistogramData>,
+    /// Native base-2 exponential distribution (for exponential histogram metrics)
+    #[serde(default)]
+    pub exponential_histogram: Option<ExponentialHistogramData>,
+    /// Pre-computed quantiles (for summary metrics)
+    #[serde(default)]
+    pub summary: Option<SummaryData>,
+    /// Sample measurements linking the data point to traces
+    #[serde(default)]
+    pub exemplars: Vec<Exemplar>,
 }
 
 impl MetricDataPoint {
@@ -48,9 +274,38 @@ impl MetricDataPoint {
             start_time_unix_nano,
             value,
             attributes,
+            histogram: None,
+            exponential_histogram: None,
+            summary: None,
+            exemplars: Vec::new(),
         }
     }
 
+    /// Attach a bucketed distribution to the data point
+    pub fn with_histogram(mut self, histogram: HistogramData) -> Self {
+        self.histogram = Some(histogram);
+        self
+    }
+
+    /// Attach an exponential distribution, along with its explicit-bucket form for display
+    pub fn with_exponential_histogram(mut self, histogram: ExponentialHistogramData) -> Self {
+        self.histogram = Some(histogram.to_explicit());
+        self.exponential_histogram = Some(histogram);
+        self
+    }
+
+    /// Attach pre-computed quantiles to the data point
+    pub fn with_summary(mut self, summary: SummaryData) -> Self {
+        self.summary = Some(summary);
+        self
+    }
+
+    /// Attach exemplars to the data point
+    pub fn with_exemplars(mut self, exemplars: Vec<Exemplar>) -> Self {
+        self.exemplars = exemplars;
+        self
+    }
+
     /// Get timestamp as DateTime
     pub fn timestamp(&self) -> DateTime<Utc> {
         DateTime::from_timestamp_nanos(self.time_unix_nano)
@@ -80,6 +335,12 @@ pub struct Metric {
     pub data_points: Vec<MetricDataPoint>,
     /// Service name (denormalized from resource)
     pub service_name: Option<String>,
+    /// Resource that produced this metric
+    #[serde(default)]
+    pub resource: Option<Resource>,
+    /// Instrumentation scope that produced this metric
+    #[serde(default)]
+    pub scope: Option<InstrumentationScope>,
 }
 
 impl Metric {
@@ -101,9 +362,23 @@ impl Metric {
             temporality,
             data_points,
             service_name,
+            resource: None,
+            scope: None,
         }
     }
 
+    /// Attach the resource that produced this metric
+    pub fn with_resource(mut self, resource: Resource) -> Self {
+        self.resource = Some(resource);
+        self
+    }
+
+    /// Attach the instrumentation scope that produced this metric
+    pub fn with_scope(mut self, scope: InstrumentationScope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
     /// Create a gauge metric
     pub fn gauge(
         name: String,
@@ -256,6 +531,174 @@ mod tests {
         assert_eq!(metric, deserialized);
     }
 
+    fn create_test_histogram() -> HistogramData {
+        HistogramData {
+            count: 10,
+            sum: Some(420.0),
+            min: Some(2.0),
+            max: Some(180.0),
+            explicit_bounds: vec![10.0, 50.0, 100.0],
+            bucket_counts: vec![2, 4, 3, 1],
+        }
+    }
+
+    #[test]
+    fn test_histogram_quantile() {
+        let histogram = create_test_histogram();
+
+        assert_eq!(histogram.quantile(0.0), Some(2.0));
+        assert_eq!(histogram.quantile(0.2), Some(10.0));
+        assert_eq!(histogram.quantile(0.4), Some(30.0));
+        assert_eq!(histogram.quantile(0.9), Some(100.0));
+        assert_eq!(histogram.quantile(1.0), Some(180.0));
+        assert_eq!(histogram.quantile(1.5), None);
+        assert_eq!(HistogramData::default().quantile(0.5), None);
+    }
+
+    #[test]
+    fn test_histogram_quantile_malformed() {
+        let histogram = HistogramData {
+            count: 6,
+            explicit_bounds: vec![10.0],
+            bucket_counts: vec![1, 2, 3],
+            ..Default::default()
+        };
+        assert_eq!(histogram.quantile(0.99), None);
+
+        let histogram = HistogramData {
+            count: u64::MAX,
+            explicit_bounds: vec![10.0],
+            bucket_counts: vec![u64::MAX, u64::MAX],
+            ..Default::default()
+        };
+        assert_eq!(histogram.quantile(1.0), Some(10.0));
+    }
+
+    #[test]
+    fn test_exponential_histogram_to_explicit() {
+        // scale 0 => base 2: positive bucket i covers (2^i, 2^(i+1)]
+        let histogram = ExponentialHistogramData {
+            count: 10,
+            sum: Some(20.0),
+            min: Some(-3.0),
+            max: Some(7.5),
+            scale: 0,
+            zero_count: 1,
+            zero_threshold: 0.0,
+            positive: ExponentialBuckets {
+                offset: 0,
+                bucket_counts: vec![2, 3, 2],
+            },
+            negative: ExponentialBuckets {
+                offset: 0,
+                bucket_counts: vec![1, 1],
+            },
+        };
+        assert_eq!(histogram.base(), 2.0);
+
+        let explicit = histogram.to_explicit();
+        assert_eq!(explicit.explicit_bounds, vec![-2.0, -1.0, 0.0, 2.0, 4.0]);
+        assert_eq!(explicit.bucket_counts, vec![1, 1, 1, 2, 3, 2]);
+        assert_eq!(explicit.count, 10);
+        assert_eq!(explicit.max, Some(7.5));
+
+        // 5 of 10 values are at most 2
+        assert_eq!(histogram.quantile(0.5), Some(2.0));
+        assert_eq!(histogram.quantile(1.0), Some(7.5));
+    }
+
+    #[test]
+    fn test_exponential_histogram_scale() {
+        let histogram = ExponentialHistogramData {
+            count: 2,
+            scale: 1,
+            positive: ExponentialBuckets {
+                offset: 2,
+                bucket_counts: vec![1, 1],
+            },
+            ..Default::default()
+        };
+
+        // scale 1 => base sqrt(2), buckets 2 and 3 cover (2, 2^1.5] and (2^1.5, 4]
+        let explicit = histogram.to_explicit();
+        assert_eq!(explicit.explicit_bounds.len(), 1);
+        assert!((explicit.explicit_bounds[0] - 2f64.powf(1.5)).abs() < 1e-9);
+        assert_eq!(explicit.bucket_counts, vec![1, 1]);
+
+        assert_eq!(
+            ExponentialHistogramData::default()
+                .to_explicit()
+                .bucket_counts,
+            Vec::<u64>::new()
+        );
+    }
+
+    #[test]
+    fn test_exponential_histogram_extreme_values() {
+        let histogram = ExponentialHistogramData {
+            count: 4,
+            scale: i32::MIN,
+            positive: ExponentialBuckets {
+                offset: i32::MAX,
+                bucket_counts: vec![1, 1],
+            },
+            negative: ExponentialBuckets {
+                offset: i32::MAX,
+                bucket_counts: vec![1, 1],
+            },
+            ..Default::default()
+        };
+
+        let explicit = histogram.to_explicit();
+        assert_eq!(explicit.bucket_counts, vec![1, 1, 0, 1, 1]);
+    }
+
+    #[test]
+    fn test_summary_value_at() {
+        let summary = SummaryData {
+            count: 100,
+            sum: 2_500.0,
+            quantile_values: vec![
+                QuantileValue {
+                    quantile: 0.5,
+                    value: 20.0,
+                },
+                QuantileValue {
+                    quantile: 0.99,
+                    value: 95.0,
+                },
+            ],
+        };
+
+        assert_eq!(summary.value_at(0.5), Some(20.0));
+        assert_eq!(summary.value_at(0.99), Some(95.0));
+        assert_eq!(summary.value_at(0.9), None);
+    }
+
+    #[test]
+    fn test_data_point_with_histogram_serde() {
+        let dp = create_test_data_point(420.0).with_histogram(create_test_histogram());
+        let metric = Metric::new(
+            "http.server.duration".to_string(),
+            None,
+            Some("ms".to_string()),
+            MetricType::Histogram,
+            AggregationTemporality::Delta,
+            vec![dp],
+            None,
+        );
+
+        let json = serde_json::to_value(&metric).unwrap();
+        assert_eq!(json["data_points"][0]["histogram"]["count"], 10);
+        assert_eq!(
+            json["data_points"][0]["histogram"]["bucket_counts"],
+            serde_json::json!([2, 4, 3, 1])
+        );
+
+        let deserialized: Metric = serde_json::from_value(json).unwrap();
+        assert_eq!(metric, deserialized);
+    }
+
     #[test]
     fn test_metric_with_attributes() {
         let mut attrs = Attributes::new();
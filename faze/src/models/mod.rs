@@ -2,13 +2,18 @@ pub mod attributes;
 pub mod log;
 pub mod metric;
 pub mod resource;
+pub mod scope;
 pub mod span;
 pub mod trace;
 
 // Re-exports
 pub use attributes::{AttributeValue, Attributes};
 pub use log::{Log, SeverityLevel};
-pub use metric::{AggregationTemporality, Metric, MetricDataPoint, MetricType};
+pub use metric::{
+    AggregationTemporality, Exemplar, ExponentialBuckets, ExponentialHistogramData, HistogramData,
+    Metric, MetricDataPoint, MetricType, QuantileValue, SummaryData,
+};
 pub use resource::Resource;
-pub use span::{Span, SpanKind, Status, StatusCode};
-pub use trace::Trace;
+pub use scope::InstrumentationScope;
+pub use span::{Span, SpanEvent, SpanKind, SpanLink, Status, StatusCode};
+pub use trace::{Trace, TraceSummary};
@@ -19,6 +19,13 @@ impl Resource {
         }
     }
 
+    /// Create a resource that only names the service producing telemetry
+    pub fn for_service(name: impl Into<String>) -> Self {
+        let mut attributes = Attributes::new();
+        attributes.insert("service.name", name.into());
+        Self { attributes }
+    }
+
     /// Get the service name from resource attributes
     pub fn service_name(&self) -> Option<&str> {
         self.attributes.get_string("service.name")
@@ -70,6 +77,13 @@ mod tests {
         assert_eq!(resource.service_name(), Some("my-api"));
     }
 
+    #[test]
+    fn test_resource_for_service() {
+        let resource = Resource::for_service("my-api");
+        assert_eq!(resource.service_name(), Some("my-api"));
+        assert_eq!(resource.attributes.len(), 1);
+    }
+
     #[test]
     fn test_resource_service_version() {
         let mut attrs = Attributes::new();
@@ -0,0 +1,52 @@
+use super::attributes::Attributes;
+use serde::{Deserialize, Serialize};
+
+/// Instrumentation scope (library) that produced telemetry
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstrumentationScope {
+    /// Scope name (e.g., "io.opentelemetry.jdbc", "my-app/http")
+    pub name: String,
+    /// Scope version (if any)
+    pub version: Option<String>,
+    /// Scope attributes
+    pub attributes: Attributes,
+}
+
+impl InstrumentationScope {
+    pub fn new(name: String, version: Option<String>, attributes: Attributes) -> Self {
+        Self {
+            name,
+            version,
+            attributes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scope_new() {
+        let scope = InstrumentationScope::new(
+            "opentelemetry-http".to_string(),
+            Some("0.27.0".to_string()),
+            Attributes::new(),
+        );
+
+        assert_eq!(scope.name, "opentelemetry-http");
+        assert_eq!(scope.version, Some("0.27.0".to_string()));
+        assert!(scope.attributes.is_empty());
+    }
+
+    #[test]
+    fn test_scope_serde() {
+        let mut attrs = Attributes::new();
+        attrs.insert("library.language", "rust");
+        let scope = InstrumentationScope::new("tracing".to_string(), None, attrs);
+
+        let json = serde_json::to_string(&scope).unwrap();
+        let deserialized: InstrumentationScope = serde_json::from_str(&json).unwrap();
+        assert_eq!(scope, deserialized);
+    }
+}
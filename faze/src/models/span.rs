@@ -1,4 +1,6 @@
 use super::attributes::Attributes;
+use super::resource::Resource;
+use super::scope::InstrumentationScope;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -63,6 +65,61 @@ impl Default for Status {
     }
 }
 
+/// A timestamped event recorded during a span, such as an exception
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpanEvent {
+    /// Name of the event (`exception` for recorded errors)
+    pub name: String,
+    /// Time the event occurred (nanoseconds since epoch)
+    pub time_unix_nano: i64,
+    /// Event attributes
+    pub attributes: Attributes,
+}
+
+impl SpanEvent {
+    pub fn new(name: String, time_unix_nano: i64, attributes: Attributes) -> Self {
+        Self {
+            name,
+            time_unix_nano,
+            attributes,
+        }
+    }
+
+    /// Check if this event records an exception
+    pub fn is_exception(&self) -> bool {
+        self.name == "exception"
+    }
+}
+
+/// A causal link from a span to a span in the same or another trace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpanLink {
+    /// Trace ID of the linked span
+    pub trace_id: String,
+    /// Span ID of the linked span
+    pub span_id: String,
+    /// W3C trace state of the linked span (if any)
+    pub trace_state: Option<String>,
+    /// Link attributes
+    pub attributes: Attributes,
+}
+
+impl SpanLink {
+    pub fn new(
+        trace_id: String,
+        span_id: String,
+        trace_state: Option<String>,
+        attributes: Attributes,
+    ) -> Self {
+        Self {
+            trace_id,
+            span_id,
+            trace_state,
+            attributes,
+        }
+    }
+}
+
 /// Represents a single span in a trace
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Span {
@@ -86,6 +143,18 @@ pub struct Span {
     pub status: Status,
     /// Service name (denormalized from resource)
     pub service_name: Option<String>,
+    /// Events recorded during the span, in time order
+    #[serde(default)]
+    pub events: Vec<SpanEvent>,
+    /// Links to spans in the same or other traces
+    #[serde(default)]
+    pub links: Vec<SpanLink>,
+    /// Resource that produced this span
+    #[serde(default)]
+    pub resource: Option<Resource>,
+    /// Instrumentation scope that produced this span
+    #[serde(default)]
+    pub scope: Option<InstrumentationScope>,
 }
 
 impl Span {
@@ -113,9 +182,37 @@ impl Span {
             attributes,
             status,
             service_name,
+            events: Vec::new(),
+            links: Vec::new(),
+            resource: None,
+            scope: None,
         }
     }
 
+    /// Attach events to the span
+    pub fn with_events(mut self, events: Vec<SpanEvent>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Attach links to the span
+    pub fn with_links(mut self, links: Vec<SpanLink>) -> Self {
+        self.links = links;
+        self
+    }
+
+    /// Attach the resource that produced this span
+    pub fn with_resource(mut self, resource: Resource) -> Self {
+        self.resource = Some(resource);
+        self
+    }
+
+    /// Attach the instrumentation scope that produced this span
+    pub fn with_scope(mut self, scope: InstrumentationScope) -> Self {
+        self.scope = Some(scope);
+        self
+    }
+
     /// Get duration in nanoseconds
     pub fn duration_nanos(&self) -> i64 {
         self.end_time_unix_nano - self.start_time_unix_nano
@@ -145,6 +242,20 @@ impl Span {
     pub fn is_error(&self) -> bool {
         self.status.code == StatusCode::Error
     }
+
+    /// Get the events that record exceptions
+    pub fn exceptions(&self) -> impl Iterator<Item = &SpanEvent> {
+        self.events.iter().filter(|event| event.is_exception())
+    }
+
+    /// How much data this copy of the span carries
+    ///
+    /// Used to choose between copies of the same span, e.g. when an exporter retries: a set
+    /// status, attributes, events and links all count towards it.
+    pub fn completeness(&self) -> i64 {
+        let status = i64::from(self.status.code != StatusCode::Unset);
+        status + (self.attributes.len() + self.events.len() + self.links.len()) as i64
+    }
 }
 
 #[cfg(test)]
@@ -257,4 +368,68 @@ mod tests {
         assert_eq!(span.attributes.get_string("http.method"), Some("GET"));
         assert_eq!(span.attributes.get_int("http.status_code"), Some(200));
     }
+
+    #[test]
+    fn test_span_with_events() {
+        let mut attrs = Attributes::new();
+        attrs.insert("exception.type", "ValueError");
+        attrs.insert("exception.message", "bad value");
+
+        let span = create_test_span().with_events(vec![
+            SpanEvent::new(
+                "cache.miss".to_string(),
+                1_000_000_000_010_000_000,
+                Attributes::new(),
+            ),
+            SpanEvent::new("exception".to_string(), 1_000_000_000_050_000_000, attrs),
+        ]);
+
+        assert_eq!(span.events.len(), 2);
+        let exceptions: Vec<_> = span.exceptions().collect();
+        assert_eq!(exceptions.len(), 1);
+        assert_eq!(
+            exceptions[0].attributes.get_string("exception.type"),
+            Some("ValueError")
+        );
+
+        let json = serde_json::to_string(&span).unwrap();
+        let deserialized: Span = serde_json::from_str(&json).unwrap();
+        assert_eq!(span, deserialized);
+    }
+
+    #[test]
+    fn test_span_with_links() {
+        let mut attrs = Attributes::new();
+        attrs.insert("messaging.message.id", "msg-1");
+
+        let span = create_test_span().with_links(vec![SpanLink::new(
+            "producer-trace".to_string(),
+            "producer-span".to_string(),
+            Some("vendor=value".to_string()),
+            attrs,
+        )]);
+
+        assert_eq!(span.links.len(), 1);
+        assert_eq!(span.links[0].trace_id, "producer-trace");
+
+        let json = serde_json::to_string(&span).unwrap();
+        let deserialized: Span = serde_json::from_str(&json).unwrap();
+        assert_eq!(span, deserialized);
+    }
+
+    #[test]
+    fn test_span_completeness() {
+        let mut span = create_test_span();
+        span.status = Status::unset();
+        assert_eq!(span.completeness(), 0);
+
+        span.status = Status::ok();
+        span.attributes.insert("http.method", "GET");
+        let span = span.with_events(vec![SpanEvent::new(
+            "cache miss".to_string(),
+            1_000_000_000_050_000_000,
+            Attributes::new(),
+        )]);
+        assert_eq!(span.completeness(), 3);
+    }
 }
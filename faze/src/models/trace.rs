@@ -1,4 +1,4 @@
-use super::span::Span;
+use super::span::{Span, SpanKind};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -113,6 +113,34 @@ impl Trace {
     }
 }
 
+/// Summary of a trace, for listing it without loading its spans
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TraceSummary {
+    /// Unique trace identifier
+    pub trace_id: String,
+    /// Service name (from root span)
+    pub service_name: Option<String>,
+    /// Number of spans in the trace
+    pub span_count: usize,
+    /// Start of the earliest span (nanoseconds since epoch)
+    pub start_time_unix_nano: i64,
+    /// End of the latest span (nanoseconds since epoch)
+    pub end_time_unix_nano: i64,
+    /// Whether any span has error status
+    pub has_errors: bool,
+    /// Name of the root span, if it has been collected
+    pub root_span_name: Option<String>,
+    /// Kind of the root span, if it has been collected
+    pub root_span_kind: Option<SpanKind>,
+}
+
+impl TraceSummary {
+    /// Get duration in milliseconds
+    pub fn duration_ms(&self) -> f64 {
+        (self.end_time_unix_nano - self.start_time_unix_nano) as f64 / 1_000_000.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
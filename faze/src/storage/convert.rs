@@ -1,5 +1,7 @@
 use crate::models::{
-    AggregationTemporality, Attributes, MetricType, SeverityLevel, Span, SpanKind, Status,
+    AggregationTemporality, Attributes, Exemplar, ExponentialBuckets, ExponentialHistogramData,
+    HistogramData, InstrumentationScope, MetricType, Resource, SeverityLevel, Span, SpanEvent,
+    SpanKind, SpanLink, Status, SummaryData,
 };
 use rusqlite::Row;
 use serde::{Deserialize, Serialize};
@@ -8,6 +10,11 @@ pub fn to_json<T: Serialize>(value: &T) -> Result<String, serde_json::Error> {
     serde_json::to_string(value)
 }
 
+/// Serialize with object keys sorted, so equal values always produce the same text
+pub fn to_canonical_json<T: Serialize>(value: &T) -> Result<String, serde_json::Error> {
+    Ok(serde_json::to_value(value)?.to_string())
+}
+
 pub fn from_json<'de, T: Deserialize<'de>>(json: &'de str) -> Result<T, serde_json::Error> {
     serde_json::from_str(json)
 }
@@ -17,6 +24,7 @@ pub fn parse_metric_type(type_str: &str) -> MetricType {
         "Gauge" => MetricType::Gauge,
         "Sum" => MetricType::Sum,
         "Histogram" => MetricType::Histogram,
+        "ExponentialHistogram" => MetricType::ExponentialHistogram,
         "Summary" => MetricType::Summary,
         _ => MetricType::Gauge,
     }
@@ -68,8 +76,9 @@ pub fn span_from_row(row: &Row) -> rusqlite::Result<Span> {
 
     let kind_str: String = row.get(4)?;
     let kind = parse_span_kind(&kind_str);
+    let (resource, scope) = resource_and_scope_from_row(row, 10)?;
 
-    Ok(Span::new(
+    let span = Span::new(
         row.get(0)?, // span_id
         row.get(1)?, // trace_id
         row.get(2)?, // parent_span_id
@@ -80,5 +89,192 @@ pub fn span_from_row(row: &Row) -> rusqlite::Result<Span> {
         attributes,
         status,
         row.get(9)?, // service_name
+    );
+
+    Ok(Span {
+        resource,
+        scope,
+        ..span
+    })
+}
+
+/// Map the joined (resource attributes, scope name, scope version, scope attributes) columns
+/// starting at `start` to the resource and scope they describe
+pub fn resource_and_scope_from_row(
+    row: &Row,
+    start: usize,
+) -> rusqlite::Result<(Option<Resource>, Option<InstrumentationScope>)> {
+    let json_column = |idx: usize| -> rusqlite::Result<Option<Attributes>> {
+        let json: Option<String> = row.get(idx)?;
+        json.map(|json| {
+            from_json(&json).map_err(|e| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    idx,
+                    rusqlite::types::Type::Text,
+                    Box::new(e),
+                )
+            })
+        })
+        .transpose()
+    };
+
+    let resource = json_column(start)?.map(Resource::new);
+
+    let scope_name: Option<String> = row.get(start + 1)?;
+    let scope = match scope_name {
+        Some(name) => Some(InstrumentationScope::new(
+            name,
+            row.get(start + 2)?,
+            json_column(start + 3)?.unwrap_or_default(),
+        )),
+        None => None,
+    };
+
+    Ok((resource, scope))
+}
+
+/// Map a `span_events` row of (span_id, name, time_unix_nano, attributes) to its span ID and event
+pub fn span_event_from_row(row: &Row) -> rusqlite::Result<(String, SpanEvent)> {
+    let attributes_json: String = row.get(3)?;
+    let attributes: Attributes = from_json(&attributes_json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(3, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+
+    Ok((
+        row.get(0)?, // span_id
+        SpanEvent::new(
+            row.get(1)?, // name
+            row.get(2)?, // time_unix_nano
+            attributes,
+        ),
     ))
 }
+
+/// Map a `span_links` row of (span_id, linked_trace_id, linked_span_id, trace_state, attributes)
+/// to its span ID and link
+pub fn span_link_from_row(row: &Row) -> rusqlite::Result<(String, SpanLink)> {
+    let attributes_json: String = row.get(4)?;
+    let attributes: Attributes = from_json(&attributes_json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(4, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+
+    Ok((
+        row.get(0)?, // span_id
+        SpanLink::new(
+            row.get(1)?, // linked_trace_id
+            row.get(2)?, // linked_span_id
+            row.get(3)?, // trace_state
+            attributes,
+        ),
+    ))
+}
+
+/// Map a `metric_exemplars` row of (metric_id, time_unix_nano, value, trace_id, span_id,
+/// filtered_attributes) to its metric row ID and exemplar
+pub fn exemplar_from_row(row: &Row) -> rusqlite::Result<(i64, Exemplar)> {
+    let attributes_json: String = row.get(5)?;
+    let filtered_attributes: Attributes = from_json(&attributes_json).map_err(|e| {
+        rusqlite::Error::FromSqlConversionFailure(5, rusqlite::types::Type::Text, Box::new(e))
+    })?;
+
+    Ok((
+        row.get(0)?, // metric_id
+        Exemplar {
+            time_unix_nano: row.get(1)?,
+            value: row.get(2)?,
+            trace_id: row.get(3)?,
+            span_id: row.get(4)?,
+            filtered_attributes,
+        },
+    ))
+}
+
+/// Parse a JSON array column, treating NULL as absent
+fn json_list_column<T: for<'de> Deserialize<'de>>(
+    row: &Row,
+    idx: usize,
+) -> rusqlite::Result<Option<Vec<T>>> {
+    let json: Option<String> = row.get(idx)?;
+    json.map(|json| {
+        from_json(&json).map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+        })
+    })
+    .transpose()
+}
+
+/// Map the (count, sum, min, max, explicit_bounds, bucket_counts) histogram columns starting at
+/// `start` to the distribution they describe, if the row is a histogram point
+pub fn histogram_from_row(row: &Row, start: usize) -> rusqlite::Result<Option<HistogramData>> {
+    let Some(bucket_counts) = json_list_column(row, start + 5)? else {
+        return Ok(None);
+    };
+    let count: Option<i64> = row.get(start)?;
+
+    Ok(Some(HistogramData {
+        count: count.unwrap_or_default() as u64,
+        sum: row.get(start + 1)?,
+        min: row.get(start + 2)?,
+        max: row.get(start + 3)?,
+        explicit_bounds: json_list_column(row, start + 4)?.unwrap_or_default(),
+        bucket_counts,
+    }))
+}
+
+/// Map the shared (count, sum, min, max) histogram columns starting at `start` and the
+/// (scale, zero_count, zero_threshold, positive_offset, positive_bucket_counts, negative_offset,
+/// negative_bucket_counts) columns starting at `exponential_start` to the exponential
+/// distribution they describe, if the row is an exponential histogram point
+pub fn exponential_histogram_from_row(
+    row: &Row,
+    start: usize,
+    exponential_start: usize,
+) -> rusqlite::Result<Option<ExponentialHistogramData>> {
+    let scale: Option<i32> = row.get(exponential_start)?;
+    let Some(scale) = scale else {
+        return Ok(None);
+    };
+    let count: Option<i64> = row.get(start)?;
+    let zero_count: Option<i64> = row.get(exponential_start + 1)?;
+    let zero_threshold: Option<f64> = row.get(exponential_start + 2)?;
+    let positive_offset: Option<i32> = row.get(exponential_start + 3)?;
+    let negative_offset: Option<i32> = row.get(exponential_start + 5)?;
+
+    Ok(Some(ExponentialHistogramData {
+        count: count.unwrap_or_default() as u64,
+        sum: row.get(start + 1)?,
+        min: row.get(start + 2)?,
+        max: row.get(start + 3)?,
+        scale,
+        zero_count: zero_count.unwrap_or_default() as u64,
+        zero_threshold: zero_threshold.unwrap_or_default(),
+        positive: ExponentialBuckets {
+            offset: positive_offset.unwrap_or_default(),
+            bucket_counts: json_list_column(row, exponential_start + 4)?.unwrap_or_default(),
+        },
+        negative: ExponentialBuckets {
+            offset: negative_offset.unwrap_or_default(),
+            bucket_counts: json_list_column(row, exponential_start + 6)?.unwrap_or_default(),
+        },
+    }))
+}
+
+/// Map the shared (count, sum) columns starting at `start` and the quantile_values column at
+/// `quantiles` to the summary they describe, if the row is a summary point
+pub fn summary_from_row(
+    row: &Row,
+    start: usize,
+    quantiles: usize,
+) -> rusqlite::Result<Option<SummaryData>> {
+    let Some(quantile_values) = json_list_column(row, quantiles)? else {
+        return Ok(None);
+    };
+    let count: Option<i64> = row.get(start)?;
+    let sum: Option<f64> = row.get(start + 1)?;
+
+    Ok(Some(SummaryData {
+        count: count.unwrap_or_default() as u64,
+        sum: sum.unwrap_or_default(),
+        quantile_values,
+    }))
+}
@@ -3,15 +3,19 @@ mod db_path;
 mod schema;
 
 use crate::models::{
-    AggregationTemporality, Attributes, Log, Metric, MetricDataPoint, MetricType, Span, Trace,
+    AggregationTemporality, Attributes, InstrumentationScope, Log, Metric, MetricDataPoint,
+    MetricType, Resource, Span, Trace, TraceSummary,
 };
-use rusqlite::{Connection, Result as SqliteResult, params};
+use rusqlite::{Connection, Result as SqliteResult, params, params_from_iter};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 use thiserror::Error;
 
 use convert::{
-    from_json, parse_metric_type, parse_severity_level, parse_temporality, span_from_row, to_json,
+    exemplar_from_row, exponential_histogram_from_row, from_json, histogram_from_row,
+    parse_metric_type, parse_severity_level, parse_span_kind, parse_temporality,
+    resource_and_scope_from_row, span_event_from_row, span_from_row, span_link_from_row,
+    summary_from_row, to_canonical_json, to_json,
 };
 pub use db_path::{
     detect_project_root, get_config_dir, get_data_dir, get_default_db_path, get_project_db_path,
@@ -35,6 +39,22 @@ pub enum StorageError {
 
 pub type Result<T> = std::result::Result<T, StorageError>;
 
+/// Outcome of a batch insert, which stores every item it can and reports the rest
+#[derive(Debug, Default)]
+pub struct BatchOutcome {
+    /// Number of items stored, leaving out copies of ones that were already stored
+    pub stored: usize,
+    /// Index within the batch and error of each item that was not stored
+    pub failures: Vec<(usize, StorageError)>,
+}
+
+impl BatchOutcome {
+    /// Number of items that were not stored
+    pub fn rejected(&self) -> usize {
+        self.failures.len()
+    }
+}
+
 /// Main storage interface for Faze
 ///
 /// By default, Faze stores data in a file-based database (`faze.db`) to prevent
@@ -104,110 +124,64 @@ impl Storage {
     /// Insert a span
     pub fn insert_span(&self, span: &Span) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        let attributes_json = to_json(&span.attributes)?;
-        let status_json = to_json(&span.status)?;
-
-        conn.execute(
-            "INSERT INTO spans (
-                span_id, trace_id, parent_span_id, name, kind,
-                start_time_unix_nano, end_time_unix_nano,
-                attributes, status, service_name
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-            params![
-                &span.span_id,
-                &span.trace_id,
-                &span.parent_span_id,
-                &span.name,
-                format!("{:?}", span.kind),
-                span.start_time_unix_nano,
-                span.end_time_unix_nano,
-                attributes_json,
-                status_json,
-                &span.service_name,
-            ],
-        )?;
-
-        Ok(())
+        write_span(&conn, span).map(drop)
     }
 
-    /// Insert multiple spans
-    pub fn insert_spans(&self, spans: &[Span]) -> Result<()> {
-        for span in spans {
-            self.insert_span(span)?;
-        }
-        Ok(())
+    /// Insert multiple spans in a single transaction
+    pub fn insert_spans(&self, spans: &[Span]) -> Result<BatchOutcome> {
+        self.insert_batch(spans, |conn, _, span| write_span(conn, span))
     }
 
     /// Insert a log
     pub fn insert_log(&self, log: &Log) -> Result<()> {
         let conn = self.conn.lock().unwrap();
-        let attributes_json = to_json(&log.attributes)?;
-
-        conn.execute(
-            "INSERT INTO logs (
-                time_unix_nano, severity_level, severity_text, body,
-                attributes, trace_id, span_id, service_name
-            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
-            params![
-                log.time_unix_nano,
-                format!("{:?}", log.severity_level),
-                &log.severity_text,
-                &log.body,
-                attributes_json,
-                &log.trace_id,
-                &log.span_id,
-                &log.service_name,
-            ],
-        )?;
-
-        Ok(())
+        write_log(&conn, 0, log).map(drop)
     }
 
-    /// Insert multiple logs
-    pub fn insert_logs(&self, logs: &[Log]) -> Result<()> {
-        for log in logs {
-            self.insert_log(log)?;
-        }
-        Ok(())
+    /// Insert multiple logs in a single transaction
+    pub fn insert_logs(&self, logs: &[Log]) -> Result<BatchOutcome> {
+        self.insert_batch(logs, write_log)
     }
 
     /// Insert a metric
     pub fn insert_metric(&self, metric: &Metric) -> Result<()> {
         let conn = self.conn.lock().unwrap();
+        write_metric(&conn, metric).map(drop)
+    }
 
-        for data_point in &metric.data_points {
-            let attributes_json = to_json(&data_point.attributes)?;
-
-            conn.execute(
-                "INSERT INTO metrics (
-                    name, description, unit, metric_type, temporality,
-                    time_unix_nano, start_time_unix_nano, value,
-                    attributes, service_name
-                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-                params![
-                    &metric.name,
-                    &metric.description,
-                    &metric.unit,
-                    format!("{:?}", metric.metric_type),
-                    format!("{:?}", metric.temporality),
-                    data_point.time_unix_nano,
-                    data_point.start_time_unix_nano,
-                    data_point.value,
-                    attributes_json,
-                    &metric.service_name,
-                ],
-            )?;
-        }
-
-        Ok(())
+    /// Insert multiple metrics in a single transaction
+    pub fn insert_metrics(&self, metrics: &[Metric]) -> Result<BatchOutcome> {
+        self.insert_batch(metrics, |conn, _, metric| write_metric(conn, metric))
     }
 
-    /// Insert multiple metrics
-    pub fn insert_metrics(&self, metrics: &[Metric]) -> Result<()> {
-        for metric in metrics {
-            self.insert_metric(metric)?;
+    /// Write every item of a batch inside one transaction
+    ///
+    /// Each item gets its own savepoint, so an item that fails part way through leaves none of
+    /// its rows behind while the rest of the batch is still committed. `write` is given each
+    /// item's index within the batch along with the item, and returns whether it was stored.
+    fn insert_batch<T>(
+        &self,
+        items: &[T],
+        write: impl Fn(&Connection, usize, &T) -> Result<bool>,
+    ) -> Result<BatchOutcome> {
+        let mut conn = self.conn.lock().unwrap();
+        let mut tx = conn.transaction()?;
+        let mut outcome = BatchOutcome::default();
+
+        for (index, item) in items.iter().enumerate() {
+            let savepoint = tx.savepoint()?;
+            match write(&savepoint, index, item) {
+                Ok(stored) => {
+                    savepoint.commit()?;
+                    outcome.stored += usize::from(stored);
+                }
+                // Dropping the savepoint rolls back the item's partial writes
+                Err(e) => outcome.failures.push((index, e)),
+            }
         }
-        Ok(())
+
+        tx.commit()?;
+        Ok(outcome)
     }
 
     /// Get a complete trace by ID
@@ -228,21 +202,120 @@ impl Storage {
     fn get_spans_by_trace_id(&self, trace_id: &str) -> Result<Vec<Span>> {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
-            "SELECT span_id, trace_id, parent_span_id, name, kind,
-                    start_time_unix_nano, end_time_unix_nano,
-                    attributes, status, service_name
-             FROM spans
-             WHERE trace_id = ?1
-             ORDER BY start_time_unix_nano",
+            "SELECT s.span_id, s.trace_id, s.parent_span_id, s.name, s.kind,
+                    s.start_time_unix_nano, s.end_time_unix_nano,
+                    s.attributes, s.status, s.service_name,
+                    r.attributes, sc.name, sc.version, sc.attributes
+             FROM spans s
+             LEFT JOIN resources r ON r.id = s.resource_id
+             LEFT JOIN scopes sc ON sc.id = s.scope_id
+             WHERE s.trace_id = ?1
+             ORDER BY s.start_time_unix_nano",
         )?;
 
-        let spans = stmt
+        let mut spans = stmt
             .query_map([trace_id], span_from_row)?
             .collect::<SqliteResult<Vec<_>>>()?;
 
+        attach_span_details(&conn, trace_id, &mut spans)?;
+
         Ok(spans)
     }
 
+    /// Get spans from other traces that link to a span in the given trace
+    pub fn get_spans_linking_to(&self, trace_id: &str) -> Result<Vec<Span>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT s.span_id, s.trace_id, s.parent_span_id, s.name, s.kind,
+                    s.start_time_unix_nano, s.end_time_unix_nano,
+                    s.attributes, s.status, s.service_name,
+                    r.attributes, sc.name, sc.version, sc.attributes
+             FROM spans s
+             LEFT JOIN resources r ON r.id = s.resource_id
+             LEFT JOIN scopes sc ON sc.id = s.scope_id
+             WHERE (s.span_id, s.trace_id) IN (
+                 SELECT span_id, trace_id FROM span_links
+                 WHERE linked_trace_id = ?1 AND trace_id != ?1
+             )
+             ORDER BY s.trace_id, s.start_time_unix_nano",
+        )?;
+
+        let spans = stmt
+            .query_map([trace_id], span_from_row)?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        let mut by_trace: Vec<Vec<Span>> = Vec::new();
+        for span in spans {
+            match by_trace.last_mut() {
+                Some(group) if group[0].trace_id == span.trace_id => group.push(span),
+                _ => by_trace.push(vec![span]),
+            }
+        }
+
+        let mut linking = Vec::new();
+        for mut group in by_trace {
+            let source_trace_id = group[0].trace_id.clone();
+            attach_span_details(&conn, &source_trace_id, &mut group)?;
+            linking.extend(group);
+        }
+
+        Ok(linking)
+    }
+
+    /// Get summaries of the given traces in one query, leaving out traces with no spans stored
+    pub fn get_trace_summaries(&self, trace_ids: &[&str]) -> Result<Vec<TraceSummary>> {
+        if trace_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let ids = (1..=trace_ids.len())
+            .map(|i| format!("?{}", i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        // The root and first spans are picked by their start time, as `Trace` orders spans
+        let mut stmt = conn.prepare(&format!(
+            "WITH roots AS (
+                 SELECT trace_id, name, kind, service_name, MIN(start_time_unix_nano)
+                 FROM spans
+                 WHERE parent_span_id IS NULL AND trace_id IN ({ids})
+                 GROUP BY trace_id
+             ),
+             firsts AS (
+                 SELECT trace_id, service_name, MIN(start_time_unix_nano)
+                 FROM spans
+                 WHERE trace_id IN ({ids})
+                 GROUP BY trace_id
+             )
+             SELECT s.trace_id, COALESCE(r.service_name, f.service_name), COUNT(*),
+                    MIN(s.start_time_unix_nano), MAX(s.end_time_unix_nano),
+                    MAX(json_extract(s.status, '$.code') = 'ERROR'), r.name, r.kind
+             FROM spans s
+             JOIN firsts f ON f.trace_id = s.trace_id
+             LEFT JOIN roots r ON r.trace_id = s.trace_id
+             WHERE s.trace_id IN ({ids})
+             GROUP BY s.trace_id"
+        ))?;
+
+        let summaries = stmt
+            .query_map(params_from_iter(trace_ids), |row| {
+                let root_span_kind: Option<String> = row.get(7)?;
+                Ok(TraceSummary {
+                    trace_id: row.get(0)?,
+                    service_name: row.get(1)?,
+                    span_count: row.get::<_, i64>(2)? as usize,
+                    start_time_unix_nano: row.get(3)?,
+                    end_time_unix_nano: row.get(4)?,
+                    has_errors: row.get(5)?,
+                    root_span_name: row.get(6)?,
+                    root_span_kind: root_span_kind.as_deref().map(parse_span_kind),
+                })
+            })?
+            .collect::<SqliteResult<Vec<_>>>()?;
+
+        Ok(summaries)
+    }
+
     /// List traces with optional filters
     pub fn list_traces(
         &self,
@@ -293,11 +366,14 @@ impl Storage {
         let (query, params_vec): (String, Vec<Box<dyn rusqlite::ToSql>>) =
             if let Some(service) = service_name {
                 (
-                    "SELECT time_unix_nano, severity_level, severity_text, body,
-                        attributes, trace_id, span_id, service_name
-                 FROM logs
-                 WHERE service_name = ?1
-                 ORDER BY time_unix_nano DESC
+                    "SELECT l.time_unix_nano, l.severity_level, l.severity_text, l.body,
+                        l.attributes, l.trace_id, l.span_id, l.service_name,
+                        r.attributes, sc.name, sc.version, sc.attributes
+                 FROM logs l
+                 LEFT JOIN resources r ON r.id = l.resource_id
+                 LEFT JOIN scopes sc ON sc.id = l.scope_id
+                 WHERE l.service_name = ?1
+                 ORDER BY l.time_unix_nano DESC
                  LIMIT ?2"
                         .to_string(),
                     vec![
@@ -307,10 +383,13 @@ impl Storage {
                 )
             } else {
                 (
-                    "SELECT time_unix_nano, severity_level, severity_text, body,
-                        attributes, trace_id, span_id, service_name
-                 FROM logs
-                 ORDER BY time_unix_nano DESC
+                    "SELECT l.time_unix_nano, l.severity_level, l.severity_text, l.body,
+                        l.attributes, l.trace_id, l.span_id, l.service_name,
+                        r.attributes, sc.name, sc.version, sc.attributes
+                 FROM logs l
+                 LEFT JOIN resources r ON r.id = l.resource_id
+                 LEFT JOIN scopes sc ON sc.id = l.scope_id
+                 ORDER BY l.time_unix_nano DESC
                  LIMIT ?1"
                         .to_string(),
                     vec![Box::new(limit.unwrap_or(100) as i64)],
@@ -334,8 +413,9 @@ impl Storage {
 
                 let severity_str: String = row.get(1)?;
                 let severity_level = parse_severity_level(&severity_str);
+                let (resource, scope) = resource_and_scope_from_row(row, 8)?;
 
-                Ok(Log::new(
+                let log = Log::new(
                     row.get(0)?,
                     severity_level,
                     row.get(2)?,
@@ -344,7 +424,13 @@ impl Storage {
                     row.get(5)?,
                     row.get(6)?,
                     row.get(7)?,
-                ))
+                );
+
+                Ok(Log {
+                    resource,
+                    scope,
+                    ..log
+                })
             })?
             .collect::<SqliteResult<Vec<_>>>()?;
 
@@ -365,23 +451,41 @@ impl Storage {
         let (query, params_vec): (String, Vec<Box<dyn rusqlite::ToSql>>) =
             if let Some(service) = service_name {
                 (
-                    "SELECT name, description, unit, metric_type, temporality,
-                            time_unix_nano, start_time_unix_nano, value,
-                            attributes, service_name
-                       FROM metrics
-                       WHERE service_name = ?1
-                       ORDER BY time_unix_nano DESC
+                    "SELECT m.name, m.description, m.unit, m.metric_type, m.temporality,
+                            m.time_unix_nano, m.start_time_unix_nano, m.value,
+                            m.attributes, m.service_name,
+                            r.attributes, sc.name, sc.version, sc.attributes,
+                            m.histogram_count, m.histogram_sum, m.histogram_min,
+                            m.histogram_max, m.explicit_bounds, m.bucket_counts,
+                            m.scale, m.zero_count, m.zero_threshold,
+                            m.positive_offset, m.positive_bucket_counts,
+                            m.negative_offset, m.negative_bucket_counts,
+                            m.quantile_values, m.id
+                       FROM metrics m
+                       LEFT JOIN resources r ON r.id = m.resource_id
+                       LEFT JOIN scopes sc ON sc.id = m.scope_id
+                       WHERE m.service_name = ?1
+                       ORDER BY m.time_unix_nano DESC
                        LIMIT ?2"
                         .to_string(),
                     vec![Box::new(service.to_string()), Box::new(limit_value)],
                 )
             } else {
                 (
-                    "SELECT name, description, unit, metric_type, temporality,
-                            time_unix_nano, start_time_unix_nano, value,
-                            attributes, service_name
-                       FROM metrics
-                       ORDER BY time_unix_nano DESC
+                    "SELECT m.name, m.description, m.unit, m.metric_type, m.temporality,
+                            m.time_unix_nano, m.start_time_unix_nano, m.value,
+                            m.attributes, m.service_name,
+                            r.attributes, sc.name, sc.version, sc.attributes,
+                            m.histogram_count, m.histogram_sum, m.histogram_min,
+                            m.histogram_max, m.explicit_bounds, m.bucket_counts,
+                            m.scale, m.zero_count, m.zero_threshold,
+                            m.positive_offset, m.positive_bucket_counts,
+                            m.negative_offset, m.negative_bucket_counts,
+                            m.quantile_values, m.id
+                       FROM metrics m
+                       LEFT JOIN resources r ON r.id = m.resource_id
+                       LEFT JOIN scopes sc ON sc.id = m.scope_id
+                       ORDER BY m.time_unix_nano DESC
                        LIMIT ?1"
                         .to_string(),
                     vec![Box::new(limit_value)],
@@ -392,40 +496,37 @@ impl Storage {
         let params_refs: Vec<&dyn rusqlite::ToSql> =
             params_vec.iter().map(|p| p.as_ref()).collect();
 
-        let metrics = stmt
+        let rows = stmt
             .query_map(&params_refs[..], |row| {
                 let attributes_json: String = row.get(8)?;
 
-                let raw_json: serde_json::Value =
-                    serde_json::from_str(&attributes_json).map_err(|e| {
-                        rusqlite::Error::FromSqlConversionFailure(
-                            8,
-                            rusqlite::types::Type::Text,
-                            Box::new(e),
-                        )
-                    })?;
-
-                let mut attributes = Attributes::default();
-                if let serde_json::Value::Object(map) = raw_json {
-                    for (k, v) in map {
-                        attributes.insert(k, v.to_string());
-                    }
-                }
+                let attributes: Attributes = from_json(&attributes_json).map_err(|e| {
+                    rusqlite::Error::FromSqlConversionFailure(
+                        8,
+                        rusqlite::types::Type::Text,
+                        Box::new(e),
+                    )
+                })?;
 
                 let metric_type_str: String = row.get(3)?;
                 let metric_type: MetricType = parse_metric_type(&metric_type_str);
 
                 let temporality_str: String = row.get(4)?;
                 let temporality: AggregationTemporality = parse_temporality(&temporality_str);
+                let (resource, scope) = resource_and_scope_from_row(row, 10)?;
 
                 let data_point = MetricDataPoint {
                     time_unix_nano: row.get(5)?,
                     start_time_unix_nano: row.get(6)?,
                     value: row.get(7)?,
                     attributes,
+                    histogram: histogram_from_row(row, 14)?,
+                    exponential_histogram: exponential_histogram_from_row(row, 14, 20)?,
+                    summary: summary_from_row(row, 14, 27)?,
+                    exemplars: Vec::new(),
                 };
 
-                Ok(Metric {
+                let metric = Metric {
                     name: row.get(0)?,
                     description: row.get(1)?,
                     unit: row.get(2)?,
@@ -433,11 +534,31 @@ impl Storage {
                     temporality,
                     data_points: vec![data_point],
                     service_name: row.get(9)?,
-                })
+                    resource,
+                    scope,
+                };
+
+                Ok((row.get::<_, i64>(28)?, metric))
             })?
-            .collect::<rusqlite::Result<Vec<Metric>>>()
+            .collect::<rusqlite::Result<Vec<(i64, Metric)>>>()
             .map_err(StorageError::from)?;
 
+        let mut exemplars_stmt = conn.prepare(
+            "SELECT metric_id, time_unix_nano, value, trace_id, span_id, filtered_attributes
+             FROM metric_exemplars
+             WHERE metric_id = ?1
+             ORDER BY time_unix_nano, id",
+        )?;
+
+        let mut metrics = Vec::with_capacity(rows.len());
+        for (metric_id, mut metric) in rows {
+            for row in exemplars_stmt.query_map([metric_id], exemplar_from_row)? {
+                let (_, exemplar) = row?;
+                metric.data_points[0].exemplars.push(exemplar);
+            }
+            metrics.push(metric);
+        }
+
         Ok(metrics)
     }
 
@@ -448,6 +569,14 @@ impl Storage {
         Ok(count)
     }
 
+    /// Get count of span events
+    pub fn count_span_events(&self) -> Result<i64> {
+        let conn = self.conn.lock().unwrap();
+        let count: i64 =
+            conn.query_row("SELECT COUNT(*) FROM span_events", [], |row| row.get(0))?;
+        Ok(count)
+    }
+
     /// Get count of logs
     pub fn count_logs(&self) -> Result<i64> {
         let conn = self.conn.lock().unwrap();
@@ -463,10 +592,380 @@ impl Storage {
     }
 }
 
+/// Write a span, returning whether it was stored rather than kept out by a more complete copy
+fn write_span(conn: &Connection, span: &Span) -> Result<bool> {
+    let attributes_json = to_json(&span.attributes)?;
+    let status_json = to_json(&span.status)?;
+    let resource_id = insert_resource(conn, span.resource.as_ref())?;
+    let scope_id = insert_scope(conn, span.scope.as_ref())?;
+
+    // A span that is sent again (e.g. an exporter retry) replaces the stored copy, unless the
+    // stored copy is more complete
+    let changed = conn
+        .prepare_cached(
+            "INSERT INTO spans (
+                span_id, trace_id, parent_span_id, name, kind,
+                start_time_unix_nano, end_time_unix_nano,
+                attributes, status, service_name, resource_id, scope_id, completeness
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            ON CONFLICT(span_id, trace_id) DO UPDATE SET
+                parent_span_id = excluded.parent_span_id,
+                name = excluded.name,
+                kind = excluded.kind,
+                start_time_unix_nano = excluded.start_time_unix_nano,
+                end_time_unix_nano = excluded.end_time_unix_nano,
+                attributes = excluded.attributes,
+                status = excluded.status,
+                service_name = excluded.service_name,
+                resource_id = excluded.resource_id,
+                scope_id = excluded.scope_id,
+                completeness = excluded.completeness
+            WHERE excluded.completeness >= spans.completeness",
+        )?
+        .execute(params![
+            &span.span_id,
+            &span.trace_id,
+            &span.parent_span_id,
+            &span.name,
+            format!("{:?}", span.kind),
+            span.start_time_unix_nano,
+            span.end_time_unix_nano,
+            attributes_json,
+            status_json,
+            &span.service_name,
+            resource_id,
+            scope_id,
+            span.completeness(),
+        ])?;
+
+    if changed == 0 {
+        return Ok(false);
+    }
+
+    // Drop the events and links of any copy stored before this one
+    conn.prepare_cached("DELETE FROM span_events WHERE span_id = ?1 AND trace_id = ?2")?
+        .execute([&span.span_id, &span.trace_id])?;
+    conn.prepare_cached("DELETE FROM span_links WHERE span_id = ?1 AND trace_id = ?2")?
+        .execute([&span.span_id, &span.trace_id])?;
+
+    for event in &span.events {
+        conn.prepare_cached(
+            "INSERT INTO span_events (
+                span_id, trace_id, name, time_unix_nano, attributes
+            ) VALUES (?1, ?2, ?3, ?4, ?5)",
+        )?
+        .execute(params![
+            &span.span_id,
+            &span.trace_id,
+            &event.name,
+            event.time_unix_nano,
+            to_json(&event.attributes)?,
+        ])?;
+    }
+
+    for link in &span.links {
+        conn.prepare_cached(
+            "INSERT INTO span_links (
+                span_id, trace_id, linked_trace_id, linked_span_id,
+                trace_state, attributes
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?
+        .execute(params![
+            &span.span_id,
+            &span.trace_id,
+            &link.trace_id,
+            &link.span_id,
+            &link.trace_state,
+            to_json(&link.attributes)?,
+        ])?;
+    }
+
+    Ok(true)
+}
+
+/// Write a log that sat at `index` within its batch, returning whether it was stored rather than
+/// skipped as a copy of one already stored
+fn write_log(conn: &Connection, index: usize, log: &Log) -> Result<bool> {
+    let attributes_json = to_json(&log.attributes)?;
+    let resource_id = insert_resource(conn, log.resource.as_ref())?;
+    let scope_id = insert_scope(conn, log.scope.as_ref())?;
+    let severity_level = format!("{:?}", log.severity_level);
+    // Only a record sent again (e.g. an exporter retry) has the same observed time and position
+    // in its export, so it gets the same fingerprint and is skipped. Records without an observed
+    // time are never deduplicated, since identical lines can legitimately repeat
+    let fingerprint = log
+        .observed_time_unix_nano
+        .map(|observed_time_unix_nano| {
+            to_canonical_json(&(
+                observed_time_unix_nano,
+                index,
+                log.time_unix_nano,
+                &severity_level,
+                &log.severity_text,
+                &log.body,
+                &log.attributes,
+                &log.trace_id,
+                &log.span_id,
+                &log.service_name,
+                resource_id,
+                scope_id,
+            ))
+        })
+        .transpose()?;
+
+    let inserted = conn
+        .prepare_cached(
+            "INSERT INTO logs (
+                time_unix_nano, severity_level, severity_text, body,
+                attributes, trace_id, span_id, service_name, resource_id, scope_id, fingerprint
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            ON CONFLICT(fingerprint) DO NOTHING",
+        )?
+        .execute(params![
+            log.time_unix_nano,
+            severity_level,
+            &log.severity_text,
+            &log.body,
+            attributes_json,
+            &log.trace_id,
+            &log.span_id,
+            &log.service_name,
+            resource_id,
+            scope_id,
+            fingerprint,
+        ])?;
+
+    Ok(inserted > 0)
+}
+
+fn write_metric(conn: &Connection, metric: &Metric) -> Result<bool> {
+    let resource_id = insert_resource(conn, metric.resource.as_ref())?;
+    let scope_id = insert_scope(conn, metric.scope.as_ref())?;
+
+    let metric_type = format!("{:?}", metric.metric_type);
+    let temporality = format!("{:?}", metric.temporality);
+
+    // OTLP requires a timestamp; a point without one can't be placed in its series. SQLite
+    // stores NaN as NULL, and Prometheus uses NaN to mark series as stale. Either way the point
+    // is left out while the rest of the metric is still stored
+    let data_points = metric
+        .data_points
+        .iter()
+        .filter(|data_point| data_point.time_unix_nano > 0 && data_point.value.is_finite());
+    for data_point in data_points {
+        let attributes_json = to_json(&data_point.attributes)?;
+        // A point of the same series at the same time (e.g. from an exporter retry) replaces the
+        // stored one, so a resent correction wins. The fingerprint is the whole key's canonical
+        // JSON rather than a hash of it, so points of different series can never collide
+        let fingerprint = to_canonical_json(&(
+            &metric.name,
+            &metric_type,
+            &temporality,
+            data_point.time_unix_nano,
+            data_point.start_time_unix_nano,
+            &data_point.attributes,
+            &metric.service_name,
+            resource_id,
+            scope_id,
+        ))?;
+        let histogram = data_point.histogram.as_ref();
+        let exponential = data_point.exponential_histogram.as_ref();
+        let explicit_bounds = histogram.map(|h| to_json(&h.explicit_bounds)).transpose()?;
+        let bucket_counts = histogram.map(|h| to_json(&h.bucket_counts)).transpose()?;
+        let positive_bucket_counts = exponential
+            .map(|e| to_json(&e.positive.bucket_counts))
+            .transpose()?;
+        let negative_bucket_counts = exponential
+            .map(|e| to_json(&e.negative.bucket_counts))
+            .transpose()?;
+        let summary = data_point.summary.as_ref();
+        let quantile_values = summary.map(|s| to_json(&s.quantile_values)).transpose()?;
+        // count, sum, min and max are shared by both histogram forms and summaries
+        let (count, sum, min, max) = match (histogram, exponential, summary) {
+            (Some(h), _, _) => (Some(h.count as i64), h.sum, h.min, h.max),
+            (None, Some(e), _) => (Some(e.count as i64), e.sum, e.min, e.max),
+            (None, None, Some(s)) => (Some(s.count as i64), Some(s.sum), None, None),
+            (None, None, None) => (None, None, None, None),
+        };
+
+        let metric_id: i64 = conn
+            .prepare_cached(
+                "INSERT INTO metrics (
+                name, description, unit, metric_type, temporality,
+                time_unix_nano, start_time_unix_nano, value,
+                attributes, service_name, resource_id, scope_id,
+                histogram_count, histogram_sum, histogram_min, histogram_max,
+                explicit_bounds, bucket_counts, scale, zero_count, zero_threshold,
+                positive_offset, positive_bucket_counts,
+                negative_offset, negative_bucket_counts, quantile_values, fingerprint
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12,
+                      ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23, ?24, ?25,
+                      ?26, ?27)
+            ON CONFLICT(fingerprint) DO UPDATE SET
+                description = excluded.description,
+                unit = excluded.unit,
+                value = excluded.value,
+                histogram_count = excluded.histogram_count,
+                histogram_sum = excluded.histogram_sum,
+                histogram_min = excluded.histogram_min,
+                histogram_max = excluded.histogram_max,
+                explicit_bounds = excluded.explicit_bounds,
+                bucket_counts = excluded.bucket_counts,
+                scale = excluded.scale,
+                zero_count = excluded.zero_count,
+                zero_threshold = excluded.zero_threshold,
+                positive_offset = excluded.positive_offset,
+                positive_bucket_counts = excluded.positive_bucket_counts,
+                negative_offset = excluded.negative_offset,
+                negative_bucket_counts = excluded.negative_bucket_counts,
+                quantile_values = excluded.quantile_values
+            RETURNING id",
+            )?
+            .query_row(
+                params![
+                    &metric.name,
+                    &metric.description,
+                    &metric.unit,
+                    metric_type,
+                    temporality,
+                    data_point.time_unix_nano,
+                    data_point.start_time_unix_nano,
+                    data_point.value,
+                    attributes_json,
+                    &metric.service_name,
+                    resource_id,
+                    scope_id,
+                    count,
+                    sum,
+                    min,
+                    max,
+                    explicit_bounds,
+                    bucket_counts,
+                    exponential.map(|e| e.scale),
+                    exponential.map(|e| e.zero_count as i64),
+                    exponential.map(|e| e.zero_threshold),
+                    exponential.map(|e| e.positive.offset),
+                    positive_bucket_counts,
+                    exponential.map(|e| e.negative.offset),
+                    negative_bucket_counts,
+                    quantile_values,
+                    fingerprint,
+                ],
+                |row| row.get(0),
+            )?;
+
+        // Exemplars of a replaced point go along with it
+        conn.prepare_cached("DELETE FROM metric_exemplars WHERE metric_id = ?1")?
+            .execute([metric_id])?;
+        // SQLite stores NaN as NULL, so exemplars without a finite value are left out rather
+        // than failing the whole metric
+        let exemplars = data_point
+            .exemplars
+            .iter()
+            .filter(|exemplar| exemplar.value.is_finite());
+        for exemplar in exemplars {
+            conn.prepare_cached(
+                "INSERT INTO metric_exemplars (
+                    metric_id, time_unix_nano, value, trace_id, span_id, filtered_attributes
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            )?
+            .execute(params![
+                metric_id,
+                exemplar.time_unix_nano,
+                exemplar.value,
+                &exemplar.trace_id,
+                &exemplar.span_id,
+                to_json(&exemplar.filtered_attributes)?,
+            ])?;
+        }
+    }
+
+    Ok(true)
+}
+
+/// Store a resource once, returning the ID of the existing row when it was seen before
+fn insert_resource(conn: &Connection, resource: Option<&Resource>) -> Result<Option<i64>> {
+    let Some(resource) = resource else {
+        return Ok(None);
+    };
+
+    let attributes_json = to_canonical_json(&resource.attributes)?;
+    conn.prepare_cached(
+        "INSERT INTO resources (attributes) VALUES (?1) ON CONFLICT(attributes) DO NOTHING",
+    )?
+    .execute([&attributes_json])?;
+    let id = conn
+        .prepare_cached("SELECT id FROM resources WHERE attributes = ?1")?
+        .query_row([&attributes_json], |row| row.get(0))?;
+
+    Ok(Some(id))
+}
+
+/// Store an instrumentation scope once, returning the ID of the existing row when it was seen before
+fn insert_scope(conn: &Connection, scope: Option<&InstrumentationScope>) -> Result<Option<i64>> {
+    let Some(scope) = scope else {
+        return Ok(None);
+    };
+
+    let fingerprint = to_canonical_json(scope)?;
+    conn.prepare_cached(
+        "INSERT INTO scopes (fingerprint, name, version, attributes)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT(fingerprint) DO NOTHING",
+    )?
+    .execute(params![
+        &fingerprint,
+        &scope.name,
+        &scope.version,
+        to_canonical_json(&scope.attributes)?,
+    ])?;
+    let id = conn
+        .prepare_cached("SELECT id FROM scopes WHERE fingerprint = ?1")?
+        .query_row([&fingerprint], |row| row.get(0))?;
+
+    Ok(Some(id))
+}
+
+/// Load the events and links of spans belonging to one trace
+fn attach_span_details(conn: &Connection, trace_id: &str, spans: &mut [Span]) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT span_id, name, time_unix_nano, attributes
+         FROM span_events
+         WHERE trace_id = ?1
+         ORDER BY time_unix_nano, id",
+    )?;
+
+    for row in stmt.query_map([trace_id], span_event_from_row)? {
+        let (span_id, event) = row?;
+        if let Some(span) = spans.iter_mut().find(|span| span.span_id == span_id) {
+            span.events.push(event);
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT span_id, linked_trace_id, linked_span_id, trace_state, attributes
+         FROM span_links
+         WHERE trace_id = ?1
+         ORDER BY id",
+    )?;
+
+    for row in stmt.query_map([trace_id], span_link_from_row)? {
+        let (span_id, link) = row?;
+        if let Some(span) = spans.iter_mut().find(|span| span.span_id == span_id) {
+            span.links.push(link);
+        }
+    }
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::{Attributes, SpanKind, Status};
+    use crate::models::{
+        Attributes, Exemplar, ExponentialBuckets, ExponentialHistogramData, HistogramData,
+        QuantileValue, SpanEvent, SpanKind, SpanLink, Status, StatusCode, SummaryData,
+    };
 
     fn create_test_span(span_id: &str, trace_id: &str) -> Span {
         Span::new(
@@ -509,12 +1008,151 @@ mod tests {
             create_test_span("span2", "trace1"),
         ];
 
-        storage.insert_spans(&spans).unwrap();
+        let outcome = storage.insert_spans(&spans).unwrap();
+        assert_eq!(outcome.rejected(), 0);
         let trace = storage.get_trace_by_id("trace1").unwrap();
 
         assert_eq!(trace.spans.len(), 2);
     }
 
+    #[test]
+    fn test_insert_spans_accepts_retried_spans() {
+        let storage = Storage::new_in_memory().unwrap();
+        let spans = vec![
+            create_test_span("span1", "trace1"),
+            create_test_span("span1", "trace1"),
+            create_test_span("span2", "trace1"),
+        ];
+
+        let outcome = storage.insert_spans(&spans).unwrap();
+
+        assert_eq!(outcome.rejected(), 0);
+        assert_eq!(storage.count_spans().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_insert_span_keeps_more_complete_copy() {
+        let storage = Storage::new_in_memory().unwrap();
+        let event = |name: &str| {
+            SpanEvent::new(
+                name.to_string(),
+                1_000_000_000_050_000_000,
+                Attributes::new(),
+            )
+        };
+        let complete = create_test_span("span1", "trace1").with_events(vec![event("retry")]);
+        storage.insert_span(&complete).unwrap();
+
+        // A bare copy of the span doesn't overwrite the richer one
+        let mut bare = create_test_span("span1", "trace1");
+        bare.status = Status::unset();
+        bare.name = "bare".to_string();
+        storage.insert_span(&bare).unwrap();
+
+        let trace = storage.get_trace_by_id("trace1").unwrap();
+        assert_eq!(trace.spans.len(), 1);
+        assert_eq!(trace.spans[0].name, "test-operation");
+        assert_eq!(trace.spans[0].events, vec![event("retry")]);
+
+        // A later copy that is at least as complete replaces it, events included
+        let mut later = create_test_span("span1", "trace1")
+            .with_events(vec![event("first attempt"), event("second attempt")]);
+        later.name = "later".to_string();
+        storage.insert_span(&later).unwrap();
+
+        let trace = storage.get_trace_by_id("trace1").unwrap();
+        assert_eq!(trace.spans.len(), 1);
+        assert_eq!(trace.spans[0].name, "later");
+        assert_eq!(trace.spans[0].events.len(), 2);
+        assert_eq!(storage.count_span_events().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_insert_metrics_rolls_back_failed_item() {
+        let storage = Storage::new_in_memory().unwrap();
+        let metric = |name: &str, exemplar_value: f64| {
+            Metric::new(
+                name.to_string(),
+                None,
+                None,
+                MetricType::Gauge,
+                AggregationTemporality::Unspecified,
+                vec![
+                    MetricDataPoint::new(1_000_000_000, None, 1.0, Attributes::new())
+                        .with_exemplars(vec![Exemplar::new(1_000_000_000, exemplar_value)]),
+                ],
+                None,
+            )
+        };
+        // Fails the exemplar insert after the metric row itself has been written
+        storage
+            .conn
+            .lock()
+            .unwrap()
+            .execute_batch(
+                "CREATE TRIGGER reject_exemplar BEFORE INSERT ON metric_exemplars
+                 WHEN NEW.value < 0 BEGIN SELECT RAISE(ABORT, 'rejected'); END",
+            )
+            .unwrap();
+        let metrics = vec![metric("good", 1.0), metric("bad", -1.0)];
+
+        let outcome = storage.insert_metrics(&metrics).unwrap();
+
+        assert_eq!(outcome.rejected(), 1);
+        assert_eq!(outcome.failures[0].0, 1);
+        let stored = storage.list_metrics(None, None).unwrap();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].name, "good");
+        assert_eq!(stored[0].data_points[0].exemplars.len(), 1);
+    }
+
+    #[test]
+    fn test_get_trace_summaries() {
+        let storage = Storage::new_in_memory().unwrap();
+        let child = |span_id: &str, trace_id: &str, service_name: &str| Span {
+            parent_span_id: Some("root".to_string()),
+            service_name: Some(service_name.to_string()),
+            end_time_unix_nano: 1_000_000_000_300_000_000,
+            status: Status {
+                code: StatusCode::Error,
+                message: None,
+            },
+            ..create_test_span(span_id, trace_id)
+        };
+        storage
+            .insert_spans(&[
+                create_test_span("root", "trace1"),
+                child("child", "trace1", "db"),
+                // Only a child of this trace has been collected
+                child("orphan", "trace2", "worker"),
+                create_test_span("other", "trace3"),
+            ])
+            .unwrap();
+
+        let mut summaries = storage
+            .get_trace_summaries(&["trace1", "trace2", "missing"])
+            .unwrap();
+        summaries.sort_by(|a, b| a.trace_id.cmp(&b.trace_id));
+
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].trace_id, "trace1");
+        assert_eq!(summaries[0].span_count, 2);
+        assert_eq!(summaries[0].service_name.as_deref(), Some("test-service"));
+        assert_eq!(
+            summaries[0].root_span_name.as_deref(),
+            Some("test-operation")
+        );
+        assert_eq!(summaries[0].root_span_kind, Some(SpanKind::Server));
+        assert_eq!(summaries[0].duration_ms(), 300.0);
+        assert!(summaries[0].has_errors);
+        assert_eq!(summaries[1].trace_id, "trace2");
+        assert_eq!(summaries[1].span_count, 1);
+        assert_eq!(summaries[1].service_name.as_deref(), Some("worker"));
+        assert_eq!(summaries[1].root_span_name, None);
+
+        assert!(storage.get_trace_summaries(&[]).unwrap().is_empty());
+    }
+
     #[test]
     fn test_list_traces() {
         let storage = Storage::new_in_memory().unwrap();
@@ -545,6 +1183,362 @@ mod tests {
         assert_eq!(storage.count_spans().unwrap(), 2);
     }
 
+    #[test]
+    fn test_insert_and_get_span_events() {
+        let storage = Storage::new_in_memory().unwrap();
+        let mut attrs = Attributes::new();
+        attrs.insert("exception.type", "ValueError");
+        attrs.insert(
+            "exception.stacktrace",
+            "Traceback (most recent call last): ...",
+        );
+
+        let span = create_test_span("span1", "trace1").with_events(vec![
+            SpanEvent::new("exception".to_string(), 1_000_000_000_050_000_000, attrs),
+            SpanEvent::new(
+                "retry".to_string(),
+                1_000_000_000_010_000_000,
+                Attributes::new(),
+            ),
+        ]);
+        storage.insert_span(&span).unwrap();
+        storage
+            .insert_span(&create_test_span("span2", "trace1"))
+            .unwrap();
+        assert_eq!(storage.count_span_events().unwrap(), 2);
+
+        let trace = storage.get_trace_by_id("trace1").unwrap();
+        let stored = trace.spans.iter().find(|s| s.span_id == "span1").unwrap();
+        assert_eq!(stored.events.len(), 2);
+        assert_eq!(stored.events[0].name, "retry");
+        assert_eq!(stored.events[1].name, "exception");
+        assert_eq!(
+            stored.events[1].attributes.get_string("exception.type"),
+            Some("ValueError")
+        );
+
+        let other = trace.spans.iter().find(|s| s.span_id == "span2").unwrap();
+        assert!(other.events.is_empty());
+    }
+
+    #[test]
+    fn test_insert_and_get_span_links() {
+        let storage = Storage::new_in_memory().unwrap();
+        storage
+            .insert_span(&create_test_span("producer", "trace1"))
+            .unwrap();
+
+        let mut attrs = Attributes::new();
+        attrs.insert("messaging.message.id", "msg-1");
+        let consumer = create_test_span("consumer", "trace2").with_links(vec![SpanLink::new(
+            "trace1".to_string(),
+            "producer".to_string(),
+            Some("vendor=value".to_string()),
+            attrs,
+        )]);
+        storage.insert_span(&consumer).unwrap();
+
+        let trace = storage.get_trace_by_id("trace2").unwrap();
+        let links = &trace.spans[0].links;
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].trace_id, "trace1");
+        assert_eq!(links[0].span_id, "producer");
+        assert_eq!(links[0].trace_state, Some("vendor=value".to_string()));
+        assert_eq!(
+            links[0].attributes.get_string("messaging.message.id"),
+            Some("msg-1")
+        );
+
+        let linking = storage.get_spans_linking_to("trace1").unwrap();
+        assert_eq!(linking.len(), 1);
+        assert_eq!(linking[0].span_id, "consumer");
+        assert_eq!(linking[0].links.len(), 1);
+
+        assert!(storage.get_spans_linking_to("trace2").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_resources_and_scopes_are_deduplicated() {
+        let storage = Storage::new_in_memory().unwrap();
+
+        let mut attrs = Attributes::new();
+        attrs.insert("service.name", "test-service");
+        attrs.insert("service.version", "1.2.3");
+        attrs.insert("deployment.environment", "staging");
+        let resource = Resource::new(attrs);
+        let scope = InstrumentationScope::new(
+            "http".to_string(),
+            Some("0.1.0".to_string()),
+            Attributes::new(),
+        );
+
+        for span_id in ["span1", "span2"] {
+            let span = create_test_span(span_id, "trace1")
+                .with_resource(resource.clone())
+                .with_scope(scope.clone());
+            storage.insert_span(&span).unwrap();
+        }
+        let log = Log::new(
+            1_000_000_000,
+            crate::models::SeverityLevel::Info,
+            None,
+            "Test log".to_string(),
+            Attributes::new(),
+            None,
+            None,
+            Some("test-service".to_string()),
+        )
+        .with_resource(resource.clone())
+        .with_scope(InstrumentationScope::new(
+            "logger".to_string(),
+            None,
+            Attributes::new(),
+        ));
+        storage.insert_log(&log).unwrap();
+        let metric = Metric::gauge(
+            "cpu".to_string(),
+            vec![MetricDataPoint::new(
+                1_000_000_000,
+                None,
+                0.5,
+                Attributes::new(),
+            )],
+            Some("test-service".to_string()),
+        )
+        .with_resource(resource.clone());
+        storage.insert_metric(&metric).unwrap();
+
+        {
+            let conn = storage.conn.lock().unwrap();
+            let resources: i64 = conn
+                .query_row("SELECT COUNT(*) FROM resources", [], |row| row.get(0))
+                .unwrap();
+            let scopes: i64 = conn
+                .query_row("SELECT COUNT(*) FROM scopes", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(resources, 1);
+            assert_eq!(scopes, 2);
+        }
+
+        let trace = storage.get_trace_by_id("trace1").unwrap();
+        assert_eq!(trace.spans[0].resource, Some(resource.clone()));
+        assert_eq!(trace.spans[0].scope, Some(scope));
+        assert_eq!(
+            trace.spans[1]
+                .resource
+                .as_ref()
+                .and_then(|r| r.service_version()),
+            Some("1.2.3")
+        );
+
+        let logs = storage.list_logs(None, None).unwrap();
+        assert_eq!(logs[0].resource, Some(resource.clone()));
+        assert_eq!(logs[0].scope.as_ref().unwrap().name, "logger");
+        assert_eq!(logs[0].scope.as_ref().unwrap().version, None);
+
+        let metrics = storage.list_metrics(None, None).unwrap();
+        assert_eq!(metrics[0].resource, Some(resource));
+        assert_eq!(metrics[0].scope, None);
+    }
+
+    #[test]
+    fn test_nested_attributes_round_trip() {
+        let storage = Storage::new_in_memory().unwrap();
+
+        let mut user = Attributes::new();
+        user.insert("id", 42i64);
+        user.insert("name", "ada");
+        let mut attrs = Attributes::new();
+        attrs.insert("user", user);
+        attrs.insert("method", "GET");
+
+        let mut span = create_test_span("span1", "trace1");
+        span.attributes = attrs.clone();
+        storage.insert_span(&span).unwrap();
+
+        let metric = Metric::gauge(
+            "queue.depth".to_string(),
+            vec![MetricDataPoint::new(
+                1_000_000_000,
+                None,
+                3.0,
+                attrs.clone(),
+            )],
+            None,
+        );
+        storage.insert_metric(&metric).unwrap();
+
+        let trace = storage.get_trace_by_id("trace1").unwrap();
+        assert_eq!(trace.spans[0].attributes, attrs);
+
+        let metrics = storage.list_metrics(None, None).unwrap();
+        assert_eq!(metrics[0].data_points[0].attributes, attrs);
+    }
+
+    #[test]
+    fn test_insert_and_list_histogram() {
+        let storage = Storage::new_in_memory().unwrap();
+        let histogram = HistogramData {
+            count: 6,
+            sum: Some(123.5),
+            min: Some(1.5),
+            max: Some(80.0),
+            explicit_bounds: vec![5.0, 25.0, 50.0],
+            bucket_counts: vec![1, 3, 1, 1],
+        };
+        let metric = Metric::new(
+            "http.server.duration".to_string(),
+            None,
+            Some("ms".to_string()),
+            MetricType::Histogram,
+            AggregationTemporality::Delta,
+            vec![
+                MetricDataPoint::new(1_000_000_000, None, 123.5, Attributes::new())
+                    .with_histogram(histogram.clone()),
+            ],
+            None,
+        );
+        storage.insert_metric(&metric).unwrap();
+        storage
+            .insert_metric(&Metric::gauge(
+                "cpu".to_string(),
+                vec![MetricDataPoint::new(
+                    2_000_000_000,
+                    None,
+                    0.5,
+                    Attributes::new(),
+                )],
+                None,
+            ))
+            .unwrap();
+
+        let metrics = storage.list_metrics(None, None).unwrap();
+        assert_eq!(metrics[0].data_points[0].histogram, None);
+        assert_eq!(metrics[1].data_points[0].histogram, Some(histogram));
+    }
+
+    #[test]
+    fn test_insert_and_list_exponential_histogram() {
+        let storage = Storage::new_in_memory().unwrap();
+        let exponential = ExponentialHistogramData {
+            count: 5,
+            sum: Some(9.5),
+            min: Some(-1.5),
+            max: Some(6.0),
+            scale: 2,
+            zero_count: 1,
+            zero_threshold: 0.001,
+            positive: ExponentialBuckets {
+                offset: -1,
+                bucket_counts: vec![1, 0, 2],
+            },
+            negative: ExponentialBuckets {
+                offset: 1,
+                bucket_counts: vec![1],
+            },
+        };
+        let metric = Metric::new(
+            "rpc.server.duration".to_string(),
+            None,
+            None,
+            MetricType::ExponentialHistogram,
+            AggregationTemporality::Cumulative,
+            vec![
+                MetricDataPoint::new(1_000_000_000, None, 9.5, Attributes::new())
+                    .with_exponential_histogram(exponential.clone()),
+            ],
+            None,
+        );
+        storage.insert_metric(&metric).unwrap();
+
+        let metrics = storage.list_metrics(None, None).unwrap();
+        assert_eq!(metrics[0].metric_type, MetricType::ExponentialHistogram);
+        let dp = &metrics[0].data_points[0];
+        assert_eq!(dp.exponential_histogram, Some(exponential.clone()));
+        assert_eq!(dp.histogram, Some(exponential.to_explicit()));
+    }
+
+    #[test]
+    fn test_insert_and_list_summary() {
+        let storage = Storage::new_in_memory().unwrap();
+        let summary = SummaryData {
+            count: 200,
+            sum: 5_000.0,
+            quantile_values: vec![
+                QuantileValue {
+                    quantile: 0.5,
+                    value: 21.0,
+                },
+                QuantileValue {
+                    quantile: 0.9,
+                    value: 48.0,
+                },
+                QuantileValue {
+                    quantile: 0.99,
+                    value: 97.5,
+                },
+            ],
+        };
+        let metric = Metric::new(
+            "rpc_duration_seconds".to_string(),
+            None,
+            None,
+            MetricType::Summary,
+            AggregationTemporality::Unspecified,
+            vec![
+                MetricDataPoint::new(1_000_000_000, None, 5_000.0, Attributes::new())
+                    .with_summary(summary.clone()),
+            ],
+            None,
+        );
+        storage.insert_metric(&metric).unwrap();
+
+        let metrics = storage.list_metrics(None, None).unwrap();
+        let dp = &metrics[0].data_points[0];
+        assert_eq!(dp.summary, Some(summary));
+        assert_eq!(dp.histogram, None);
+        assert_eq!(dp.exponential_histogram, None);
+    }
+
+    #[test]
+    fn test_insert_and_list_exemplars() {
+        let storage = Storage::new_in_memory().unwrap();
+        let mut filtered = Attributes::new();
+        filtered.insert("http.route", "/checkout");
+        let metric = Metric::new(
+            "http.server.duration".to_string(),
+            None,
+            Some("ms".to_string()),
+            MetricType::Gauge,
+            AggregationTemporality::Unspecified,
+            vec![
+                MetricDataPoint::new(2_000_000_000, None, 950.0, Attributes::new()).with_exemplars(
+                    vec![
+                        Exemplar::new(1_500_000_000, 950.0)
+                            .with_trace("trace123".to_string(), "span456".to_string())
+                            .with_filtered_attributes(filtered.clone()),
+                        Exemplar::new(1_800_000_000, 12.0),
+                        Exemplar::new(1_900_000_000, f64::NAN),
+                    ],
+                ),
+                MetricDataPoint::new(1_000_000_000, None, 10.0, Attributes::new()),
+            ],
+            None,
+        );
+        storage.insert_metric(&metric).unwrap();
+
+        let metrics = storage.list_metrics(None, None).unwrap();
+        assert_eq!(metrics.len(), 2);
+
+        let exemplars = &metrics[0].data_points[0].exemplars;
+        assert_eq!(exemplars.len(), 2);
+        assert_eq!(exemplars[0].trace_id.as_deref(), Some("trace123"));
+        assert_eq!(exemplars[0].span_id.as_deref(), Some("span456"));
+        assert_eq!(exemplars[0].filtered_attributes, filtered);
+        assert_eq!(exemplars[1].trace_id, None);
+        assert!(metrics[1].data_points[0].exemplars.is_empty());
+    }
+
     #[test]
     fn test_insert_and_list_logs() {
         let storage = Storage::new_in_memory().unwrap();
@@ -566,6 +1560,183 @@ mod tests {
         assert_eq!(logs[0].body, "Test log");
     }
 
+    #[test]
+    fn test_insert_logs_skips_duplicates() {
+        let storage = Storage::new_in_memory().unwrap();
+        let log = |time_unix_nano: i64| {
+            let mut attributes = Attributes::new();
+            attributes.insert("user.id", "42");
+            attributes.insert("http.route", "/login");
+            Log::new(
+                time_unix_nano,
+                crate::models::SeverityLevel::Warn,
+                None,
+                "Slow login".to_string(),
+                attributes,
+                None,
+                None,
+                Some("test-service".to_string()),
+            )
+            .with_observed_time(time_unix_nano + 1_000)
+        };
+
+        let outcome = storage
+            .insert_logs(&[log(1_000_000_000), log(2_000_000_000)])
+            .unwrap();
+        assert_eq!(outcome.rejected(), 0);
+        assert_eq!(outcome.stored, 2);
+
+        // The whole batch is retried
+        let outcome = storage
+            .insert_logs(&[log(1_000_000_000), log(2_000_000_000)])
+            .unwrap();
+        assert_eq!(outcome.rejected(), 0);
+        assert_eq!(outcome.stored, 0);
+
+        assert_eq!(storage.count_logs().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_insert_logs_keeps_repeated_lines() {
+        let storage = Storage::new_in_memory().unwrap();
+        let log = Log::new(
+            1_000_000_000,
+            crate::models::SeverityLevel::Info,
+            None,
+            "Retrying connection".to_string(),
+            Attributes::new(),
+            None,
+            None,
+            Some("test-service".to_string()),
+        );
+
+        // The same line printed twice in the same second, e.g. from a file or syslog
+        let outcome = storage.insert_logs(&[log.clone(), log.clone()]).unwrap();
+        assert_eq!(outcome.rejected(), 0);
+        assert_eq!(storage.count_logs().unwrap(), 2);
+
+        // An export holding two identical records observed at once
+        let log = log.with_observed_time(1_000_000_000);
+        let outcome = storage.insert_logs(&[log.clone(), log]).unwrap();
+        assert_eq!(outcome.rejected(), 0);
+        assert_eq!(storage.count_logs().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_insert_metrics_skips_duplicate_points() {
+        let storage = Storage::new_in_memory().unwrap();
+        let metric = |points: &[(i64, f64)]| {
+            Metric::new(
+                "http.requests".to_string(),
+                None,
+                None,
+                MetricType::Sum,
+                AggregationTemporality::Cumulative,
+                points
+                    .iter()
+                    .map(|&(time, value)| {
+                        MetricDataPoint::new(time, Some(0), value, Attributes::new())
+                            .with_exemplars(vec![Exemplar::new(time, value)])
+                    })
+                    .collect(),
+                Some("test-service".to_string()),
+            )
+        };
+
+        storage
+            .insert_metric(&metric(&[(1_000_000_000, 10.0), (2_000_000_000, 25.0)]))
+            .unwrap();
+        // A retry resends the first point along with a new one
+        storage
+            .insert_metric(&metric(&[(1_000_000_000, 10.0), (3_000_000_000, 40.0)]))
+            .unwrap();
+
+        let metrics = storage.list_metrics(None, None).unwrap();
+        let values: Vec<f64> = metrics.iter().map(|m| m.data_points[0].value).collect();
+        assert_eq!(values, vec![40.0, 25.0, 10.0]);
+        assert!(
+            metrics
+                .iter()
+                .all(|m| m.data_points[0].exemplars.len() == 1)
+        );
+    }
+
+    #[test]
+    fn test_insert_metric_skips_points_without_timestamp() {
+        let storage = Storage::new_in_memory().unwrap();
+        let metric = Metric::new(
+            "queue.depth".to_string(),
+            None,
+            None,
+            MetricType::Gauge,
+            AggregationTemporality::Unspecified,
+            vec![
+                MetricDataPoint::new(1_000_000_000, None, 3.0, Attributes::new()),
+                MetricDataPoint::new(0, None, 4.0, Attributes::new()),
+            ],
+            None,
+        );
+
+        storage.insert_metric(&metric).unwrap();
+
+        let metrics = storage.list_metrics(None, None).unwrap();
+        assert_eq!(metrics.len(), 1);
+        assert_eq!(metrics[0].data_points[0].value, 3.0);
+    }
+
+    #[test]
+    fn test_insert_metric_skips_non_finite_points() {
+        let storage = Storage::new_in_memory().unwrap();
+        let metric = Metric::new(
+            "queue.depth".to_string(),
+            None,
+            None,
+            MetricType::Gauge,
+            AggregationTemporality::Unspecified,
+            vec![
+                MetricDataPoint::new(1_000_000_000, None, f64::NAN, Attributes::new()),
+                MetricDataPoint::new(2_000_000_000, None, f64::INFINITY, Attributes::new()),
+                MetricDataPoint::new(3_000_000_000, None, 5.0, Attributes::new()),
+            ],
+            None,
+        );
+
+        storage.insert_metric(&metric).unwrap();
+
+        let metrics = storage.list_metrics(None, None).unwrap();
+        assert_eq!(metrics[0].data_points.len(), 1);
+        assert_eq!(metrics[0].data_points[0].value, 5.0);
+    }
+
+    #[test]
+    fn test_insert_metrics_keeps_resent_correction() {
+        let storage = Storage::new_in_memory().unwrap();
+        let metric = |value: f64| {
+            Metric::new(
+                "queue.depth".to_string(),
+                None,
+                None,
+                MetricType::Gauge,
+                AggregationTemporality::Unspecified,
+                vec![
+                    MetricDataPoint::new(1_000_000_000, None, value, Attributes::new())
+                        .with_exemplars(vec![Exemplar::new(1_000_000_000, value)]),
+                ],
+                Some("test-service".to_string()),
+            )
+        };
+
+        storage.insert_metric(&metric(7.0)).unwrap();
+        storage.insert_metric(&metric(9.0)).unwrap();
+
+        let metrics = storage.list_metrics(None, None).unwrap();
+        assert_eq!(metrics.len(), 1);
+        let point = &metrics[0].data_points[0];
+        assert_eq!(point.value, 9.0);
+        assert_eq!(point.exemplars.len(), 1);
+        assert_eq!(point.exemplars[0].value, 9.0);
+    }
+
     #[test]
     fn test_get_nonexistent_trace() {
         let storage = Storage::new_in_memory().unwrap();
@@ -1,13 +1,78 @@
 use rusqlite::Connection;
 
+const RESOURCES_SCHEMA: &str = include_str!("sql/resources.sql");
 const SPANS_SCHEMA: &str = include_str!("sql/spans.sql");
+const SPAN_EVENTS_SCHEMA: &str = include_str!("sql/span_events.sql");
+const SPAN_LINKS_SCHEMA: &str = include_str!("sql/span_links.sql");
 const LOGS_SCHEMA: &str = include_str!("sql/logs.sql");
 const METRICS_SCHEMA: &str = include_str!("sql/metrics.sql");
+const METRIC_EXEMPLARS_SCHEMA: &str = include_str!("sql/metric_exemplars.sql");
+const DEDUP_INDEXES: &str = include_str!("sql/dedup.sql");
+
+/// Columns added after the first release, which `CREATE TABLE IF NOT EXISTS` won't add to
+/// databases created before them
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("spans", "resource_id", "INTEGER REFERENCES resources(id)"),
+    ("spans", "scope_id", "INTEGER REFERENCES scopes(id)"),
+    ("spans", "completeness", "INTEGER NOT NULL DEFAULT 0"),
+    ("logs", "resource_id", "INTEGER REFERENCES resources(id)"),
+    ("logs", "scope_id", "INTEGER REFERENCES scopes(id)"),
+    ("logs", "fingerprint", "TEXT"),
+    ("metrics", "resource_id", "INTEGER REFERENCES resources(id)"),
+    ("metrics", "scope_id", "INTEGER REFERENCES scopes(id)"),
+    ("metrics", "histogram_count", "INTEGER"),
+    ("metrics", "histogram_sum", "REAL"),
+    ("metrics", "histogram_min", "REAL"),
+    ("metrics", "histogram_max", "REAL"),
+    ("metrics", "explicit_bounds", "TEXT"),
+    ("metrics", "bucket_counts", "TEXT"),
+    ("metrics", "scale", "INTEGER"),
+    ("metrics", "zero_count", "INTEGER"),
+    ("metrics", "zero_threshold", "REAL"),
+    ("metrics", "positive_offset", "INTEGER"),
+    ("metrics", "positive_bucket_counts", "TEXT"),
+    ("metrics", "negative_offset", "INTEGER"),
+    ("metrics", "negative_bucket_counts", "TEXT"),
+    ("metrics", "quantile_values", "TEXT"),
+    ("metrics", "fingerprint", "TEXT"),
+];
 
 pub fn init_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.execute_batch(RESOURCES_SCHEMA)?;
     conn.execute_batch(SPANS_SCHEMA)?;
+    conn.execute_batch(SPAN_EVENTS_SCHEMA)?;
+    conn.execute_batch(SPAN_LINKS_SCHEMA)?;
     conn.execute_batch(LOGS_SCHEMA)?;
     conn.execute_batch(METRICS_SCHEMA)?;
+    conn.execute_batch(METRIC_EXEMPLARS_SCHEMA)?;
+
+    for (table, column, definition) in ADDED_COLUMNS {
+        add_column_if_missing(conn, table, column, definition)?;
+    }
+
+    // Indexes on added columns can only be created once the columns exist
+    conn.execute_batch(DEDUP_INDEXES)?;
+
+    Ok(())
+}
+
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> rusqlite::Result<()> {
+    let exists: bool = conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+        [table, column],
+        |row| row.get(0),
+    )?;
+
+    if !exists {
+        conn.execute_batch(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+        ))?;
+    }
 
     Ok(())
 }
@@ -31,8 +96,45 @@ mod tests {
             .unwrap();
 
         assert!(tables.contains(&"spans".to_string()));
+        assert!(tables.contains(&"span_events".to_string()));
+        assert!(tables.contains(&"span_links".to_string()));
         assert!(tables.contains(&"logs".to_string()));
         assert!(tables.contains(&"metrics".to_string()));
+        assert!(tables.contains(&"metric_exemplars".to_string()));
+        assert!(tables.contains(&"resources".to_string()));
+        assert!(tables.contains(&"scopes".to_string()));
+    }
+
+    #[test]
+    fn test_init_schema_upgrades_old_tables() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE logs (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                time_unix_nano INTEGER NOT NULL,
+                severity_level TEXT NOT NULL,
+                severity_text TEXT,
+                body TEXT NOT NULL,
+                attributes TEXT NOT NULL,
+                trace_id TEXT,
+                span_id TEXT,
+                service_name TEXT
+            );",
+        )
+        .unwrap();
+
+        init_schema(&conn).unwrap();
+
+        let columns: Vec<String> = conn
+            .prepare("SELECT name FROM pragma_table_info('logs')")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<_>>>()
+            .unwrap();
+
+        assert!(columns.contains(&"resource_id".to_string()));
+        assert!(columns.contains(&"scope_id".to_string()));
     }
 
     #[test]
@@ -0,0 +1,2 @@
+CREATE UNIQUE INDEX IF NOT EXISTS idx_logs_fingerprint ON logs(fingerprint);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_metrics_fingerprint ON metrics(fingerprint);
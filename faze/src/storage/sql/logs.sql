@@ -7,7 +7,10 @@ CREATE TABLE IF NOT EXISTS logs (
     attributes TEXT NOT NULL,
     trace_id TEXT,
     span_id TEXT,
-    service_name TEXT
+    service_name TEXT,
+    resource_id INTEGER REFERENCES resources(id),
+    scope_id INTEGER REFERENCES scopes(id),
+    fingerprint TEXT
 );
 
 CREATE INDEX IF NOT EXISTS idx_logs_time ON logs(time_unix_nano);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS metric_exemplars (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    metric_id INTEGER NOT NULL REFERENCES metrics(id),
+    time_unix_nano INTEGER NOT NULL,
+    value REAL NOT NULL,
+    trace_id TEXT,
+    span_id TEXT,
+    filtered_attributes TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_metric_exemplars_metric_id ON metric_exemplars(metric_id);
+CREATE INDEX IF NOT EXISTS idx_metric_exemplars_trace_id ON metric_exemplars(trace_id);
@@ -9,7 +9,24 @@ CREATE TABLE IF NOT EXISTS metrics (
     start_time_unix_nano INTEGER,
     value REAL NOT NULL,
     attributes TEXT NOT NULL,
-    service_name TEXT
+    service_name TEXT,
+    resource_id INTEGER REFERENCES resources(id),
+    scope_id INTEGER REFERENCES scopes(id),
+    histogram_count INTEGER,
+    histogram_sum REAL,
+    histogram_min REAL,
+    histogram_max REAL,
+    explicit_bounds TEXT,
+    bucket_counts TEXT,
+    scale INTEGER,
+    zero_count INTEGER,
+    zero_threshold REAL,
+    positive_offset INTEGER,
+    positive_bucket_counts TEXT,
+    negative_offset INTEGER,
+    negative_bucket_counts TEXT,
+    quantile_values TEXT,
+    fingerprint TEXT
 );
 
 CREATE INDEX IF NOT EXISTS idx_metrics_name ON metrics(name);
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS resources (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    attributes TEXT NOT NULL UNIQUE
+);
+
+CREATE TABLE IF NOT EXISTS scopes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    fingerprint TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    version TEXT,
+    attributes TEXT NOT NULL
+);
@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS span_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    span_id TEXT NOT NULL,
+    trace_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    time_unix_nano INTEGER NOT NULL,
+    attributes TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_span_events_trace_id ON span_events(trace_id);
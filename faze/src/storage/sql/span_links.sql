@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS span_links (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    span_id TEXT NOT NULL,
+    trace_id TEXT NOT NULL,
+    linked_trace_id TEXT NOT NULL,
+    linked_span_id TEXT NOT NULL,
+    trace_state TEXT,
+    attributes TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_span_links_trace_id ON span_links(trace_id);
+CREATE INDEX IF NOT EXISTS idx_span_links_linked_trace_id ON span_links(linked_trace_id);
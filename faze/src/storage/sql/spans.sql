@@ -9,6 +9,9 @@ CREATE TABLE IF NOT EXISTS spans (
     attributes TEXT NOT NULL,
     status TEXT NOT NULL,
     service_name TEXT,
+    resource_id INTEGER REFERENCES resources(id),
+    scope_id INTEGER REFERENCES scopes(id),
+    completeness INTEGER NOT NULL DEFAULT 0,
     PRIMARY KEY (span_id, trace_id)
 );
 
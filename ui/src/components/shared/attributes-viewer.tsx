@@ -28,11 +28,17 @@ function renderAttributeValue(value: AttributeValue): string {
         return `[${value.value.length} bytes]`;
       case "array":
         return `[${value.value.length} items]`;
+      case "map":
+        return JSON.stringify(value.value);
       default:
         return "unknown";
     }
   }
 
+  if (value && typeof value === "object" && !Array.isArray(value)) {
+    return JSON.stringify(value);
+  }
+
   return String(value);
 }
 
@@ -4,6 +4,17 @@ export type AttributeValue =
   | { type: "double"; value: number }
   | { type: "bool"; value: boolean }
   | { type: "bytes"; value: number[] }
-  | { type: "array"; value: AttributeValue[] };
+  | { type: "array"; value: AttributeValue[] }
+  | { type: "map"; value: Attributes };
 
 export type Attributes = Record<string, AttributeValue>;
+
+export interface Resource {
+  attributes: Attributes;
+}
+
+export interface InstrumentationScope {
+  name: string;
+  version?: string;
+  attributes: Attributes;
+}
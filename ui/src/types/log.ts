@@ -1,4 +1,4 @@
-import type { Attributes } from "./common";
+import type { Attributes, InstrumentationScope, Resource } from "./common";
 
 export type SeverityLevel =
   | "Unspecified"
@@ -36,6 +36,8 @@ export interface Log {
   trace_id?: string;
   span_id?: string;
   service_name?: string;
+  resource?: Resource;
+  scope?: InstrumentationScope;
 }
 
 export interface LogFilters {
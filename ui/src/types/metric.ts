@@ -1,14 +1,68 @@
-import type { Attributes } from "./common";
+import type { Attributes, InstrumentationScope, Resource } from "./common";
 
-export type MetricType = "Gauge" | "Sum" | "Histogram" | "Summary";
+export type MetricType =
+  | "Gauge"
+  | "Sum"
+  | "Histogram"
+  | "ExponentialHistogram"
+  | "Summary";
 
 export type AggregationTemporality = "Unspecified" | "Delta" | "Cumulative";
 
+export interface HistogramData {
+  count: number;
+  sum?: number;
+  min?: number;
+  max?: number;
+  explicit_bounds: number[];
+  bucket_counts: number[];
+}
+
+export interface ExponentialBuckets {
+  offset: number;
+  bucket_counts: number[];
+}
+
+export interface ExponentialHistogramData {
+  count: number;
+  sum?: number;
+  min?: number;
+  max?: number;
+  scale: number;
+  zero_count: number;
+  zero_threshold: number;
+  positive: ExponentialBuckets;
+  negative: ExponentialBuckets;
+}
+
+export interface QuantileValue {
+  quantile: number;
+  value: number;
+}
+
+export interface SummaryData {
+  count: number;
+  sum: number;
+  quantile_values: QuantileValue[];
+}
+
+export interface Exemplar {
+  time_unix_nano: number;
+  value: number;
+  trace_id?: string;
+  span_id?: string;
+  filtered_attributes: Attributes;
+}
+
 export interface MetricDataPoint {
   time_unix_nano: number;
   start_time_unix_nano?: number;
   value: number;
   attributes: Attributes;
+  histogram?: HistogramData;
+  exponential_histogram?: ExponentialHistogramData;
+  summary?: SummaryData;
+  exemplars?: Exemplar[];
 }
 
 export interface Metric {
@@ -19,6 +73,8 @@ export interface Metric {
   temporality: AggregationTemporality;
   data_points: MetricDataPoint[];
   service_name?: string;
+  resource?: Resource;
+  scope?: InstrumentationScope;
 }
 
 export interface MetricFilters {
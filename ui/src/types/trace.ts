@@ -1,4 +1,4 @@
-import type { Attributes } from "./common";
+import type { Attributes, InstrumentationScope, Resource } from "./common";
 
 export type SpanKind =
   | "Unspecified"
@@ -15,6 +15,19 @@ export interface Status {
   message?: string;
 }
 
+export interface SpanEvent {
+  name: string;
+  time_unix_nano: number;
+  attributes: Attributes;
+}
+
+export interface SpanLink {
+  trace_id: string;
+  span_id: string;
+  trace_state?: string;
+  attributes: Attributes;
+}
+
 export interface Span {
   span_id: string;
   trace_id: string;
@@ -26,6 +39,10 @@ export interface Span {
   attributes: Attributes;
   status: Status;
   service_name?: string;
+  events: SpanEvent[];
+  links: SpanLink[];
+  resource?: Resource;
+  scope?: InstrumentationScope;
 }
 
 export interface Trace {
@@ -45,6 +62,22 @@ export interface TraceInfo {
   root_span_kind?: SpanKind;
 }
 
+export type LinkDirection = "outgoing" | "incoming";
+
+export interface TraceLink {
+  direction: LinkDirection;
+  span_id: string;
+  linked_trace_id: string;
+  linked_span_id: string;
+  trace_state?: string;
+  attributes: Attributes;
+  linked_trace?: TraceInfo;
+}
+
+export interface TraceLinksResponse {
+  links: TraceLink[];
+}
+
 export interface TraceListResponse {
   traces: TraceInfo[];
   total: number;